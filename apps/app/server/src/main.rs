@@ -108,15 +108,53 @@ fn main() {
                 ClerkConfiguration::new(None, None, Some(get_env("CLERK_SECRET_KEY")), None);
             let clerk = Clerk::new(clerk_config);
 
-            let realtime_stt = hypr_stt::realtime::Client::builder()
-                .deepgram_api_key(get_env("DEEPGRAM_API_KEY"))
-                .clova_api_key(get_env("CLOVA_API_KEY"))
-                .build();
+            let stt_routing: hypr_stt::Routing = std::env::var("STT_ROUTING")
+                .map(|v| {
+                    v.parse()
+                        .unwrap_or_else(|e| panic!("env: 'STT_ROUTING' is invalid: {}", e))
+                })
+                .unwrap_or_default();
+
+            let rtzr = match (
+                std::env::var("RTZR_CLIENT_ID"),
+                std::env::var("RTZR_CLIENT_SECRET"),
+            ) {
+                (Ok(client_id), Ok(client_secret)) => Some((client_id, client_secret)),
+                _ => None,
+            };
 
-            let recorded_stt = hypr_stt::recorded::Client::builder()
-                .deepgram_api_key(get_env("DEEPGRAM_API_KEY"))
-                .clova_api_key(get_env("CLOVA_API_KEY"))
-                .build();
+            // Whisper and RTZR are optional, languages routed to them fall back to the next provider without them.
+            let realtime_stt = {
+                let mut builder = hypr_stt::realtime::Client::builder()
+                    .deepgram_api_key(get_env("DEEPGRAM_API_KEY"))
+                    .clova_api_key(get_env("CLOVA_API_KEY"))
+                    .routing(stt_routing.clone());
+
+                if let (Ok(api_base), Ok(api_key)) = (
+                    std::env::var("WHISPER_API_BASE"),
+                    std::env::var("WHISPER_API_KEY"),
+                ) {
+                    builder = builder.whisper(api_base, api_key);
+                }
+                if let Some((client_id, client_secret)) = rtzr.clone() {
+                    builder = builder.rtzr(client_id, client_secret);
+                }
+
+                builder.build()
+            };
+
+            let recorded_stt = {
+                let mut builder = hypr_stt::recorded::Client::builder()
+                    .deepgram_api_key(get_env("DEEPGRAM_API_KEY"))
+                    .clova_api_key(get_env("CLOVA_API_KEY"))
+                    .routing(stt_routing);
+
+                if let Some((client_id, client_secret)) = rtzr {
+                    builder = builder.rtzr(client_id, client_secret);
+                }
+
+                builder.build()
+            };
 
             let admin_db = {
                 let base_db = {
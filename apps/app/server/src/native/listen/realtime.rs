@@ -5,14 +5,18 @@ use axum::{
         ws::{Message, WebSocket, WebSocketUpgrade},
         Query, State,
     },
+    http::StatusCode,
     response::IntoResponse,
 };
 
 use bytes::Bytes;
 use futures_util::{SinkExt, StreamExt};
 
-use hypr_listener_interface::{ListenInputChunk, ListenOutputChunk, ListenParams};
-use hypr_stt::realtime::RealtimeSpeechToText;
+use hypr_audio_utils::{f32_to_i16_le_bytes, OpusDecoder};
+use hypr_listener_interface::{
+    AudioFormat, ListenInputChunk, ListenOutputChunk, ListenParams, AUDIO_FORMAT_HEADER,
+};
+use hypr_stt::realtime::{MultiClient, RealtimeSpeechToText};
 
 use crate::state::STTState;
 
@@ -20,19 +24,49 @@ pub async fn handler(
     Query(params): Query<ListenParams>,
     ws: WebSocketUpgrade,
     State(state): State<STTState>,
-) -> impl IntoResponse {
-    ws.on_upgrade(|socket| websocket(socket, state, params))
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    // Resolved before upgrading, so the client gets a status it can act on instead of a socket
+    // that closes right away.
+    let stt = state
+        .realtime_stt
+        .for_language(params.language.clone())
+        .await
+        .map_err(|e| {
+            tracing::error!("stt_unavailable: {:?}", e);
+            match e {
+                hypr_stt::Error::UnsupportedLanguage(_) => (StatusCode::BAD_REQUEST, e.to_string()),
+                _ => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+            }
+        })?;
+    tracing::info!("stt_provider: {}", stt.provider());
+
+    // Lets the client know binary audio is understood. Without it, clients fall back to JSON.
+    let audio_format = [(AUDIO_FORMAT_HEADER, params.audio_format.as_str())];
+    Ok((
+        audio_format,
+        ws.on_upgrade(|socket| websocket(socket, stt, params)),
+    ))
 }
 
-async fn websocket(socket: WebSocket, state: STTState, params: ListenParams) {
-    tracing::info!("websocket_connected");
+async fn websocket(socket: WebSocket, mut stt: MultiClient, params: ListenParams) {
+    tracing::info!("websocket_connected: {:?}", params.resume_id);
 
     let (mut ws_sender, ws_receiver) = socket.split();
 
-    let mut stt = state.realtime_stt.for_language(params.language).await;
+    let decoder = match params.audio_format {
+        AudioFormat::Opus => match OpusDecoder::new(16 * 1000) {
+            Ok(decoder) => Some(decoder),
+            Err(e) => {
+                tracing::error!("opus_decoder_error: {:?}", e);
+                return;
+            }
+        },
+        AudioFormat::Json | AudioFormat::Pcm => None,
+    };
 
-    let input_stream =
-        futures_util::stream::try_unfold(ws_receiver, |mut ws_receiver| async move {
+    let input_stream = futures_util::stream::try_unfold(
+        (ws_receiver, decoder),
+        |(mut ws_receiver, mut decoder)| async move {
             match ws_receiver.next().await {
                 Some(Ok(Message::Text(data))) => {
                     let input: ListenInputChunk = serde_json::from_str(&data).unwrap();
@@ -40,16 +74,35 @@ async fn websocket(socket: WebSocket, state: STTState, params: ListenParams) {
                     match input {
                         ListenInputChunk::Audio { data } => {
                             let audio = Bytes::from(data);
-                            Ok::<Option<(Bytes, _)>, axum::Error>(Some((audio, ws_receiver)))
+                            Ok::<Option<(Bytes, _)>, axum::Error>(Some((
+                                audio,
+                                (ws_receiver, decoder),
+                            )))
                         }
                         ListenInputChunk::End => Ok::<Option<(Bytes, _)>, axum::Error>(None),
                     }
                 }
+                // Sent instead of JSON audio chunks when the client asked for `AudioFormat::Pcm` or `AudioFormat::Opus`.
+                Some(Ok(Message::Binary(data))) => {
+                    let audio = match decoder.as_mut() {
+                        Some(decoder) => match decoder.decode(&data) {
+                            Ok(samples) => f32_to_i16_le_bytes(&samples),
+                            Err(e) => {
+                                tracing::warn!("opus_decode_error: {:?}", e);
+                                Bytes::new()
+                            }
+                        },
+                        None => data,
+                    };
+                    Ok::<Option<(Bytes, _)>, axum::Error>(Some((audio, (ws_receiver, decoder))))
+                }
                 _ => Ok::<Option<(Bytes, _)>, axum::Error>(None),
             }
-        });
+        },
+    );
 
     let input_stream = Box::pin(input_stream);
+    let offset_ms = params.offset_ms;
 
     let _handle = tokio::spawn(async move {
         match stt.transcribe(input_stream).await {
@@ -58,7 +111,13 @@ async fn websocket(socket: WebSocket, state: STTState, params: ListenParams) {
                 while let Some(result) = transcript_stream.next().await {
                     match result {
                         Ok(data) => {
-                            let out: ListenOutputChunk = data.into();
+                            let mut out: ListenOutputChunk = data.into();
+                            // Providers count from the start of this connection, which starts `offset_ms` into the session.
+                            for word in out.words.iter_mut() {
+                                word.start_ms = word.start_ms.map(|ms| ms + offset_ms);
+                                word.end_ms = word.end_ms.map(|ms| ms + offset_ms);
+                            }
+
                             let msg = Message::Text(serde_json::to_string(&out).unwrap().into());
 
                             if let Err(e) = ws_sender.send(msg).await {
@@ -1,6 +1,6 @@
 use axum::{
     extract::{Query, State},
-    response::IntoResponse,
+    http::StatusCode,
     Json,
 };
 
@@ -12,11 +12,18 @@ use hypr_stt::recorded::{RecordedSpeech, RecordedSpeechToText};
 pub async fn handler(
     Query(params): Query<ListenParams>,
     State(state): State<STTState>,
-) -> impl IntoResponse {
-    let stt = state.recorded_stt.for_language(params.language).await;
+) -> Result<Json<String>, (StatusCode, String)> {
+    let stt = state
+        .recorded_stt
+        .for_language(params.language)
+        .await
+        .map_err(|e| match e {
+            hypr_stt::Error::UnsupportedLanguage(_) => (StatusCode::BAD_REQUEST, e.to_string()),
+            _ => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+        })?;
 
     let input = RecordedSpeech::File("TODO".into());
     let result = stt.transcribe(input).await.unwrap();
 
-    Json(result)
+    Ok(Json(result))
 }
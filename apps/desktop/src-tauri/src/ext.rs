@@ -55,6 +55,13 @@ impl<R: tauri::Runtime, T: tauri::Manager<R>> AppExt<R> for T {
                     tracing::error!("start_local_llm_server: {}", e);
                 }
             }
+
+            // Sessions recorded before the embedding model was downloaded, or since the last run.
+            if self.is_embedding_model_downloaded().await {
+                if let Err(e) = self.index_pending_embeddings().await {
+                    tracing::error!("index_pending_embeddings: {}", e);
+                }
+            }
         }
 
         Ok(())
@@ -9,6 +9,7 @@ import { useHypr } from "@/contexts";
 import { commands as analyticsCommands } from "@hypr/plugin-analytics";
 import { commands as connectorCommands } from "@hypr/plugin-connector";
 import { commands as dbCommands } from "@hypr/plugin-db";
+import { commands as localLlmCommands } from "@hypr/plugin-local-llm";
 import { commands as miscCommands } from "@hypr/plugin-misc";
 import { commands as templateCommands } from "@hypr/plugin-template";
 import Editor, { type TiptapEditor } from "@hypr/tiptap/editor";
@@ -297,7 +298,10 @@ export function useEnhanceMutation({
         session_id: sessionId,
       });
 
-      persistSession();
+      persistSession()
+        .then(() => localLlmCommands.isEmbeddingModelDownloaded())
+        .then((downloaded) => downloaded && localLlmCommands.indexSessionEmbeddings(sessionId))
+        .catch(console.error);
     },
     onError: (error) => {
       console.error("增强失败:", error);
@@ -21,6 +21,9 @@ export function useTranscript(sessionId: string | null) {
   ]);
 
   const [words, setWords] = useState<Word[]>([]);
+  const [partialWords, setPartialWords] = useState<Word[]>([]);
+  const [isDegraded, setIsDegraded] = useState(false);
+  const [isFailed, setIsFailed] = useState(false);
   const [selectedLanguage, setSelectedLanguage] = useState<string>("en");
 
   const existingWords = useQuery({
@@ -49,7 +52,19 @@ export function useTranscript(sessionId: string | null) {
 
     listenerEvents.sessionEvent.listen(({ payload }) => {
       if (payload.type === "words") {
-        setWords(payload.words as Word[]);
+        setWords((words) => [...words, ...(payload.words as Word[])]);
+        setPartialWords([]);
+      } else if (payload.type === "partialWords") {
+        setPartialWords(payload.words as Word[]);
+      } else if (payload.type === "transcriptionDegraded") {
+        setIsDegraded(true);
+        setPartialWords([]);
+      } else if (payload.type === "transcriptionRestored") {
+        setIsDegraded(false);
+      } else if (payload.type === "transcriptionFailed") {
+        setIsDegraded(false);
+        setIsFailed(true);
+        setPartialWords([]);
       }
     }).then((fn) => {
       unlisten = fn;
@@ -59,6 +74,9 @@ export function useTranscript(sessionId: string | null) {
       if (unlisten) {
         unlisten();
       }
+      setPartialWords([]);
+      setIsDegraded(false);
+      setIsFailed(false);
     };
   }, [ongoingSessionState.status, ongoingSessionState.sessionId, sessionId]);
 
@@ -67,8 +85,11 @@ export function useTranscript(sessionId: string | null) {
   };
 
   return {
-    words,
+    words: partialWords.length ? [...words, ...partialWords] : words,
+    partialWords,
     isLive,
+    isDegraded,
+    isFailed,
     selectedLanguage,
     handleLanguageChange,
   };
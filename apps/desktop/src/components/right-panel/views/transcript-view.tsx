@@ -34,7 +34,7 @@ export function TranscriptView() {
     isInactive: s.status === "inactive",
   }));
   const { showEmptyMessage, hasTranscript } = useTranscriptWidget(sessionId);
-  const { isLive, words } = useTranscript(sessionId);
+  const { isLive, isDegraded, isFailed, words } = useTranscript(sessionId);
 
   const editorRef = useRef<TranscriptEditorRef | null>(null);
 
@@ -70,11 +70,7 @@ export function TranscriptView() {
 
   const handleUpdate = (words: Word[]) => {
     if (!isLive) {
-      dbCommands.getSession({ id: sessionId! }).then((session) => {
-        if (session) {
-          dbCommands.upsertSession({ ...session, words });
-        }
-      });
+      dbCommands.replaceWords(sessionId!, words);
     }
   };
 
@@ -99,6 +95,8 @@ export function TranscriptView() {
                 <span className="text-xs font-medium text-red-600">实时</span>
               </div>
             )}
+            {isLive && isDegraded && <span className="text-xs font-medium text-amber-600">正在重新连接…</span>}
+            {isLive && isFailed && <span className="text-xs font-medium text-red-600">转录已中断</span>}
           </div>
         )}
         <div className="not-draggable flex items-center ">
@@ -129,6 +129,8 @@ export default function General() {
           .map((jargon) => jargon.trim())
           .filter(Boolean),
         save_recordings: v.saveRecordings ?? true,
+        mic_device: config.data.general.mic_device ?? null,
+        echo_cancellation: config.data.general.echo_cancellation ?? true,
       };
 
       await dbCommands.setConfig({
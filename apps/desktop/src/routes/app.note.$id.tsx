@@ -35,7 +35,8 @@ export const Route = createFileRoute("/app/note/$id")({
         }
 
         const { insert } = sessionsStore.getState();
-        insert(session);
+        // Sessions first seen in a list were stored without their words.
+        insert(session).setState({ session });
 
         return session;
       },
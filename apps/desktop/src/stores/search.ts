@@ -64,7 +64,7 @@ export const createSearchStore = (userId: string) => {
 
       try {
         const [sessions, events, humans, organizations] = await Promise.all([
-          dbCommands.listSessions({ type: "search", query, limit: 10, user_id: userId }),
+          dbCommands.listSessions({ type: "fullText", query, limit: 10, user_id: userId }),
           dbCommands.listEvents({ type: "search", query, limit: 5, user_id: userId }),
           dbCommands.listHumans({ search: [3, query] }),
           dbCommands.listOrganizations({ search: [3, query] }),
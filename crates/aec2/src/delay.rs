@@ -0,0 +1,103 @@
+use std::collections::VecDeque;
+
+// Envelopes are compared at 2kHz, plenty for locating the echo within the filter tail.
+const DECIMATED_RATE: usize = 2000;
+const MIN_CORRELATION: f32 = 0.3;
+
+/// Estimates how far the echo in the mic lags behind the reference, by cross-correlating the
+/// amplitude envelopes of both signals over a sliding window.
+pub struct DelayEstimator {
+    decimation: usize,
+    max_lag: usize,
+    window: usize,
+    mic: VecDeque<f32>,
+    reference: VecDeque<f32>,
+    mic_block: Vec<f32>,
+    reference_block: Vec<f32>,
+}
+
+impl DelayEstimator {
+    pub fn new(sample_rate: usize, max_lag_ms: usize, window_ms: usize) -> Self {
+        let decimation = (sample_rate / DECIMATED_RATE).max(1);
+        let decimated_rate = sample_rate / decimation;
+
+        Self {
+            decimation,
+            max_lag: max_lag_ms * decimated_rate / 1000,
+            window: window_ms * decimated_rate / 1000,
+            mic: VecDeque::new(),
+            reference: VecDeque::new(),
+            mic_block: Vec::with_capacity(decimation),
+            reference_block: Vec::with_capacity(decimation),
+        }
+    }
+
+    pub fn push(&mut self, mic: &[f32], reference: &[f32]) {
+        for (&m, &r) in mic.iter().zip(reference.iter()) {
+            self.mic_block.push(m.abs());
+            self.reference_block.push(r.abs());
+
+            if self.mic_block.len() == self.decimation {
+                let n = self.decimation as f32;
+                self.mic.push_back(self.mic_block.iter().sum::<f32>() / n);
+                self.reference
+                    .push_back(self.reference_block.iter().sum::<f32>() / n);
+                self.mic_block.clear();
+                self.reference_block.clear();
+            }
+        }
+
+        let capacity = self.window + 2 * self.max_lag;
+        while self.mic.len() > capacity {
+            self.mic.pop_front();
+            self.reference.pop_front();
+        }
+    }
+
+    /// Delay in samples at the input sample rate. Positive when the echo arrives after the reference.
+    /// `None` until there is enough history, or while the mic doesn't carry a recognizable echo.
+    pub fn estimate(&self) -> Option<isize> {
+        if self.mic.len() < self.window + 2 * self.max_lag {
+            return None;
+        }
+
+        let mic = centered(self.mic.iter().copied());
+        let reference = centered(self.reference.iter().copied());
+
+        // The mic window sits in the middle, so it can be compared against references on both sides of it.
+        let mic_window = &mic[self.max_lag..self.max_lag + self.window];
+        let mic_energy: f32 = mic_window.iter().map(|x| x * x).sum();
+
+        let mut best: Option<(isize, f32)> = None;
+
+        for offset in 0..=2 * self.max_lag {
+            let reference_window = &reference[offset..offset + self.window];
+            let reference_energy: f32 = reference_window.iter().map(|x| x * x).sum();
+
+            let denominator = (mic_energy * reference_energy).sqrt();
+            if denominator <= f32::EPSILON {
+                continue;
+            }
+
+            let correlation = mic_window
+                .iter()
+                .zip(reference_window.iter())
+                .map(|(m, r)| m * r)
+                .sum::<f32>()
+                / denominator;
+
+            if best.is_none_or(|(_, c)| correlation > c) {
+                best = Some((self.max_lag as isize - offset as isize, correlation));
+            }
+        }
+
+        best.filter(|(_, c)| *c >= MIN_CORRELATION)
+            .map(|(lag, _)| lag * self.decimation as isize)
+    }
+}
+
+fn centered(values: impl Iterator<Item = f32>) -> Vec<f32> {
+    let values: Vec<f32> = values.collect();
+    let mean = values.iter().sum::<f32>() / values.len() as f32;
+    values.into_iter().map(|v| v - mean).collect()
+}
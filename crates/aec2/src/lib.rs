@@ -1,5 +1,10 @@
+mod delay;
 mod error;
+mod stream;
+
+pub use delay::*;
 pub use error::*;
+pub use stream::*;
 
 #[derive(Default)]
 pub struct AECBuilder {
@@ -131,4 +136,36 @@ mod tests {
         }
         writer.finalize().unwrap();
     }
+
+    #[test]
+    fn test_delay_estimator() {
+        let sample_rate = 16000;
+        let delay = 1600;
+
+        // Noise bursts, so the envelope has structure to correlate against.
+        let mut seed = 42u32;
+        let reference: Vec<f32> = (0..sample_rate * 3)
+            .map(|i| {
+                seed = seed.wrapping_mul(1664525).wrapping_add(1013904223);
+                let noise = (seed >> 8) as f32 / (1 << 24) as f32 - 0.5;
+                if (i / 2000) % 3 == 0 {
+                    noise
+                } else {
+                    noise * 0.05
+                }
+            })
+            .collect();
+        let mic: Vec<f32> = std::iter::repeat_n(0.0, delay)
+            .chain(reference.iter().map(|s| s * 0.5))
+            .take(reference.len())
+            .collect();
+
+        let mut estimator = DelayEstimator::new(sample_rate, 250, 2000);
+        for (m, r) in mic.chunks(1024).zip(reference.chunks(1024)) {
+            estimator.push(m, r);
+        }
+
+        let estimate = estimator.estimate().unwrap();
+        assert!((estimate - delay as isize).abs() <= 16);
+    }
 }
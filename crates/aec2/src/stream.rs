@@ -0,0 +1,152 @@
+use std::collections::VecDeque;
+
+use crate::{DelayEstimator, AEC};
+
+const MAX_DELAY_MS: usize = 250;
+const ESTIMATION_WINDOW_MS: usize = 2000;
+// Leaves some of the filter tail in front of the echo, since the estimate is only as precise as the envelopes.
+const DELAY_MARGIN_MS: usize = 10;
+// Realigning disturbs the adaptive filter, so small drifts are left to the filter itself.
+const REALIGN_THRESHOLD_MS: usize = 5;
+
+/// Cancels echo from chunks of arbitrary length, as long as mic and reference chunks are paired.
+///
+/// The echo path delay is re-estimated continuously and compensated before the samples reach the
+/// filter. Output is always as long as the input, at the cost of one frame of latency.
+pub struct StreamingAEC {
+    aec: AEC,
+    frame_size: usize,
+    sample_rate: usize,
+    estimator: DelayEstimator,
+    samples_since_estimate: usize,
+    mic: Delayed,
+    reference: Delayed,
+    output: VecDeque<f32>,
+}
+
+impl StreamingAEC {
+    pub fn new(sample_rate: usize) -> Self {
+        let aec = AEC::builder().sample_rate(sample_rate).build();
+        let frame_size = aec.config.frame_size;
+
+        Self {
+            aec,
+            frame_size,
+            sample_rate,
+            estimator: DelayEstimator::new(sample_rate, MAX_DELAY_MS, ESTIMATION_WINDOW_MS),
+            samples_since_estimate: 0,
+            mic: Delayed::default(),
+            reference: Delayed::default(),
+            output: std::iter::repeat_n(0.0, frame_size).collect(),
+        }
+    }
+
+    pub fn process(&mut self, mic: &[f32], reference: &[f32]) -> Vec<f32> {
+        self.estimator.push(mic, reference);
+        self.samples_since_estimate += mic.len();
+
+        if self.samples_since_estimate >= self.ms_to_samples(ESTIMATION_WINDOW_MS) {
+            self.samples_since_estimate = 0;
+            if let Some(delay) = self.estimator.estimate() {
+                self.align(delay);
+            }
+        }
+
+        self.mic.extend(mic);
+        self.reference.extend(reference);
+
+        let mut input_frame = vec![0i16; self.frame_size];
+        let mut reference_frame = vec![0i16; self.frame_size];
+        let mut output_frame = vec![0i16; self.frame_size];
+
+        while self.mic.queue.len() >= self.frame_size
+            && self.reference.queue.len() >= self.frame_size
+        {
+            self.mic.read(&mut input_frame);
+            self.reference.read(&mut reference_frame);
+
+            self.aec
+                .inner
+                .cancel_echo(&input_frame, &reference_frame, &mut output_frame);
+            self.output
+                .extend(output_frame.iter().map(|&s| s as f32 / 32768.0));
+        }
+
+        (0..mic.len())
+            .map(|_| self.output.pop_front().unwrap_or(0.0))
+            .collect()
+    }
+
+    fn align(&mut self, delay: isize) {
+        let margin = self.ms_to_samples(DELAY_MARGIN_MS) as isize;
+        let target = delay - margin;
+
+        let (mic_delay, reference_delay) = if target >= 0 {
+            (0, target as usize)
+        } else {
+            ((-target) as usize, 0)
+        };
+
+        let threshold = self.ms_to_samples(REALIGN_THRESHOLD_MS);
+        if mic_delay.abs_diff(self.mic.delay) < threshold
+            && reference_delay.abs_diff(self.reference.delay) < threshold
+        {
+            return;
+        }
+
+        self.mic.set_delay(mic_delay);
+        self.reference.set_delay(reference_delay);
+    }
+
+    fn ms_to_samples(&self, ms: usize) -> usize {
+        self.sample_rate * ms / 1000
+    }
+}
+
+#[derive(Default)]
+struct Delayed {
+    queue: VecDeque<i16>,
+    delay: usize,
+    // Samples still to be dropped after the delay was shortened by more than what was queued.
+    skip: usize,
+}
+
+impl Delayed {
+    fn set_delay(&mut self, delay: usize) {
+        if delay > self.delay {
+            let mut added = delay - self.delay;
+
+            let absorbed = added.min(self.skip);
+            self.skip -= absorbed;
+            added -= absorbed;
+
+            for _ in 0..added {
+                self.queue.push_front(0);
+            }
+        } else {
+            let removed = self.delay - delay;
+            let dropped = removed.min(self.queue.len());
+            self.queue.drain(..dropped);
+            self.skip += removed - dropped;
+        }
+
+        self.delay = delay;
+    }
+
+    fn extend(&mut self, samples: &[f32]) {
+        let skipped = self.skip.min(samples.len());
+        self.skip -= skipped;
+        self.queue
+            .extend(samples[skipped..].iter().map(|&s| to_i16(s)));
+    }
+
+    fn read(&mut self, frame: &mut [i16]) {
+        for (dst, src) in frame.iter_mut().zip(self.queue.drain(..frame.len())) {
+            *dst = src;
+        }
+    }
+}
+
+fn to_i16(sample: f32) -> i16 {
+    (sample * i16::MAX as f32).clamp(i16::MIN as f32, i16::MAX as f32) as i16
+}
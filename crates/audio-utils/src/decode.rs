@@ -0,0 +1,224 @@
+use std::{fs::File, path::Path};
+
+use symphonia::core::{
+    audio::SampleBuffer,
+    codecs::{DecoderOptions, CODEC_TYPE_NULL, CODEC_TYPE_OPUS},
+    errors::Error as SymphoniaError,
+    formats::{FormatOptions, FormatReader},
+    io::MediaSourceStream,
+    meta::MetadataOptions,
+    probe::Hint,
+};
+
+use crate::OpusDecoder;
+
+const OPUS_SAMPLE_RATE: u32 = 48 * 1000;
+
+#[derive(Debug, thiserror::Error)]
+pub enum DecodeError {
+    #[error(transparent)]
+    IoError(#[from] std::io::Error),
+    #[error(transparent)]
+    SymphoniaError(#[from] SymphoniaError),
+    #[error(transparent)]
+    OpusError(#[from] crate::OpusError),
+    #[error("no audio track")]
+    NoAudioTrack,
+    #[error("unknown sample rate")]
+    UnknownSampleRate,
+}
+
+/// Decodes the first audio track of a media file (WAV, MP3, M4A/AAC, MP4, OGG Vorbis/Opus or
+/// FLAC), returning its sample rate and the samples mixed down to mono.
+pub fn decode_file(path: impl AsRef<Path>) -> Result<(u32, Vec<f32>), DecodeError> {
+    let decoder = FileDecoder::open(path)?;
+    let sample_rate = decoder.sample_rate();
+
+    let mut samples = Vec::new();
+    for chunk in decoder {
+        samples.extend(chunk?);
+    }
+
+    Ok((sample_rate, samples))
+}
+
+/// [`decode_file`] one packet at a time, yielding mono samples, so long files never have to fit
+/// in memory.
+pub struct FileDecoder {
+    format: Box<dyn FormatReader>,
+    track_id: u32,
+    sample_rate: u32,
+    codec: Codec,
+}
+
+enum Codec {
+    Symphonia {
+        decoder: Box<dyn symphonia::core::codecs::Decoder>,
+        buffer: Option<SampleBuffer<f32>>,
+    },
+    // Symphonia demuxes Ogg Opus but has no decoder for it.
+    Opus {
+        decoder: OpusDecoder,
+        pre_skip: usize,
+    },
+}
+
+impl FileDecoder {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, DecodeError> {
+        let path = path.as_ref();
+
+        let mut hint = Hint::new();
+        if let Some(ext) = path.extension().and_then(|ext| ext.to_str()) {
+            hint.with_extension(ext);
+        }
+
+        let source = MediaSourceStream::new(Box::new(File::open(path)?), Default::default());
+        let probed = symphonia::default::get_probe().format(
+            &hint,
+            source,
+            &FormatOptions::default(),
+            &MetadataOptions::default(),
+        )?;
+        let format = probed.format;
+
+        let track = format
+            .tracks()
+            .iter()
+            .find(|t| {
+                t.codec_params.codec != CODEC_TYPE_NULL && t.codec_params.sample_rate.is_some()
+            })
+            .ok_or(DecodeError::NoAudioTrack)?;
+        let track_id = track.id;
+
+        let (sample_rate, codec) = if track.codec_params.codec == CODEC_TYPE_OPUS {
+            // A mono decoder downmixes stereo streams by itself.
+            let codec = Codec::Opus {
+                decoder: OpusDecoder::new(OPUS_SAMPLE_RATE)?,
+                pre_skip: track.codec_params.delay.unwrap_or(0) as usize,
+            };
+            (OPUS_SAMPLE_RATE, codec)
+        } else {
+            let sample_rate = track
+                .codec_params
+                .sample_rate
+                .ok_or(DecodeError::UnknownSampleRate)?;
+            let codec = Codec::Symphonia {
+                decoder: symphonia::default::get_codecs()
+                    .make(&track.codec_params, &DecoderOptions::default())?,
+                buffer: None,
+            };
+            (sample_rate, codec)
+        };
+
+        Ok(Self {
+            format,
+            track_id,
+            sample_rate,
+            codec,
+        })
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    fn next_chunk(&mut self) -> Result<Option<Vec<f32>>, DecodeError> {
+        while let Some(packet) = next_packet(self.format.as_mut())? {
+            if packet.track_id() != self.track_id {
+                continue;
+            }
+
+            match &mut self.codec {
+                Codec::Symphonia { decoder, buffer } => {
+                    let decoded = match decoder.decode(&packet) {
+                        Ok(decoded) => decoded,
+                        // Corrupt frames are skipped rather than failing the whole file.
+                        Err(SymphoniaError::DecodeError(e)) => {
+                            tracing::warn!("decode_error: {}", e);
+                            continue;
+                        }
+                        Err(e) => return Err(e.into()),
+                    };
+
+                    let spec = *decoded.spec();
+                    let channels = spec.channels.count();
+                    if !matches!(buffer, Some(b) if b.capacity() >= decoded.capacity() * channels) {
+                        *buffer = Some(SampleBuffer::new(decoded.capacity() as u64, spec));
+                    }
+
+                    let buffer = buffer.as_mut().unwrap();
+                    buffer.copy_interleaved_ref(decoded);
+                    return Ok(Some(downmix(buffer.samples(), channels).collect()));
+                }
+                Codec::Opus { decoder, pre_skip } => {
+                    let mut samples = decoder.decode(&packet.data)?;
+
+                    let skipped = (*pre_skip).min(samples.len());
+                    samples.drain(..skipped);
+                    *pre_skip -= skipped;
+
+                    return Ok(Some(samples));
+                }
+            }
+        }
+
+        Ok(None)
+    }
+}
+
+impl Iterator for FileDecoder {
+    type Item = Result<Vec<f32>, DecodeError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_chunk().transpose()
+    }
+}
+
+fn next_packet(
+    format: &mut dyn FormatReader,
+) -> Result<Option<symphonia::core::formats::Packet>, DecodeError> {
+    match format.next_packet() {
+        Ok(packet) => Ok(Some(packet)),
+        Err(SymphoniaError::IoError(e)) if e.kind() == std::io::ErrorKind::UnexpectedEof => {
+            Ok(None)
+        }
+        Err(SymphoniaError::ResetRequired) => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+fn downmix(interleaved: &[f32], channels: usize) -> impl Iterator<Item = f32> + '_ {
+    interleaved
+        .chunks_exact(channels.max(1))
+        .map(move |frame| frame.iter().sum::<f32>() / frame.len() as f32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_wav() {
+        let path = std::env::temp_dir().join("audio_utils_test_decode.wav");
+
+        let spec = hound::WavSpec {
+            channels: 2,
+            sample_rate: 44100,
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        };
+        let mut writer = hound::WavWriter::create(&path, spec).unwrap();
+        for _ in 0..44100 {
+            writer.write_sample(i16::MAX / 2).unwrap();
+            writer.write_sample(0i16).unwrap();
+        }
+        writer.finalize().unwrap();
+
+        let (sample_rate, samples) = decode_file(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(sample_rate, 44100);
+        assert_eq!(samples.len(), 44100);
+        assert!((samples[0] - 0.25).abs() < 0.01);
+    }
+}
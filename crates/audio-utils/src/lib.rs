@@ -2,6 +2,19 @@ use bytes::{BufMut, Bytes, BytesMut};
 use futures_util::{Stream, StreamExt};
 use kalosm_sound::AsyncSource;
 
+// Zero crossings of the resampling kernel on each side.
+const SINC_ZERO_CROSSINGS: f64 = 16.0;
+
+#[cfg(feature = "opus")]
+mod opus;
+#[cfg(feature = "opus")]
+pub use opus::*;
+
+#[cfg(feature = "decode")]
+mod decode;
+#[cfg(feature = "decode")]
+pub use decode::*;
+
 impl<T: AsyncSource> AudioFormatExt for T {}
 
 pub trait AudioFormatExt: AsyncSource {
@@ -13,16 +26,297 @@ pub trait AudioFormatExt: AsyncSource {
     where
         Self: Sized + Send + Unpin + 'static,
     {
-        self.resample(sample_rate).chunks(chunk_size).map(|chunk| {
-            let n = std::mem::size_of::<f32>() * chunk.len();
-
-            let mut buf = BytesMut::with_capacity(n);
-            for sample in chunk {
-                let scaled = (sample * std::i16::MAX as f32)
-                    .clamp(std::i16::MIN as f32, std::i16::MAX as f32);
-                buf.put_i16_le(scaled as i16);
-            }
-            buf.freeze()
-        })
+        self.resample(sample_rate)
+            .chunks(chunk_size)
+            .map(|chunk| f32_to_i16_le_bytes(&chunk))
+    }
+
+    /// One Opus packet of [`OPUS_FRAME_MS`] per item. A trailing partial frame is dropped, and so
+    /// are frames that fail to encode, with a warning counting them.
+    #[cfg(feature = "opus")]
+    fn to_opus_packets(
+        self,
+        sample_rate: u32,
+    ) -> Result<impl Stream<Item = Bytes> + Send + Unpin, OpusError>
+    where
+        Self: Sized + Send + Unpin + 'static,
+    {
+        let mut encoder = OpusEncoder::new(sample_rate)?;
+        let frame_len = (sample_rate * OPUS_FRAME_MS / 1000) as usize;
+        let mut dropped_frames = 0u64;
+
+        Ok(self
+            .resample(sample_rate)
+            .chunks(frame_len)
+            .flat_map(move |chunk| {
+                let packets = encoder.encode(&chunk).unwrap_or_else(|e| {
+                    dropped_frames += 1;
+                    tracing::warn!(dropped_frames, "opus_encode_error: {:?}", e);
+                    Vec::new()
+                });
+                futures_util::stream::iter(packets)
+            }))
+    }
+}
+
+pub fn f32_to_i16_le_bytes(samples: &[f32]) -> Bytes {
+    let mut buf = BytesMut::with_capacity(std::mem::size_of::<i16>() * samples.len());
+    for sample in samples {
+        let scaled =
+            (sample * std::i16::MAX as f32).clamp(std::i16::MIN as f32, std::i16::MAX as f32);
+        buf.put_i16_le(scaled as i16);
+    }
+    buf.freeze()
+}
+
+/// Band-limited resampling with a windowed sinc kernel. When downsampling, the cutoff is lowered
+/// to the output's Nyquist frequency, so what's above it is filtered out instead of aliasing into
+/// the speech band.
+pub fn resample(samples: Vec<f32>, from: u32, to: u32) -> Vec<f32> {
+    if from == to || from == 0 || to == 0 || samples.is_empty() {
+        return samples;
+    }
+
+    let mut resampler = Resampler::new(from, to);
+    let mut resampled = resampler.process(&samples);
+    resampled.extend(resampler.finish());
+    resampled
+}
+
+/// [`resample`] for input that arrives in chunks. Only the input the kernel still reaches is
+/// kept, and the output is the same as resampling everything at once.
+pub struct Resampler {
+    up: u64,
+    down: u64,
+    reach: i64,
+    // Empty when the rates match.
+    kernels: Vec<Vec<f32>>,
+    pending: Vec<f32>,
+    // Input index of `pending[0]`.
+    offset: u64,
+    received: u64,
+    next: u64,
+}
+
+impl Resampler {
+    pub fn new(from: u32, to: u32) -> Self {
+        if from == to || from == 0 || to == 0 {
+            return Self {
+                up: 1,
+                down: 1,
+                reach: 0,
+                kernels: vec![],
+                pending: vec![],
+                offset: 0,
+                received: 0,
+                next: 0,
+            };
+        }
+
+        let gcd = gcd(from, to);
+        let (up, down) = ((to / gcd) as u64, (from / gcd) as u64);
+
+        let cutoff = (to as f64 / from as f64).min(1.0);
+        let half_width = SINC_ZERO_CROSSINGS / cutoff;
+        let reach = half_width.ceil() as i64;
+
+        // Output samples fall between input samples at one of `up` distinct offsets, so a kernel
+        // is computed once per offset.
+        let kernels = (0..up)
+            .map(|phase| {
+                let frac = phase as f64 / up as f64;
+                let kernel: Vec<f64> = (1 - reach..=reach)
+                    .map(|k| windowed_sinc(frac - k as f64, cutoff, half_width))
+                    .collect();
+
+                // Normalized, so constant input stays constant.
+                let sum: f64 = kernel.iter().sum();
+                kernel.iter().map(|w| (w / sum) as f32).collect()
+            })
+            .collect();
+
+        Self {
+            up,
+            down,
+            reach,
+            kernels,
+            pending: vec![],
+            offset: 0,
+            received: 0,
+            next: 0,
+        }
+    }
+
+    /// Returns the output samples whose kernel is fully covered by the input so far.
+    pub fn process(&mut self, samples: &[f32]) -> Vec<f32> {
+        if self.kernels.is_empty() {
+            return samples.to_vec();
+        }
+
+        self.pending.extend_from_slice(samples);
+        self.received += samples.len() as u64;
+
+        let mut output = Vec::new();
+        while self.base(self.next) + self.reach < self.received as i64 {
+            output.push(self.output(self.next));
+            self.next += 1;
+        }
+
+        let needed = (self.base(self.next) + 1 - self.reach).max(0) as u64;
+        let done = (needed.saturating_sub(self.offset) as usize).min(self.pending.len());
+        self.pending.drain(..done);
+        self.offset += done as u64;
+
+        output
+    }
+
+    /// Returns the remaining output, treating the input as ending here.
+    pub fn finish(self) -> Vec<f32> {
+        if self.kernels.is_empty() {
+            return vec![];
+        }
+
+        let len = self.received * self.up / self.down;
+        (self.next..len).map(|i| self.output(i)).collect()
+    }
+
+    fn base(&self, i: u64) -> i64 {
+        (i * self.down / self.up) as i64
+    }
+
+    fn output(&self, i: u64) -> f32 {
+        let kernel = &self.kernels[(i * self.down % self.up) as usize];
+
+        kernel
+            .iter()
+            .zip(self.base(i) + 1 - self.reach..)
+            .filter_map(|(w, j)| {
+                let j = u64::try_from(j).ok()?.checked_sub(self.offset)?;
+                Some(w * self.pending.get(usize::try_from(j).ok()?)?)
+            })
+            .sum()
+    }
+}
+
+fn windowed_sinc(x: f64, cutoff: f64, half_width: f64) -> f64 {
+    if x.abs() >= half_width {
+        return 0.0;
+    }
+
+    let sinc = if x == 0.0 {
+        1.0
+    } else {
+        let t = std::f64::consts::PI * cutoff * x;
+        t.sin() / t
+    };
+    let hann = 0.5 + 0.5 * (std::f64::consts::PI * x / half_width).cos();
+
+    cutoff * sinc * hann
+}
+
+fn gcd(a: u32, b: u32) -> u32 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sine(freq: f32, sample_rate: u32, secs: u32) -> Vec<f32> {
+        (0..sample_rate * secs)
+            .map(|i| {
+                (i as f32 * freq * 2.0 * std::f32::consts::PI / sample_rate as f32).sin() * 0.5
+            })
+            .collect()
+    }
+
+    // Skips the edges, where the kernel runs off the input.
+    fn rms(samples: &[f32]) -> f32 {
+        let samples = &samples[100..samples.len() - 100];
+        (samples.iter().map(|s| s * s).sum::<f32>() / samples.len() as f32).sqrt()
+    }
+
+    #[test]
+    fn test_resample() {
+        assert_eq!(resample(vec![0.0, 1.0], 16000, 16000), vec![0.0, 1.0]);
+        assert_eq!(resample(vec![0.0, 1.0, 2.0, 3.0], 48000, 16000).len(), 1);
+        assert_eq!(resample(vec![0.0; 44100], 44100, 16000).len(), 16000);
+
+        let upsampled = resample(vec![0.25; 16000], 16000, 32000);
+        assert_eq!(upsampled.len(), 32000);
+        assert!(upsampled[100..31900]
+            .iter()
+            .all(|s| (s - 0.25).abs() < 1e-4));
+    }
+
+    #[test]
+    fn test_resample_keeps_speech_band() {
+        for from in [44100, 48000] {
+            let resampled = resample(sine(1000.0, from, 1), from, 16000);
+
+            assert!((rms(&resampled) - 0.5 / 2f32.sqrt()).abs() < 0.01);
+            // Still 1kHz: two zero crossings per period.
+            let crossings = resampled
+                .windows(2)
+                .filter(|w| (w[0] < 0.0) != (w[1] < 0.0))
+                .count();
+            assert!((1998..=2001).contains(&crossings), "{crossings}");
+        }
+    }
+
+    #[test]
+    fn test_resampler_matches_resample() {
+        let samples = sine(1000.0, 44100, 1);
+        let expected = resample(samples.clone(), 44100, 16000);
+
+        for chunk_size in [1, 7, 441, 10000] {
+            let mut resampler = Resampler::new(44100, 16000);
+            let mut resampled: Vec<f32> = samples
+                .chunks(chunk_size)
+                .flat_map(|chunk| resampler.process(chunk))
+                .collect();
+            resampled.extend(resampler.finish());
+
+            assert_eq!(resampled, expected, "{chunk_size}");
+        }
+    }
+
+    #[test]
+    fn test_resample_filters_aliases() {
+        // Above 8kHz, a 10kHz tone would fold back to 6kHz without filtering.
+        for from in [44100, 48000] {
+            let resampled = resample(sine(10000.0, from, 1), from, 16000);
+            assert!(rms(&resampled) < 0.01, "{}", rms(&resampled));
+        }
+    }
+
+    #[cfg(feature = "opus")]
+    #[test]
+    fn test_opus_roundtrip() {
+        let sample_rate = 16000;
+        let samples: Vec<f32> = (0..sample_rate)
+            .map(|i| {
+                (i as f32 * 440.0 * 2.0 * std::f32::consts::PI / sample_rate as f32).sin() * 0.5
+            })
+            .collect();
+
+        let mut encoder = OpusEncoder::new(sample_rate).unwrap();
+        let mut decoder = OpusDecoder::new(sample_rate).unwrap();
+
+        let packets = encoder.encode(&samples).unwrap();
+        assert_eq!(packets.len(), 1000 / OPUS_FRAME_MS as usize);
+
+        let encoded: usize = packets.iter().map(|p| p.len()).sum();
+        assert!(encoded < samples.len() * 2 / 4);
+
+        let decoded: Vec<f32> = packets
+            .iter()
+            .flat_map(|p| decoder.decode(p).unwrap())
+            .collect();
+        assert_eq!(decoded.len(), samples.len());
     }
 }
@@ -0,0 +1,89 @@
+use audiopus::{
+    coder::{Decoder, Encoder},
+    packet::Packet,
+    Application, Channels, MutSignals, SampleRate,
+};
+use bytes::Bytes;
+
+pub use audiopus::Error as OpusError;
+
+pub const OPUS_FRAME_MS: u32 = 20;
+const MAX_PACKET_BYTES: usize = 4000;
+
+/// Encodes mono audio into Opus packets of [`OPUS_FRAME_MS`] each.
+pub struct OpusEncoder {
+    encoder: Encoder,
+    frame_len: usize,
+    pending: Vec<f32>,
+    packet: Vec<u8>,
+}
+
+impl OpusEncoder {
+    pub fn new(sample_rate: u32) -> Result<Self, OpusError> {
+        let encoder = Encoder::new(
+            SampleRate::try_from(sample_rate as i32)?,
+            Channels::Mono,
+            Application::Voip,
+        )?;
+
+        Ok(Self {
+            encoder,
+            frame_len: opus_frame_len(sample_rate),
+            pending: Vec::new(),
+            packet: vec![0u8; MAX_PACKET_BYTES],
+        })
+    }
+
+    /// Returns a packet for every whole frame available, keeping the rest for the next call.
+    /// On error, the frames up to the one that failed are dropped, so the next call starts fresh.
+    pub fn encode(&mut self, samples: &[f32]) -> Result<Vec<Bytes>, OpusError> {
+        self.pending.extend_from_slice(samples);
+
+        let mut packets = Vec::with_capacity(self.pending.len() / self.frame_len);
+        let mut start = 0;
+        while self.pending.len() - start >= self.frame_len {
+            let frame = &self.pending[start..start + self.frame_len];
+            start += self.frame_len;
+
+            match self.encoder.encode_float(frame, &mut self.packet) {
+                Ok(len) => packets.push(Bytes::copy_from_slice(&self.packet[..len])),
+                Err(e) => {
+                    self.pending.drain(..start);
+                    return Err(e);
+                }
+            }
+        }
+        self.pending.drain(..start);
+
+        Ok(packets)
+    }
+}
+
+/// Decodes the packets produced by [`OpusEncoder`], one packet at a time.
+pub struct OpusDecoder {
+    decoder: Decoder,
+    output: Vec<f32>,
+}
+
+impl OpusDecoder {
+    pub fn new(sample_rate: u32) -> Result<Self, OpusError> {
+        let decoder = Decoder::new(SampleRate::try_from(sample_rate as i32)?, Channels::Mono)?;
+
+        Ok(Self {
+            decoder,
+            // Opus packets are at most 120ms long.
+            output: vec![0.0; opus_frame_len(sample_rate) * 120 / OPUS_FRAME_MS as usize],
+        })
+    }
+
+    pub fn decode(&mut self, packet: &[u8]) -> Result<Vec<f32>, OpusError> {
+        let packet = Packet::try_from(packet)?;
+        let output = MutSignals::try_from(&mut self.output[..])?;
+        let len = self.decoder.decode_float(Some(packet), output, false)?;
+        Ok(self.output[..len].to_vec())
+    }
+}
+
+fn opus_frame_len(sample_rate: u32) -> usize {
+    (sample_rate * OPUS_FRAME_MS / 1000) as usize
+}
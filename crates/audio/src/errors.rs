@@ -1,2 +1,11 @@
 #[derive(thiserror::Error, Debug)]
-pub enum Error {}
+pub enum Error {
+    #[error("no monitor source available: {0}")]
+    NoMonitorSource(String),
+    #[error("speaker capture failed: {0}")]
+    SpeakerCapture(String),
+    #[error("no input device available")]
+    NoInputDevice,
+    #[error("mic capture failed: {0}")]
+    MicCapture(String),
+}
@@ -79,6 +79,15 @@ impl AudioInput {
         }
     }
 
+    pub fn from_mic_device(device_name: Option<&str>) -> Result<Self, Error> {
+        Ok(Self {
+            source: AudioSource::RealtimeMic,
+            mic: Some(MicInput::new(device_name)?),
+            speaker: None,
+            data: None,
+        })
+    }
+
     pub fn from_speaker(sample_rate_override: Option<u32>) -> Self {
         Self {
             source: AudioSource::RealtimeSpeaker,
@@ -97,6 +106,10 @@ impl AudioInput {
         }
     }
 
+    pub fn mic_device_name(&self) -> Option<String> {
+        self.mic.as_ref().map(|mic| mic.device_name())
+    }
+
     pub fn stream(&mut self) -> AudioStream {
         match &self.source {
             AudioSource::RealtimeMic => AudioStream::RealtimeMic {
@@ -1,9 +1,200 @@
-pub use kalosm_sound::{MicInput, MicStream};
+use std::pin::Pin;
+use std::task::Poll;
+
+use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use futures_channel::mpsc;
+use futures_util::{Stream, StreamExt};
+
+use crate::Error;
+
+// https://github.com/floneum/floneum/blob/50afe10/interfaces/kalosm-sound/src/source/mic.rs#L41
+pub struct MicInput {
+    #[allow(dead_code)]
+    host: cpal::Host,
+    device: cpal::Device,
+    config: cpal::SupportedStreamConfig,
+}
+
+impl Default for MicInput {
+    fn default() -> Self {
+        Self::new(None).expect("no input device available")
+    }
+}
+
+impl MicInput {
+    pub fn list_devices() -> Result<Vec<String>, Error> {
+        let host = cpal::default_host();
+        let devices = host
+            .input_devices()
+            .map_err(|e| Error::MicCapture(e.to_string()))?;
+        Ok(devices.filter_map(|d| d.name().ok()).collect())
+    }
+
+    /// Opens the input device with the given name, or the default one if it is `None` or no longer connected.
+    pub fn new(device_name: Option<&str>) -> Result<Self, Error> {
+        let host = cpal::default_host();
+
+        let device = match device_name {
+            Some(name) => host
+                .input_devices()
+                .map_err(|e| Error::MicCapture(e.to_string()))?
+                .find(|d| d.name().is_ok_and(|n| n == name))
+                .or_else(|| {
+                    tracing::warn!(device = name, "mic_device_not_found");
+                    host.default_input_device()
+                }),
+            None => host.default_input_device(),
+        }
+        .ok_or(Error::NoInputDevice)?;
+
+        let config = device
+            .default_input_config()
+            .map_err(|e| Error::MicCapture(e.to_string()))?;
+
+        Ok(Self {
+            host,
+            device,
+            config,
+        })
+    }
+
+    pub fn device_name(&self) -> String {
+        self.device.name().unwrap_or_default()
+    }
+
+    pub fn stream(&self) -> MicStream {
+        let (tx, rx) = mpsc::unbounded::<f32>();
+        let (drop_tx, drop_rx) = std::sync::mpsc::channel::<()>();
+
+        let device = self.device.clone();
+        let config = self.config.clone();
+        let channels = config.channels() as usize;
+
+        // `cpal::Stream` is not `Send`, so it lives on its own thread until the `MicStream` is dropped.
+        std::thread::spawn(move || {
+            // Closing the sender ends the `MicStream`, which is how callers find out the device went away.
+            let err_tx = tx.clone();
+            let error_callback = move |err: cpal::StreamError| {
+                tracing::error!("mic_stream_error: {:?}", err);
+                err_tx.close_channel();
+            };
+
+            let stream = match config.sample_format() {
+                cpal::SampleFormat::I8 => {
+                    build_stream::<i8>(&device, &config, channels, tx, error_callback)
+                }
+                cpal::SampleFormat::I16 => {
+                    build_stream::<i16>(&device, &config, channels, tx, error_callback)
+                }
+                cpal::SampleFormat::I32 => {
+                    build_stream::<i32>(&device, &config, channels, tx, error_callback)
+                }
+                cpal::SampleFormat::F32 => {
+                    build_stream::<f32>(&device, &config, channels, tx, error_callback)
+                }
+                sample_format => Err(Error::MicCapture(format!(
+                    "unsupported sample format: {:?}",
+                    sample_format
+                ))),
+            };
+
+            let stream = match stream {
+                Ok(stream) => stream,
+                Err(e) => {
+                    tracing::error!("mic_stream_error: {:?}", e);
+                    return;
+                }
+            };
+
+            if let Err(e) = stream.play() {
+                tracing::error!("mic_stream_error: {:?}", e);
+                return;
+            }
+
+            let _ = drop_rx.recv();
+        });
+
+        MicStream {
+            drop_tx,
+            sample_rate: self.config.sample_rate().0,
+            receiver: rx,
+        }
+    }
+}
+
+fn build_stream<S>(
+    device: &cpal::Device,
+    config: &cpal::SupportedStreamConfig,
+    channels: usize,
+    tx: mpsc::UnboundedSender<f32>,
+    error_callback: impl FnMut(cpal::StreamError) + Send + 'static,
+) -> Result<cpal::Stream, Error>
+where
+    S: cpal::SizedSample,
+    f32: cpal::FromSample<S>,
+{
+    device
+        .build_input_stream(
+            &config.config(),
+            move |data: &[S], _: &_| {
+                // Downmix to mono by averaging every frame.
+                for frame in data.chunks(channels) {
+                    let sum: f32 = frame
+                        .iter()
+                        .map(|&s| <f32 as cpal::FromSample<S>>::from_sample_(s))
+                        .sum();
+                    let _ = tx.unbounded_send(sum / frame.len() as f32);
+                }
+            },
+            error_callback,
+            None,
+        )
+        .map_err(|e| Error::MicCapture(e.to_string()))
+}
+
+// https://github.com/floneum/floneum/blob/50afe10/interfaces/kalosm-sound/src/source/mic.rs#L140
+pub struct MicStream {
+    drop_tx: std::sync::mpsc::Sender<()>,
+    sample_rate: u32,
+    receiver: mpsc::UnboundedReceiver<f32>,
+}
+
+impl MicStream {
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+}
+
+impl Drop for MicStream {
+    fn drop(&mut self) {
+        let _ = self.drop_tx.send(());
+    }
+}
+
+impl Stream for MicStream {
+    type Item = f32;
+
+    fn poll_next(
+        mut self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        self.receiver.poll_next_unpin(cx)
+    }
+}
+
+impl kalosm_sound::AsyncSource for MicStream {
+    fn as_stream(&mut self) -> impl Stream<Item = f32> + '_ {
+        self
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+}
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use futures_util::StreamExt;
 
     #[tokio::test]
     async fn test_mic() {
@@ -20,4 +211,12 @@ mod tests {
 
         assert!(buffer.iter().any(|x| *x != 0.0));
     }
+
+    #[test]
+    fn test_unknown_device_falls_back_to_default() {
+        let mic = MicInput::new(Some("definitely-not-a-real-device")).unwrap();
+        let default = MicInput::default();
+
+        assert_eq!(mic.device_name(), default.device_name());
+    }
 }
@@ -1,36 +1,199 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::{Poll, Waker};
+
+use anyhow::Result;
 use futures_util::Stream;
+use ringbuf::{
+    traits::{Consumer, Producer, Split},
+    HeapCons, HeapProd, HeapRb,
+};
+
+use libpulse_binding::{
+    sample::{Format, Spec},
+    stream::Direction,
+};
+use libpulse_simple_binding::Simple;
+
+use crate::Error;
+
+// Resolved by both PulseAudio and pipewire-pulse to the monitor of the current default sink.
+const DEFAULT_MONITOR: &str = "@DEFAULT_MONITOR@";
+const DEFAULT_SAMPLE_RATE: u32 = 48000;
+// ~10ms at 48kHz, small enough to keep latency in line with the macOS tap.
+const READ_FRAMES: usize = 480;
+
+pub struct SpeakerInput {
+    spec: Spec,
+}
+
+struct WakerState {
+    waker: Option<Waker>,
+    has_data: bool,
+}
+
+pub struct SpeakerStream {
+    consumer: HeapCons<f32>,
+    sample_rate: u32,
+    stop: Arc<AtomicBool>,
+    waker_state: Arc<Mutex<WakerState>>,
+    handle: std::thread::JoinHandle<()>,
+}
 
-pub struct SpeakerInput {}
+impl SpeakerStream {
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+}
+
+impl Drop for SpeakerStream {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+fn connect(spec: &Spec) -> Result<Simple, Error> {
+    Simple::new(
+        None,
+        "hyprnote",
+        Direction::Record,
+        Some(DEFAULT_MONITOR),
+        "speaker",
+        spec,
+        None,
+        None,
+    )
+    .map_err(|e| Error::NoMonitorSource(e.to_string().unwrap_or_default()))
+}
 
 impl SpeakerInput {
-    pub fn new(_sample_rate_override: Option<u32>) -> Self {
-        Self {}
+    pub fn new(sample_rate_override: Option<u32>) -> Result<Self> {
+        let spec = Spec {
+            format: Format::F32le,
+            channels: 1,
+            rate: sample_rate_override.unwrap_or(DEFAULT_SAMPLE_RATE),
+        };
+
+        if !spec.is_valid() {
+            return Err(Error::SpeakerCapture(format!("invalid sample spec: {:?}", spec)).into());
+        }
+
+        // `Simple` is not `Send`, so we only probe here and reconnect on the capture thread.
+        drop(connect(&spec)?);
+
+        tracing::info!(
+            source = DEFAULT_MONITOR,
+            sample_rate = spec.rate,
+            "speaker_monitor_source"
+        );
+
+        Ok(Self { spec })
     }
 
     pub fn stream(self) -> SpeakerStream {
-        SpeakerStream::new()
+        let rb = HeapRb::<f32>::new(8192);
+        let (producer, consumer) = rb.split();
+
+        let stop = Arc::new(AtomicBool::new(false));
+        let waker_state = Arc::new(Mutex::new(WakerState {
+            waker: None,
+            has_data: false,
+        }));
+
+        let handle = std::thread::spawn({
+            let spec = self.spec;
+            let stop = stop.clone();
+            let waker_state = waker_state.clone();
+
+            move || {
+                if let Err(e) = capture(spec, producer, stop, waker_state.clone()) {
+                    tracing::error!("linux_speaker_capture_error: {:?}", e);
+                }
+
+                // Let a pending consumer observe the end of the stream.
+                if let Some(waker) = waker_state.lock().unwrap().waker.take() {
+                    waker.wake();
+                }
+            }
+        });
+
+        SpeakerStream {
+            consumer,
+            sample_rate: self.spec.rate,
+            stop,
+            waker_state,
+            handle,
+        }
     }
 }
 
-pub struct SpeakerStream {}
+fn capture(
+    spec: Spec,
+    mut producer: HeapProd<f32>,
+    stop: Arc<AtomicBool>,
+    waker_state: Arc<Mutex<WakerState>>,
+) -> Result<(), Error> {
+    let simple = connect(&spec)?;
 
-impl SpeakerStream {
-    pub fn new() -> Self {
-        Self {}
-    }
+    let mut bytes = vec![0u8; READ_FRAMES * std::mem::size_of::<f32>()];
+    let mut samples = Vec::with_capacity(READ_FRAMES);
 
-    pub fn sample_rate(&self) -> u32 {
-        16000
+    while !stop.load(Ordering::Relaxed) {
+        simple
+            .read(&mut bytes)
+            .map_err(|e| Error::SpeakerCapture(e.to_string().unwrap_or_default()))?;
+
+        samples.clear();
+        samples.extend(
+            bytes
+                .chunks_exact(4)
+                .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]])),
+        );
+
+        let pushed = producer.push_slice(&samples);
+        if pushed < samples.len() {
+            tracing::warn!("linux_speaker_dropped_{}_samples", samples.len() - pushed);
+        }
+
+        let mut state = waker_state.lock().unwrap();
+        if pushed > 0 && !state.has_data {
+            state.has_data = true;
+            if let Some(waker) = state.waker.take() {
+                drop(state);
+                waker.wake();
+            }
+        }
     }
+
+    Ok(())
 }
 
 impl Stream for SpeakerStream {
     type Item = f32;
 
     fn poll_next(
-        self: std::pin::Pin<&mut Self>,
-        _cx: &mut std::task::Context<'_>,
-    ) -> std::task::Poll<Option<Self::Item>> {
-        std::task::Poll::Ready(Some(0.0))
+        mut self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        if let Some(sample) = self.consumer.try_pop() {
+            return Poll::Ready(Some(sample));
+        }
+
+        if self.handle.is_finished() {
+            return Poll::Ready(None);
+        }
+
+        {
+            let mut state = self.waker_state.lock().unwrap();
+            state.has_data = false;
+            state.waker = Some(cx.waker().clone());
+            drop(state);
+        }
+
+        match self.consumer.try_pop() {
+            Some(sample) => Poll::Ready(Some(sample)),
+            None if self.handle.is_finished() => Poll::Ready(None),
+            None => Poll::Pending,
+        }
     }
 }
@@ -29,26 +29,26 @@ pub struct SpeakerInput {
 }
 
 impl SpeakerInput {
-    #[cfg(target_os = "macos")]
+    #[cfg(any(target_os = "macos", target_os = "linux"))]
     pub fn new(sample_rate_override: Option<u32>) -> Result<Self> {
         let inner = PlatformSpeakerInput::new(sample_rate_override)?;
         Ok(Self { inner })
     }
 
-    #[cfg(not(any(target_os = "macos")))]
+    #[cfg(not(any(target_os = "macos", target_os = "linux")))]
     pub fn new(sample_rate_override: Option<u32>) -> Result<Self> {
         Err(anyhow::anyhow!(
             "'SpeakerInput::new' is not supported on this platform"
         ))
     }
 
-    #[cfg(any(target_os = "macos", target_os = "windows"))]
+    #[cfg(any(target_os = "macos", target_os = "windows", target_os = "linux"))]
     pub fn stream(self) -> Result<SpeakerStream> {
         let inner = self.inner.stream();
         Ok(SpeakerStream { inner })
     }
 
-    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    #[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
     pub fn stream(self) -> Result<SpeakerStream> {
         Err(anyhow::anyhow!(
             "'SpeakerInput::stream' is not supported on this platform"
@@ -68,12 +68,12 @@ impl Stream for SpeakerStream {
         mut self: std::pin::Pin<&mut Self>,
         cx: &mut std::task::Context<'_>,
     ) -> std::task::Poll<Option<Self::Item>> {
-        #[cfg(any(target_os = "macos", target_os = "windows"))]
+        #[cfg(any(target_os = "macos", target_os = "windows", target_os = "linux"))]
         {
             self.inner.poll_next_unpin(cx)
         }
 
-        #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+        #[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
         {
             std::task::Poll::Pending
         }
@@ -85,12 +85,12 @@ impl kalosm_sound::AsyncSource for SpeakerStream {
         self
     }
 
-    #[cfg(any(target_os = "macos", target_os = "windows"))]
+    #[cfg(any(target_os = "macos", target_os = "windows", target_os = "linux"))]
     fn sample_rate(&self) -> u32 {
         self.inner.sample_rate()
     }
 
-    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    #[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
     fn sample_rate(&self) -> u32 {
         0
     }
@@ -150,6 +150,29 @@ mod tests {
         assert!(buffer.iter().any(|x| *x != 0.0));
     }
 
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    #[serial]
+    async fn test_linux() {
+        let input = SpeakerInput::new(None).unwrap();
+        let mut stream = input.stream().unwrap();
+
+        tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
+
+        let handle = play_sine_for_sec(2);
+
+        let mut buffer = Vec::new();
+        while let Some(sample) = stream.next().await {
+            buffer.push(sample);
+            if buffer.len() > 48000 {
+                break;
+            }
+        }
+
+        handle.join().unwrap();
+        assert!(buffer.iter().any(|x| *x != 0.0));
+    }
+
     #[cfg(target_os = "windows")]
     #[test]
     #[serial]
@@ -0,0 +1,727 @@
+use crate::Error;
+
+/// A parsed HTML node, detached from `tl`'s borrowed DOM.
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+    Element {
+        name: String,
+        attrs: Vec<(String, String)>,
+        children: Vec<Node>,
+    },
+    Text(String),
+}
+
+impl Node {
+    fn attr(&self, key: &str) -> Option<&str> {
+        match self {
+            Node::Element { attrs, .. } => attrs
+                .iter()
+                .find(|(k, _)| k == key)
+                .map(|(_, v)| v.as_str()),
+            Node::Text(_) => None,
+        }
+    }
+}
+
+const BLOCK_TAGS: &[&str] = &[
+    "p",
+    "div",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "ul",
+    "ol",
+    "li",
+    "blockquote",
+    "pre",
+    "hr",
+    "table",
+];
+
+/// A rendered block, and whether it is a list so list items can keep nested lists tight.
+struct Block {
+    md: String,
+    list: bool,
+}
+
+pub fn html_to_md(html: impl AsRef<str>) -> Result<String, Error> {
+    let nodes = parse(html.as_ref())?;
+
+    let mut blocks = Vec::new();
+    render_blocks(&nodes, &mut blocks);
+
+    let mut md = join_blocks(&blocks, false);
+    if !md.is_empty() {
+        md.push('\n');
+    }
+    Ok(md)
+}
+
+fn parse(html: &str) -> Result<Vec<Node>, Error> {
+    let dom = tl::parse(html, tl::ParserOptions::default())
+        .map_err(|e| Error::HTMLParseError(e.to_string()))?;
+    let parser = dom.parser();
+
+    fn convert(handle: &tl::NodeHandle, parser: &tl::Parser) -> Option<Node> {
+        match handle.get(parser)? {
+            tl::Node::Tag(tag) => Some(Node::Element {
+                name: tag.name().as_utf8_str().to_lowercase(),
+                attrs: tag
+                    .attributes()
+                    .iter()
+                    .map(|(k, v)| (k.to_lowercase(), decode_entities(&v.unwrap_or_default())))
+                    .collect(),
+                children: tag
+                    .children()
+                    .top()
+                    .iter()
+                    .filter_map(|h| convert(h, parser))
+                    .collect(),
+            }),
+            tl::Node::Raw(bytes) => Some(Node::Text(decode_entities(&bytes.as_utf8_str()))),
+            tl::Node::Comment(_) => None,
+        }
+    }
+
+    Ok(dom
+        .children()
+        .iter()
+        .filter_map(|h| convert(h, parser))
+        .collect())
+}
+
+fn is_block(node: &Node) -> bool {
+    matches!(node, Node::Element { name, .. } if BLOCK_TAGS.contains(&name.as_str()))
+}
+
+/// Renders `nodes` as Markdown blocks. Runs of inline content between blocks become paragraphs.
+fn render_blocks(nodes: &[Node], out: &mut Vec<Block>) {
+    let mut inline: Vec<Node> = Vec::new();
+
+    for node in nodes {
+        if is_block(node) {
+            flush_paragraph(&mut inline, out);
+            render_block(node, out);
+        } else {
+            inline.push(node.clone());
+        }
+    }
+    flush_paragraph(&mut inline, out);
+}
+
+fn flush_paragraph(inline: &mut Vec<Node>, out: &mut Vec<Block>) {
+    let rendered = render_inlines(inline);
+    inline.clear();
+
+    let mut text = rendered.trim_end();
+    while let Some(rest) = text.strip_suffix("\\") {
+        text = rest.trim_end();
+    }
+
+    // Paragraphs holding only a non-breaking space are how the editor spaces out sections.
+    let text = text.trim();
+    if !text.is_empty() {
+        out.push(Block {
+            md: text
+                .split('\n')
+                .map(escape_line_start)
+                .collect::<Vec<_>>()
+                .join("\n"),
+            list: false,
+        });
+    }
+}
+
+fn join_blocks(blocks: &[Block], tight: bool) -> String {
+    let mut out = String::new();
+    for (i, block) in blocks.iter().enumerate() {
+        if i > 0 {
+            let nested = tight && (block.list || blocks[i - 1].list);
+            out.push_str(if nested { "\n" } else { "\n\n" });
+        }
+        out.push_str(&block.md);
+    }
+    out
+}
+
+fn render_block(node: &Node, out: &mut Vec<Block>) {
+    let Node::Element { name, children, .. } = node else {
+        return;
+    };
+
+    match name.as_str() {
+        "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+            let depth = name[1..].parse::<usize>().unwrap_or(1);
+            let text = render_inlines(children).replace("\\\n", " ");
+            let text = text.trim();
+            if !text.is_empty() {
+                // A trailing `#` would be read as the closing sequence of the heading.
+                let text = match text.strip_suffix('#') {
+                    Some(rest) => format!("{}\\#", rest),
+                    None => text.to_string(),
+                };
+                out.push(Block {
+                    md: format!("{} {}", "#".repeat(depth), text),
+                    list: false,
+                });
+            }
+        }
+        "ul" | "ol" => {
+            let list = render_list(node);
+            if !list.is_empty() {
+                out.push(Block {
+                    md: list,
+                    list: true,
+                });
+            }
+        }
+        "blockquote" => {
+            let mut inner = Vec::new();
+            render_blocks(children, &mut inner);
+            if !inner.is_empty() {
+                out.push(Block {
+                    md: prefix_lines(&join_blocks(&inner, false), "> ", "> "),
+                    list: false,
+                });
+            }
+        }
+        "pre" => {
+            let code = text_content(children);
+            let fence = "`".repeat(longest_run(&code, '`').max(2) + 1);
+            out.push(Block {
+                md: format!("{fence}\n{}\n{fence}", code.trim_end_matches('\n')),
+                list: false,
+            });
+        }
+        "hr" => out.push(Block {
+            md: "---".to_string(),
+            list: false,
+        }),
+        _ => render_blocks(children, out),
+    }
+}
+
+fn render_list(node: &Node) -> String {
+    let Node::Element { name, children, .. } = node else {
+        return String::new();
+    };
+
+    let ordered = name == "ol";
+    let start = node
+        .attr("start")
+        .and_then(|s| s.parse::<usize>().ok())
+        .unwrap_or(1);
+
+    let mut items: Vec<(String, Vec<Block>)> = Vec::new();
+    for item in children {
+        let Node::Element {
+            name: item_name,
+            children: item_children,
+            ..
+        } = item
+        else {
+            continue;
+        };
+
+        let mut blocks = Vec::new();
+
+        // Lists pasted from elsewhere sometimes nest `<ul>` right in `<ul>`, meaning a sublist of
+        // the previous item.
+        if matches!(item_name.as_str(), "ul" | "ol") {
+            render_block(item, &mut blocks);
+            match items.last_mut() {
+                Some((_, previous)) => previous.extend(blocks),
+                None => items.push((String::new(), blocks)),
+            }
+            continue;
+        }
+
+        render_blocks(item_children, &mut blocks);
+
+        let checkbox = match task_state(item) {
+            Some(true) => "[x] ",
+            Some(false) => "[ ] ",
+            None => "",
+        };
+        items.push((checkbox.to_string(), blocks));
+    }
+
+    let mut lines = Vec::new();
+    for (i, (checkbox, blocks)) in items.iter().enumerate() {
+        let marker = if ordered {
+            format!("{}. ", start + i)
+        } else {
+            "- ".to_string()
+        };
+
+        let content = format!("{}{}", checkbox, join_blocks(blocks, true));
+        let content = content.trim_end();
+
+        if content.is_empty() {
+            lines.push(marker.trim_end().to_string());
+        } else {
+            let indent = " ".repeat(marker.len());
+            lines.push(prefix_lines(content, &marker, &indent));
+        }
+    }
+
+    lines.join("\n")
+}
+
+/// Whether the item is a checked (`Some(true)`) or unchecked task, for the editor's task lists
+/// (`data-checked`) and plain HTML checkboxes alike.
+fn task_state(item: &Node) -> Option<bool> {
+    if let Some(checked) = item.attr("data-checked") {
+        return Some(checked == "true");
+    }
+    if item.attr("data-type") == Some("taskItem") {
+        return Some(false);
+    }
+
+    fn find_checkbox(nodes: &[Node], depth: usize) -> Option<&Node> {
+        nodes.iter().find_map(|node| match node {
+            Node::Element { name, .. }
+                if name == "input" && node.attr("type") == Some("checkbox") =>
+            {
+                Some(node)
+            }
+            Node::Element { name, children, .. } if name == "label" && depth > 0 => {
+                find_checkbox(children, depth - 1)
+            }
+            _ => None,
+        })
+    }
+
+    let Node::Element { children, .. } = item else {
+        return None;
+    };
+    find_checkbox(children, 1).map(|input| input.attr("checked").is_some())
+}
+
+fn render_inlines(nodes: &[Node]) -> String {
+    let mut out = String::new();
+    for node in nodes {
+        render_inline(node, &mut out);
+    }
+    out
+}
+
+fn render_inline(node: &Node, out: &mut String) {
+    match node {
+        Node::Text(text) => {
+            let text = collapse_whitespace(text);
+            // Whitespace between two inline tags is kept, but never doubled.
+            let text = if out.ends_with(' ') || out.ends_with('\n') {
+                text.trim_start()
+            } else {
+                text.as_str()
+            };
+            out.push_str(&escape(text));
+        }
+        Node::Element { name, children, .. } => match name.as_str() {
+            "br" => out.push_str("\\\n"),
+            "strong" | "b" => wrap_inline(children, "**", out),
+            "em" | "i" => wrap_inline(children, "*", out),
+            "s" | "del" | "strike" => wrap_inline(children, "~~", out),
+            "code" => {
+                let code = text_content(children);
+                let fence = "`".repeat(longest_run(&code, '`') + 1);
+                let pad = if code.starts_with('`') || code.ends_with('`') {
+                    " "
+                } else {
+                    ""
+                };
+                out.push_str(&format!("{fence}{pad}{code}{pad}{fence}"));
+            }
+            "a" => {
+                let text = render_inlines(children);
+                match node.attr("href") {
+                    // Mentions link to `javascript:` handlers that mean nothing outside the app.
+                    Some(href) if !href.is_empty() && !href.starts_with("javascript:") => {
+                        out.push_str(&format!("[{}]({})", text.trim(), escape_url(href)))
+                    }
+                    _ => out.push_str(&text),
+                }
+            }
+            "img" => {
+                if let Some(src) = node.attr("src") {
+                    let alt = node.attr("alt").unwrap_or_default();
+                    out.push_str(&format!("![{}]({})", escape(alt), escape_url(src)));
+                }
+            }
+            "script" | "style" | "input" => {}
+            _ => {
+                for child in children {
+                    render_inline(child, out);
+                }
+            }
+        },
+    }
+}
+
+/// Emphasis markers have to hug the text, so surrounding whitespace is moved outside of them.
+fn wrap_inline(children: &[Node], marker: &str, out: &mut String) {
+    let inner = render_inlines(children);
+    let trimmed = inner.trim();
+    if trimmed.is_empty() {
+        out.push_str(&inner);
+        return;
+    }
+
+    if inner.starts_with(char::is_whitespace) && !out.ends_with(' ') && !out.is_empty() {
+        out.push(' ');
+    }
+    out.push_str(marker);
+    out.push_str(trimmed);
+    out.push_str(marker);
+    if inner.ends_with(char::is_whitespace) {
+        out.push(' ');
+    }
+}
+
+fn text_content(nodes: &[Node]) -> String {
+    let mut out = String::new();
+    for node in nodes {
+        match node {
+            Node::Text(text) => out.push_str(text),
+            Node::Element { name, .. } if name == "br" => out.push('\n'),
+            Node::Element { children, .. } => out.push_str(&text_content(children)),
+        }
+    }
+    out
+}
+
+fn prefix_lines(text: &str, first: &str, rest: &str) -> String {
+    text.lines()
+        .enumerate()
+        .map(|(i, line)| {
+            let prefix = if i == 0 { first } else { rest };
+            if line.is_empty() {
+                prefix.trim_end().to_string()
+            } else {
+                format!("{prefix}{line}")
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn collapse_whitespace(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut in_space = false;
+    for c in text.chars() {
+        if c.is_whitespace() {
+            if !in_space {
+                out.push(' ');
+            }
+            in_space = true;
+        } else {
+            out.push(c);
+            in_space = false;
+        }
+    }
+    out
+}
+
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for (i, c) in text.char_indices() {
+        let escaped = match c {
+            '\\' | '*' | '_' | '`' | '[' | ']' | '<' => true,
+            // Only where it would otherwise be read as an entity.
+            '&' => looks_like_entity(&text[i..]),
+            _ => false,
+        };
+        if escaped {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out
+}
+
+fn looks_like_entity(text: &str) -> bool {
+    let Some(end) = text.find(';') else {
+        return false;
+    };
+    let name = text[1..end].strip_prefix('#').unwrap_or(&text[1..end]);
+    !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric())
+}
+
+/// Escapes what would otherwise turn a line of a paragraph into a heading, quote, list, code
+/// fence or thematic break.
+fn escape_line_start(text: &str) -> String {
+    let digits = text.chars().take_while(char::is_ascii_digit).count();
+    let rest = &text[digits..];
+
+    let needs_escape = if digits > 0 {
+        rest.starts_with(". ") || rest.starts_with(") ") || rest == "." || rest == ")"
+    } else {
+        let trimmed = text.trim_end();
+        text.starts_with('#')
+            || text.starts_with('>')
+            || text.starts_with("~~~")
+            || (!trimmed.is_empty() && trimmed.chars().all(|c| c == '-' || c == '='))
+            || ["- ", "+ ", "* "].iter().any(|m| text.starts_with(m))
+            || text == "+"
+    };
+
+    match (needs_escape, digits) {
+        (false, _) => text.to_string(),
+        (true, 0) => format!("\\{text}"),
+        (true, _) => format!("{}\\{}", &text[..digits], rest),
+    }
+}
+
+fn escape_url(url: &str) -> String {
+    url.replace(' ', "%20")
+        .replace('(', "%28")
+        .replace(')', "%29")
+}
+
+fn longest_run(text: &str, c: char) -> usize {
+    let mut longest = 0;
+    let mut current = 0;
+    for ch in text.chars() {
+        if ch == c {
+            current += 1;
+            longest = longest.max(current);
+        } else {
+            current = 0;
+        }
+    }
+    longest
+}
+
+fn decode_entities(text: &str) -> String {
+    if !text.contains('&') {
+        return text.to_string();
+    }
+
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(start) = rest.find('&') {
+        out.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let decoded = rest.find(';').filter(|&end| end <= 10).and_then(|end| {
+            let entity = &rest[1..end];
+            let c = match entity {
+                "amp" => Some('&'),
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "quot" => Some('"'),
+                "apos" => Some('\''),
+                "nbsp" => Some('\u{a0}'),
+                _ => entity
+                    .strip_prefix("#x")
+                    .or_else(|| entity.strip_prefix("#X"))
+                    .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+                    .or_else(|| entity.strip_prefix('#').and_then(|d| d.parse().ok()))
+                    .and_then(char::from_u32),
+            };
+            c.map(|c| (c, end))
+        });
+
+        match decoded {
+            Some((c, end)) => {
+                out.push(c);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_html_to_md() {
+        let input = r#"<h1>Action items</h1><p>Talked to <strong>Yujong</strong> about <em>pricing</em>, see <a href="https://hyprnote.com">the site</a>.</p><ul><li><p>Ship &amp; tell</p></li><li><p>Follow up</p><ol><li><p>Email</p></li><li><p>Call</p></li></ol></li></ul><p> </p><p>Done</p>"#;
+
+        insta::assert_snapshot!(html_to_md(input).unwrap(), @r###"
+        # Action items
+
+        Talked to **Yujong** about *pricing*, see [the site](https://hyprnote.com).
+
+        - Ship & tell
+        - Follow up
+          1. Email
+          2. Call
+
+        Done
+        "###);
+    }
+
+    #[test]
+    fn test_task_list() {
+        let input = r#"<ul data-type="taskList"><li data-checked="true" data-type="taskItem"><label><input type="checkbox" checked="checked"><span></span></label><div><p>Send deck</p></div></li><li data-checked="false" data-type="taskItem"><label><input type="checkbox"><span></span></label><div><p>Book room</p><ul data-type="taskList"><li data-checked="false" data-type="taskItem"><label><input type="checkbox"><span></span></label><div><p>Nested</p></div></li></ul></div></li></ul>"#;
+
+        insta::assert_snapshot!(html_to_md(input).unwrap(), @r###"
+        - [x] Send deck
+        - [ ] Book room
+          - [ ] Nested
+        "###);
+    }
+
+    #[test]
+    fn test_mention() {
+        let input = r#"<p>Ask <a class="mention" data-mention="true" data-id="1" data-type="human" data-label="John" href="javascript:void(0)">@John</a> about Q3 &amp;amp; pricing #</p><h1>Topic #</h1>"#;
+
+        insta::assert_snapshot!(html_to_md(input).unwrap(), @r###"
+        Ask @John about Q3 \&amp; pricing #
+
+        # Topic \#
+        "###);
+    }
+
+    #[test]
+    fn test_nested_lists() {
+        let input = r#"<ul><li>a</li><ul><li>b</li></ul><li>c</li></ul><ol start="3"><li>x</li><li>y</li></ol>"#;
+
+        insta::assert_snapshot!(html_to_md(input).unwrap(), @r###"
+        - a
+          - b
+        - c
+
+        3. x
+        4. y
+        "###);
+    }
+
+    #[test]
+    fn test_line_start_escapes() {
+        let input = "<p>---</p><p>a<br>- b<br> ===</p>";
+
+        insta::assert_snapshot!(html_to_md(input).unwrap(), @r###"
+        \---
+
+        a\
+        \- b\
+        \===
+        "###);
+    }
+
+    #[test]
+    fn test_decode_entities() {
+        assert_eq!(
+            decode_entities("a &lt;b&gt; &#39;c&#x27; &unknown; &"),
+            "a <b> 'c' &unknown; &"
+        );
+    }
+
+    mod roundtrip {
+        use super::*;
+        use crate::opinionated_md_to_html;
+        use proptest::prelude::*;
+
+        fn word() -> impl Strategy<Value = String> {
+            "[a-zA-Z0-9]{1,8}"
+        }
+
+        fn inline() -> impl Strategy<Value = String> {
+            prop_oneof![
+                6 => word(),
+                1 => word().prop_map(|w| format!("**{}**", w)),
+                1 => word().prop_map(|w| format!("*{}*", w)),
+                1 => word().prop_map(|w| format!("`{}`", w)),
+                1 => (word(), word())
+                    .prop_map(|(text, path)| format!("[{}](https://hyprnote.com/{})", text, path)),
+                1 => prop::sample::select(vec![
+                    "\\*", "\\_", "\\[", "\\]", "\\<", "\\\\", "&", "\\&amp;", "#",
+                ])
+                .prop_map(String::from),
+            ]
+        }
+
+        // Starts with a word so that no line accidentally opens a block.
+        fn line() -> impl Strategy<Value = String> {
+            (word(), prop::collection::vec(inline(), 0..6)).prop_map(|(first, rest)| {
+                std::iter::once(first)
+                    .chain(rest)
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            })
+        }
+
+        fn list(depth: u32) -> BoxedStrategy<Vec<String>> {
+            let item = if depth == 0 {
+                line().prop_map(|l| vec![format!("- {}", l)]).boxed()
+            } else {
+                (line(), prop::option::of(list(depth - 1)))
+                    .prop_map(|(l, children)| {
+                        let mut lines = vec![format!("- {}", l)];
+                        lines.extend(children.into_iter().flatten().map(|c| format!("  {}", c)));
+                        lines
+                    })
+                    .boxed()
+            };
+
+            prop::collection::vec(item, 1..4)
+                .prop_map(|items| items.concat())
+                .boxed()
+        }
+
+        #[derive(Debug, Clone)]
+        enum Section {
+            Heading(String),
+            Paragraph(String),
+            List(Vec<String>),
+        }
+
+        fn document() -> impl Strategy<Value = String> {
+            let section = prop_oneof![
+                line().prop_map(Section::Heading),
+                line().prop_map(Section::Paragraph),
+                list(2).prop_map(Section::List),
+            ];
+
+            prop::collection::vec(section, 1..6)
+                // Two lists separated by a blank line are a single loose list in Markdown.
+                .prop_filter("adjacent lists", |sections| {
+                    !sections
+                        .windows(2)
+                        .any(|w| matches!(w, [Section::List(_), Section::List(_)]))
+                })
+                .prop_map(|sections| {
+                    sections
+                        .into_iter()
+                        .map(|s| match s {
+                            Section::Heading(l) => format!("# {}", l),
+                            Section::Paragraph(l) => l,
+                            Section::List(lines) => lines.join("\n"),
+                        })
+                        .collect::<Vec<_>>()
+                        .join("\n\n")
+                })
+        }
+
+        fn normalize(html: &str) -> String {
+            html.split_whitespace()
+                .collect::<Vec<_>>()
+                .join(" ")
+                .replace("> <", "><")
+        }
+
+        proptest! {
+            #[test]
+            fn test_roundtrip(md in document()) {
+                let html = opinionated_md_to_html(&md).unwrap();
+                let again = opinionated_md_to_html(html_to_md(&html).unwrap()).unwrap();
+
+                prop_assert_eq!(normalize(&html), normalize(&again));
+            }
+        }
+    }
+}
@@ -10,6 +10,9 @@ pub enum Error {
     HTMLParseError(String),
 }
 
+mod html_to_md;
+pub use html_to_md::html_to_md;
+
 pub fn opinionated_md_to_html(text: impl AsRef<str>) -> Result<String, Error> {
     let md = md_to_md(text)?;
     md_to_html(&md)
@@ -57,4 +57,87 @@ mod tests {
             i += 1;
         }
     }
+
+    #[tokio::test]
+    async fn test_chunk_start() {
+        let sample_rate = 16000;
+        let mut samples = vec![0.0; sample_rate];
+        samples.extend(vec![0.5; sample_rate * 6]);
+        samples.extend(vec![0.0; sample_rate]);
+        samples.extend(vec![0.5; sample_rate * 2]);
+
+        let source = rodio::buffer::SamplesBuffer::new(1, sample_rate as u32, samples);
+        let stream = source
+            .chunks(RMS::new(), Duration::from_secs(15))
+            .offset(Duration::from_secs(10));
+
+        let starts: Vec<Duration> = stream.map(|chunk| chunk.start()).collect().await;
+
+        // The first chunk is cut after 500ms of silence, the second one at the end of the input.
+        // Leading silence is trimmed from both, which moves their start forward.
+        assert_eq!(
+            starts,
+            vec![Duration::from_secs(11), Duration::from_secs(18)]
+        );
+    }
+
+    fn to_f32(bytes: &[u8]) -> Vec<f32> {
+        bytes
+            .chunks_exact(2)
+            .map(|c| i16::from_le_bytes([c[0], c[1]]) as f32 / 32768.0)
+            .collect()
+    }
+
+    #[test]
+    fn test_silero_silence() {
+        let silero = Silero::new().unwrap();
+
+        assert!(!silero.predict(&[0.0; 16000]).unwrap());
+        assert_eq!(silero.speech_start(&[0.0; 16000]).unwrap(), None);
+    }
+
+    #[test]
+    fn test_silero_speech_start() {
+        let silero = Silero::new().unwrap();
+        let samples = to_f32(hypr_data::english_1::AUDIO);
+
+        let words: Vec<serde_json::Value> =
+            serde_json::from_str(hypr_data::english_1::TRANSCRIPTION_JSON).unwrap();
+        let first_word_ms = words[0]["start"].as_u64().unwrap() as i64;
+
+        let start = silero.speech_start(&samples).unwrap().unwrap();
+        let start_ms = (start * 1000 / 16000) as i64;
+
+        assert!(
+            (start_ms - first_word_ms).abs() < 300,
+            "{} vs {}",
+            start_ms,
+            first_word_ms
+        );
+    }
+
+    #[tokio::test]
+    async fn test_silero_chunker() {
+        let audio_source = rodio::Decoder::new_wav(std::io::BufReader::new(
+            std::fs::File::open(hypr_data::english_1::AUDIO_PATH).unwrap(),
+        ))
+        .unwrap();
+
+        let silero = Silero::new().unwrap();
+        let mut stream = audio_source.chunks(Silero::new().unwrap(), Duration::from_secs(15));
+
+        let mut chunks = vec![];
+        while let Some(chunk) = stream.next().await {
+            let samples: Vec<f32> = chunk.collect();
+            assert!(samples.len() <= 16000 * 15);
+            chunks.push(samples);
+        }
+        assert!(chunks.len() > 1);
+
+        // Leading silence is trimmed, so every chunk but the trailing one starts on speech.
+        for samples in &chunks[..chunks.len() - 1] {
+            let start = silero.speech_start(samples).unwrap().unwrap();
+            assert!(start < 16000 / 2);
+        }
+    }
 }
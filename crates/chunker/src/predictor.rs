@@ -1,5 +1,32 @@
+use std::ops::Range;
+use std::sync::Mutex;
+
 pub trait Predictor: Send + Sync {
     fn predict(&self, samples: &[f32]) -> Result<bool, crate::Error>;
+
+    /// Index of the first sample that belongs to speech, if there is any.
+    fn speech_start(&self, samples: &[f32]) -> Result<Option<usize>, crate::Error> {
+        const WINDOW_SIZE: usize = 100;
+
+        for start in (0..samples.len()).step_by(WINDOW_SIZE) {
+            let end = (start + WINDOW_SIZE).min(samples.len());
+            if self.predict(&samples[start..end])? {
+                return Ok(Some(start));
+            }
+        }
+
+        Ok(None)
+    }
+}
+
+impl<P: Predictor + ?Sized> Predictor for Box<P> {
+    fn predict(&self, samples: &[f32]) -> Result<bool, crate::Error> {
+        (**self).predict(samples)
+    }
+
+    fn speech_start(&self, samples: &[f32]) -> Result<Option<usize>, crate::Error> {
+        (**self).speech_start(samples)
+    }
 }
 
 #[derive(Debug)]
@@ -26,20 +53,54 @@ impl Predictor for RMS {
 
 #[derive(Debug)]
 pub struct Silero {
-    #[allow(dead_code)]
-    inner: hypr_vad::Vad,
+    inner: Mutex<hypr_vad::Vad>,
+    config: hypr_vad::VadConfig,
 }
 
 impl Silero {
     pub fn new() -> Result<Self, crate::Error> {
+        Self::with_config(hypr_vad::VadConfig::default())
+    }
+
+    pub fn with_config(config: hypr_vad::VadConfig) -> Result<Self, crate::Error> {
         Ok(Self {
-            inner: hypr_vad::Vad::new()?,
+            inner: Mutex::new(hypr_vad::Vad::new()?),
+            config,
         })
     }
+
+    /// Speech probability of each 30ms frame in `samples`, which must be 16kHz mono.
+    pub fn probabilities(&self, samples: &[f32]) -> Result<Vec<f32>, crate::Error> {
+        let mut vad = self.inner.lock().unwrap();
+
+        // Windows passed in by the chunker overlap, so the recurrent state can not be carried over.
+        vad.reset();
+        Ok(vad.run_frames(samples)?)
+    }
+
+    fn segments(&self, samples: &[f32]) -> Result<Vec<Range<usize>>, crate::Error> {
+        let mut detector = hypr_vad::SpeechDetector::new(self.config.clone());
+
+        let mut segments: Vec<_> = self
+            .probabilities(samples)?
+            .into_iter()
+            .filter_map(|p| detector.push(p))
+            .collect();
+        segments.extend(detector.finish());
+
+        Ok(segments
+            .into_iter()
+            .map(|r| hypr_vad::frames_to_samples(r.start)..hypr_vad::frames_to_samples(r.end))
+            .collect())
+    }
 }
 
 impl Predictor for Silero {
-    fn predict(&self, _samples: &[f32]) -> Result<bool, crate::Error> {
-        Ok(true)
+    fn predict(&self, samples: &[f32]) -> Result<bool, crate::Error> {
+        Ok(!self.segments(samples)?.is_empty())
+    }
+
+    fn speech_start(&self, samples: &[f32]) -> Result<Option<usize>, crate::Error> {
+        Ok(self.segments(samples)?.first().map(|r| r.start))
     }
 }
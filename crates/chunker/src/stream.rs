@@ -6,27 +6,127 @@ use std::{
 };
 
 use kalosm_sound::AsyncSource;
-use rodio::buffer::SamplesBuffer;
+use rodio::{buffer::SamplesBuffer, Source};
 
 use crate::Predictor;
 
+/// Audio source that knows where it starts in the session.
+pub trait TimedSource: Source {
+    fn start(&self) -> Duration;
+}
+
+/// Mono samples of one chunk, with the position of its first sample in the session.
+pub struct AudioChunk {
+    start: Duration,
+    samples: SamplesBuffer<f32>,
+}
+
+impl AudioChunk {
+    pub fn new(start: Duration, sample_rate: u32, samples: Vec<f32>) -> Self {
+        Self {
+            start,
+            samples: SamplesBuffer::new(1, sample_rate, samples),
+        }
+    }
+}
+
+impl TimedSource for AudioChunk {
+    fn start(&self) -> Duration {
+        self.start
+    }
+}
+
+impl Iterator for AudioChunk {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        self.samples.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.samples.size_hint()
+    }
+}
+
+impl Source for AudioChunk {
+    fn current_frame_len(&self) -> Option<usize> {
+        self.samples.current_frame_len()
+    }
+
+    fn channels(&self) -> u16 {
+        self.samples.channels()
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.samples.sample_rate()
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        self.samples.total_duration()
+    }
+}
+
 pub struct ChunkStream<S: AsyncSource + Unpin, P: Predictor + Unpin> {
     source: S,
     predictor: P,
     buffer: Vec<f32>,
     max_duration: Duration,
+    clock: ChunkClock,
+}
+
+/// Tracks where the samples in the buffer sit in the session.
+struct ChunkClock {
+    offset: Duration,
+    sample_rate: u32,
+    // Samples taken from the source before the first one in the buffer.
+    buffer_start: u64,
+}
+
+impl ChunkClock {
+    /// Position of the sample `index` samples into the buffer.
+    fn position(&self, index: usize) -> Duration {
+        let sample = self.buffer_start + index as u64;
+        self.offset + Duration::from_secs_f64(sample as f64 / self.sample_rate as f64)
+    }
+
+    /// Start of a chunk that skips `trimmed` samples, then moves past the `len` samples it was cut from.
+    fn advance(&mut self, trimmed: usize, len: usize) -> Duration {
+        let start = self.position(trimmed);
+        self.buffer_start += len as u64;
+        start
+    }
 }
 
 impl<S: AsyncSource + Unpin, P: Predictor + Unpin> ChunkStream<S, P> {
     pub fn new(source: S, predictor: P, max_duration: Duration) -> Self {
+        let clock = ChunkClock {
+            offset: Duration::ZERO,
+            sample_rate: source.sample_rate(),
+            buffer_start: 0,
+        };
+
         Self {
             source,
             predictor,
             buffer: Vec::new(),
             max_duration,
+            clock,
         }
     }
 
+    /// Position of the source's first sample in the session, e.g. the length of audio recorded
+    /// before this stream was opened. Added to every [`TimedSource::start`].
+    pub fn offset(mut self, offset: Duration) -> Self {
+        self.clock.offset = offset;
+        self
+    }
+
+    /// Samples buffered for the next chunk, and where they start in the session. Useful for
+    /// interim results while the chunk is still growing.
+    pub fn pending(&self) -> (Duration, &[f32]) {
+        (self.clock.position(0), &self.buffer)
+    }
+
     fn max_samples(&self) -> usize {
         (self.source.sample_rate() as f64 * self.max_duration.as_secs_f64()) as usize
     }
@@ -35,34 +135,41 @@ impl<S: AsyncSource + Unpin, P: Predictor + Unpin> ChunkStream<S, P> {
         (self.source.sample_rate() as f64 * duration.as_secs_f64()) as usize
     }
 
-    fn trim_silence(predictor: &P, data: &mut Vec<f32>) {
-        const WINDOW_SIZE: usize = 100;
-
-        let mut trim_index = 0;
-        for start_idx in (0..data.len()).step_by(WINDOW_SIZE) {
-            let end_idx = (start_idx + WINDOW_SIZE).min(data.len());
-            let window = &data[start_idx..end_idx];
-
-            if let Ok(false) = predictor.predict(window) {
-                trim_index = start_idx;
-                break;
+    /// Trims leading silence, returning how many samples were removed.
+    fn trim_silence(predictor: &P, data: &mut Vec<f32>) -> usize {
+        match predictor.speech_start(data) {
+            Ok(Some(start)) => {
+                data.drain(0..start);
+                start
+            }
+            Ok(None) => 0,
+            Err(e) => {
+                tracing::warn!("speech_start_error: {:?}", e);
+                0
             }
         }
+    }
+
+    /// Turns `data`, cut from the first `len` samples of the buffer, into a chunk.
+    fn emit(predictor: &P, clock: &mut ChunkClock, mut data: Vec<f32>, len: usize) -> AudioChunk {
+        let trimmed = Self::trim_silence(predictor, &mut data);
+        let start = clock.advance(trimmed, len);
 
-        data.drain(0..trim_index);
+        AudioChunk::new(start, clock.sample_rate, data)
     }
 }
 
 impl<S: AsyncSource + Unpin, P: Predictor + Unpin> Stream for ChunkStream<S, P> {
-    type Item = SamplesBuffer<f32>;
+    type Item = AudioChunk;
 
     fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
         let this = self.get_mut();
         let max_samples = this.max_samples();
-        let sample_rate = this.source.sample_rate();
 
         let min_buffer_samples = this.samples_for_duration(Duration::from_secs(6));
         let silence_window_samples = this.samples_for_duration(Duration::from_millis(500));
+        // Only look for a chunk boundary every 30ms instead of on every sample.
+        let predict_interval_samples = this.samples_for_duration(Duration::from_millis(30)).max(1);
 
         let stream = this.source.as_stream();
         let mut stream = std::pin::pin!(stream);
@@ -72,33 +179,45 @@ impl<S: AsyncSource + Unpin, P: Predictor + Unpin> Stream for ChunkStream<S, P>
                 Poll::Ready(Some(sample)) => {
                     this.buffer.push(sample);
 
-                    if this.buffer.len() >= min_buffer_samples {
+                    if this.buffer.len() >= min_buffer_samples
+                        && this.buffer.len() % predict_interval_samples == 0
+                    {
                         let buffer_len = this.buffer.len();
                         let silence_start = buffer_len.saturating_sub(silence_window_samples);
                         let last_samples = &this.buffer[silence_start..buffer_len];
 
                         if let Ok(false) = this.predictor.predict(last_samples) {
-                            let mut data = std::mem::take(&mut this.buffer);
-                            Self::trim_silence(&this.predictor, &mut data);
-
-                            return Poll::Ready(Some(SamplesBuffer::new(1, sample_rate, data)));
+                            let data = std::mem::take(&mut this.buffer);
+                            return Poll::Ready(Some(Self::emit(
+                                &this.predictor,
+                                &mut this.clock,
+                                data,
+                                buffer_len,
+                            )));
                         }
                     }
                 }
                 Poll::Ready(None) if !this.buffer.is_empty() => {
-                    let mut data = std::mem::take(&mut this.buffer);
-                    Self::trim_silence(&this.predictor, &mut data);
-
-                    return Poll::Ready(Some(SamplesBuffer::new(1, sample_rate, data)));
+                    let data = std::mem::take(&mut this.buffer);
+                    let len = data.len();
+                    return Poll::Ready(Some(Self::emit(
+                        &this.predictor,
+                        &mut this.clock,
+                        data,
+                        len,
+                    )));
                 }
                 Poll::Ready(None) => return Poll::Ready(None),
                 Poll::Pending => return Poll::Pending,
             }
         }
 
-        let mut chunk: Vec<_> = this.buffer.drain(0..max_samples).collect();
-        Self::trim_silence(&this.predictor, &mut chunk);
-
-        Poll::Ready(Some(SamplesBuffer::new(1, sample_rate, chunk)))
+        let chunk: Vec<_> = this.buffer.drain(0..max_samples).collect();
+        Poll::Ready(Some(Self::emit(
+            &this.predictor,
+            &mut this.clock,
+            chunk,
+            max_samples,
+        )))
     }
 }
@@ -0,0 +1,34 @@
+#[derive(clap::Args)]
+pub struct ExportArgs {
+    /// Path to the local database of the desktop app.
+    #[arg(long)]
+    pub db: std::path::PathBuf,
+    #[arg(long)]
+    pub session_id: String,
+    /// One of `markdown`, `text`, `srt`, `vtt` or `json`.
+    #[arg(long, default_value = "markdown")]
+    pub format: hypr_export::ExportFormat,
+    /// Written to stdout when omitted.
+    #[arg(long)]
+    pub output: Option<std::path::PathBuf>,
+}
+
+pub async fn handle_export(args: ExportArgs) -> anyhow::Result<()> {
+    let db = hypr_db_core::DatabaseBuilder::default()
+        .local(&args.db)
+        .build()
+        .await?;
+    let db = hypr_db_user::UserDatabase::from(db);
+
+    let input = hypr_export::ExportSession::load(&db, &args.session_id)
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("session not found: {}", args.session_id))?;
+    let content = hypr_export::export(&input, args.format)?;
+
+    match args.output {
+        Some(path) => std::fs::write(path, content)?,
+        None => print!("{}", content),
+    }
+
+    Ok(())
+}
@@ -1,5 +1,7 @@
 mod create;
+mod export;
 mod list;
 
 pub use create::*;
+pub use export::*;
 pub use list::*;
@@ -1,7 +1,7 @@
 mod commands;
 
 use clap::Parser;
-use commands::{CreateArgs, ListArgs};
+use commands::{CreateArgs, ExportArgs, ListArgs};
 
 #[derive(Parser)]
 #[command(about)]
@@ -14,6 +14,7 @@ struct Args {
 enum Commands {
     Create(CreateArgs),
     List(ListArgs),
+    Export(ExportArgs),
 }
 
 #[tokio::main]
@@ -23,6 +24,7 @@ async fn main() -> anyhow::Result<()> {
     match args.cmd {
         Commands::Create(create_args) => commands::handle_create(create_args),
         Commands::List(list_args) => commands::handle_list(list_args),
+        Commands::Export(export_args) => commands::handle_export(export_args).await,
     }
 }
 
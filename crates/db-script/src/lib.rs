@@ -63,16 +63,27 @@ pub mod conversation_to_words {
 
             let words = transform(conversations);
 
-            if let Err(_) = conn
-                .execute(
-                    "UPDATE sessions SET words = ? WHERE id = ?",
-                    (
-                        serde_json::to_string(&words).unwrap_or_default(),
-                        id.clone(),
-                    ),
-                )
-                .await
-            {
+            let mut failed = false;
+            for (seq, word) in words.into_iter().enumerate() {
+                if conn
+                    .execute(
+                        "INSERT OR IGNORE INTO words (session_id, seq, text, confidence) VALUES (?, ?, ?, ?)",
+                        (
+                            id.clone(),
+                            seq as i64,
+                            word.text,
+                            word.confidence.map(|c| c as f64),
+                        ),
+                    )
+                    .await
+                    .is_err()
+                {
+                    failed = true;
+                    break;
+                }
+            }
+
+            if failed {
                 continue;
             }
 
@@ -44,6 +44,10 @@ user_common_derives! {
         pub jargons: Vec<String>,
         pub telemetry_consent: bool,
         pub save_recordings: Option<bool>,
+        /// Name of the input device to record from. `None` follows the system default.
+        pub mic_device: Option<String>,
+        /// Whether sessions start with speaker echo removed from the mic. `None` means enabled.
+        pub echo_cancellation: Option<bool>,
     }
 }
 
@@ -55,6 +59,8 @@ impl Default for ConfigGeneral {
             jargons: vec![],
             telemetry_consent: true,
             save_recordings: Some(true),
+            mic_device: None,
+            echo_cancellation: Some(true),
         }
     }
 }
@@ -0,0 +1,12 @@
+CREATE TABLE IF NOT EXISTS session_embeddings (
+  session_id TEXT NOT NULL,
+  seq INTEGER NOT NULL,
+  model TEXT NOT NULL,
+  field TEXT NOT NULL,
+  text TEXT NOT NULL,
+  start_ms INTEGER DEFAULT NULL,
+  end_ms INTEGER DEFAULT NULL,
+  embedding BLOB NOT NULL,
+  PRIMARY KEY (session_id, seq),
+  FOREIGN KEY (session_id) REFERENCES sessions(id) ON DELETE CASCADE
+);
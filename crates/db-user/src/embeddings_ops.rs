@@ -0,0 +1,449 @@
+use std::{ops::Range, str::FromStr};
+
+use hypr_listener_interface::Word;
+
+use super::{
+    SearchField, SemanticMatch, SemanticSearchResult, Session, SessionChunk, UserDatabase,
+};
+
+// Roughly 150 English words, well within the context of small embedding models.
+const CHUNK_CHARS: usize = 800;
+// Transcript chunks overlap, so a sentence cut at a boundary is whole in one of them.
+const CHUNK_OVERLAP_CHARS: usize = 160;
+const MATCHES_PER_SESSION: usize = 3;
+
+impl UserDatabase {
+    /// Replaces every chunk embedded for the session. `model` identifies the embedding space,
+    /// only chunks embedded by the same model are compared.
+    pub async fn replace_session_embeddings(
+        &self,
+        session_id: impl Into<String>,
+        model: impl Into<String>,
+        chunks: Vec<(SessionChunk, Vec<f32>)>,
+    ) -> Result<(), crate::Error> {
+        let session_id = session_id.into();
+        let model = model.into();
+        let conn = self.conn()?;
+        let tx = conn.transaction().await?;
+
+        tx.execute(
+            "DELETE FROM session_embeddings WHERE session_id = ?",
+            vec![session_id.clone()],
+        )
+        .await?;
+
+        for (seq, (chunk, embedding)) in chunks.into_iter().enumerate() {
+            tx.execute(
+                "INSERT INTO session_embeddings (
+                    session_id,
+                    seq,
+                    model,
+                    field,
+                    text,
+                    start_ms,
+                    end_ms,
+                    embedding
+                ) VALUES (
+                    :session_id,
+                    :seq,
+                    :model,
+                    :field,
+                    :text,
+                    :start_ms,
+                    :end_ms,
+                    vector32(:embedding)
+                )",
+                libsql::named_params! {
+                    ":session_id": session_id.clone(),
+                    ":seq": seq as i64,
+                    ":model": model.clone(),
+                    ":field": chunk.field.to_string(),
+                    ":text": chunk.text,
+                    ":start_ms": chunk.start_ms.map(|ms| ms as i64),
+                    ":end_ms": chunk.end_ms.map(|ms| ms as i64),
+                    ":embedding": serde_json::to_string(&embedding).unwrap(),
+                },
+            )
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Sessions of the user with nothing embedded by `model` yet, newest first.
+    pub async fn list_sessions_without_embeddings(
+        &self,
+        user_id: impl Into<String>,
+        model: impl Into<String>,
+    ) -> Result<Vec<String>, crate::Error> {
+        let conn = self.conn()?;
+
+        let mut rows = conn
+            .query(
+                "SELECT id FROM sessions
+                WHERE user_id = ? AND NOT EXISTS (
+                    SELECT 1 FROM session_embeddings
+                    WHERE session_embeddings.session_id = sessions.id
+                    AND session_embeddings.model = ?
+                )
+                ORDER BY created_at DESC",
+                vec![user_id.into(), model.into()],
+            )
+            .await?;
+
+        let mut items = Vec::new();
+        while let Some(row) = rows.next().await? {
+            items.push(row.get(0)?);
+        }
+        Ok(items)
+    }
+
+    /// Sessions with the chunks closest to `embedding`, closest first.
+    ///
+    /// An exact scan over the user's chunks. Fast enough at the scale of one person's meetings,
+    /// and works with embeddings of any dimension.
+    pub async fn semantic_search_sessions(
+        &self,
+        user_id: impl Into<String>,
+        model: impl Into<String>,
+        embedding: Vec<f32>,
+        limit: Option<u8>,
+    ) -> Result<Vec<SemanticSearchResult>, crate::Error> {
+        let limit = limit.unwrap_or(10) as usize;
+        let conn = self.conn()?;
+
+        // Chunks are ranked within their session, so `limit` counts sessions rather than chunks.
+        let mut rows = conn
+            .query(
+                "WITH ranked AS (
+                    SELECT
+                        session_embeddings.session_id,
+                        session_embeddings.field,
+                        session_embeddings.text,
+                        session_embeddings.start_ms,
+                        session_embeddings.end_ms,
+                        vector_distance_cos(session_embeddings.embedding, vector32(:embedding)) AS distance
+                    FROM session_embeddings
+                    JOIN sessions ON sessions.id = session_embeddings.session_id
+                    WHERE sessions.user_id = :user_id AND session_embeddings.model = :model
+                ),
+                numbered AS (
+                    SELECT
+                        *,
+                        ROW_NUMBER() OVER (PARTITION BY session_id ORDER BY distance ASC) AS rank
+                    FROM ranked
+                )
+                SELECT session_id, field, text, start_ms, end_ms, distance
+                FROM numbered
+                WHERE rank <= :matches_per_session AND session_id IN (
+                    SELECT session_id FROM numbered
+                    WHERE rank = 1
+                    ORDER BY distance ASC
+                    LIMIT :limit
+                )
+                ORDER BY distance ASC",
+                libsql::named_params! {
+                    ":embedding": serde_json::to_string(&embedding).unwrap(),
+                    ":user_id": user_id.into(),
+                    ":model": model.into(),
+                    ":matches_per_session": MATCHES_PER_SESSION as i64,
+                    ":limit": limit as i64,
+                },
+            )
+            .await?;
+
+        let mut matches: Vec<(String, Vec<SemanticMatch>)> = Vec::new();
+
+        while let Some(row) = rows.next().await? {
+            let session_id: String = row.get(0)?;
+            let Ok(field) = SearchField::from_str(row.get_str(1)?) else {
+                continue;
+            };
+
+            let item = SemanticMatch {
+                chunk: SessionChunk {
+                    field,
+                    text: row.get(2)?,
+                    start_ms: row.get::<Option<i64>>(3)?.map(|ms| ms as u64),
+                    end_ms: row.get::<Option<i64>>(4)?.map(|ms| ms as u64),
+                },
+                score: 1.0 - row.get::<f64>(5)? as f32,
+            };
+
+            // Rows come closest first, so sessions are ordered by their best chunk.
+            match matches.iter_mut().find(|(id, _)| id == &session_id) {
+                Some((_, session_matches)) => session_matches.push(item),
+                None => matches.push((session_id, vec![item])),
+            }
+        }
+
+        let mut items = Vec::with_capacity(matches.len());
+        for (session_id, matches) in matches {
+            let Some(session) = self.get_session_without_words(session_id).await? else {
+                continue;
+            };
+            items.push(SemanticSearchResult { session, matches });
+        }
+        Ok(items)
+    }
+}
+
+/// Splits the title, memos and transcript of the session into chunks to embed.
+pub fn chunk_session(session: &Session) -> Vec<SessionChunk> {
+    let mut chunks = Vec::new();
+
+    let notes = [
+        (SearchField::Title, session.title.clone()),
+        (
+            SearchField::RawMemo,
+            crate::search_ops::html_to_text(&session.raw_memo_html),
+        ),
+        (
+            SearchField::EnhancedMemo,
+            session
+                .enhanced_memo_html
+                .as_deref()
+                .map(crate::search_ops::html_to_text)
+                .unwrap_or_default(),
+        ),
+    ];
+
+    for (field, text) in notes {
+        let tokens = text
+            .split_whitespace()
+            .flat_map(split_long_token)
+            .collect::<Vec<_>>();
+
+        for range in windows(&tokens, 0) {
+            chunks.push(SessionChunk {
+                field,
+                text: tokens[range].join(" "),
+                start_ms: None,
+                end_ms: None,
+            });
+        }
+    }
+
+    chunks.extend(chunk_transcript(&session.words));
+    chunks
+}
+
+fn chunk_transcript(words: &[Word]) -> Vec<SessionChunk> {
+    let tokens = words.iter().map(|w| w.text.trim()).collect::<Vec<_>>();
+
+    windows(&tokens, CHUNK_OVERLAP_CHARS)
+        .into_iter()
+        .map(|range| {
+            let words = &words[range.clone()];
+            SessionChunk {
+                field: SearchField::Transcript,
+                text: tokens[range].join(" "),
+                start_ms: words.iter().find_map(|w| w.start_ms),
+                end_ms: words.iter().rev().find_map(|w| w.end_ms),
+            }
+        })
+        .collect()
+}
+
+// Text without spaces, like Chinese or Japanese, would otherwise be a single huge token.
+fn split_long_token(token: &str) -> Vec<&str> {
+    let mut pieces = Vec::new();
+    let mut rest = token;
+
+    while rest.chars().count() > CHUNK_CHARS {
+        let (i, _) = rest.char_indices().nth(CHUNK_CHARS).unwrap();
+        pieces.push(&rest[..i]);
+        rest = &rest[i..];
+    }
+    pieces.push(rest);
+    pieces
+}
+
+/// Consecutive ranges of `tokens` of about `CHUNK_CHARS` each, each starting `overlap` chars
+/// before the previous one ended.
+fn windows(tokens: &[&str], overlap: usize) -> Vec<Range<usize>> {
+    let len = |token: &&str| token.chars().count() + 1;
+
+    let mut ranges = Vec::new();
+    let mut start = 0;
+
+    while start < tokens.len() {
+        let mut end = start;
+        let mut chars = 0;
+        while end < tokens.len() && (end == start || chars + len(&tokens[end]) <= CHUNK_CHARS) {
+            chars += len(&tokens[end]);
+            end += 1;
+        }
+        ranges.push(start..end);
+
+        if end == tokens.len() {
+            break;
+        }
+
+        let mut next = end;
+        let mut chars = 0;
+        while next > start + 1 && chars + len(&tokens[next - 1]) <= overlap {
+            next -= 1;
+            chars += len(&tokens[next]);
+        }
+        start = next;
+    }
+
+    ranges
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{tests::setup_db, Human};
+
+    fn word(text: &str, start_ms: u64) -> Word {
+        Word {
+            text: text.to_string(),
+            speaker: None,
+            confidence: None,
+            start_ms: Some(start_ms),
+            end_ms: Some(start_ms + 100),
+        }
+    }
+
+    #[test]
+    fn test_windows() {
+        let tokens = vec!["a".repeat(299); 5];
+        let tokens = tokens.iter().map(|t| t.as_str()).collect::<Vec<_>>();
+
+        assert_eq!(windows(&tokens, 0), vec![0..2, 2..4, 4..5]);
+        assert_eq!(windows(&tokens, 300), vec![0..2, 1..3, 2..4, 3..5]);
+        assert_eq!(windows(&tokens[..1], 300), vec![0..1]);
+        assert!(windows(&[], 300).is_empty());
+
+        assert_eq!(split_long_token(&"会".repeat(CHUNK_CHARS * 2 + 1)).len(), 3);
+    }
+
+    #[test]
+    fn test_chunk_transcript() {
+        let words = (0..400)
+            .map(|i| word(&format!("w{:03}", i), i * 100))
+            .collect::<Vec<_>>();
+
+        let chunks = chunk_transcript(&words);
+        assert_eq!(chunks.len(), 3);
+        assert!(chunks[0].text.starts_with("w000 w001"));
+        assert_eq!(chunks[0].start_ms, Some(0));
+        assert_eq!(chunks[1].start_ms, Some(12800));
+        assert_eq!(chunks[2].end_ms, Some(40000));
+    }
+
+    #[tokio::test]
+    async fn test_semantic_search_sessions() {
+        let db = setup_db().await;
+        let user = db.upsert_human(Human::default()).await.unwrap();
+
+        let new_session = |title: &str| Session {
+            id: uuid::Uuid::new_v4().to_string(),
+            user_id: user.id.clone(),
+            created_at: chrono::Utc::now(),
+            visited_at: chrono::Utc::now(),
+            calendar_event_id: None,
+            title: title.to_string(),
+            raw_memo_html: "".to_string(),
+            enhanced_memo_html: None,
+            conversations: vec![],
+            words: vec![],
+        };
+
+        let pricing = db.upsert_session(new_session("Pricing")).await.unwrap();
+        let offsite = db.upsert_session(new_session("Offsite")).await.unwrap();
+
+        let chunk = |text: &str, start_ms: Option<u64>| SessionChunk {
+            field: SearchField::Transcript,
+            text: text.to_string(),
+            start_ms,
+            end_ms: start_ms.map(|ms| ms + 1000),
+        };
+
+        db.replace_session_embeddings(
+            &pricing.id,
+            "test",
+            vec![
+                (chunk("raise the price", Some(0)), vec![1.0, 0.0, 0.0]),
+                (chunk("new plans", Some(1000)), vec![0.8, 0.6, 0.0]),
+            ],
+        )
+        .await
+        .unwrap();
+        db.replace_session_embeddings(
+            &offsite.id,
+            "test",
+            vec![(chunk("book the venue", None), vec![0.0, 0.0, 1.0])],
+        )
+        .await
+        .unwrap();
+
+        assert!(db
+            .list_sessions_without_embeddings(&user.id, "test")
+            .await
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            db.list_sessions_without_embeddings(&user.id, "other")
+                .await
+                .unwrap()
+                .len(),
+            2
+        );
+
+        let results = db
+            .semantic_search_sessions(&user.id, "test", vec![1.0, 0.0, 0.0], None)
+            .await
+            .unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].session.id, pricing.id);
+        assert_eq!(results[0].matches.len(), 2);
+        assert_eq!(
+            results[0].matches[0].chunk,
+            chunk("raise the price", Some(0))
+        );
+        assert!((results[0].matches[0].score - 1.0).abs() < 1e-6);
+        assert_eq!(results[1].session.id, offsite.id);
+
+        let results = db
+            .semantic_search_sessions(&user.id, "test", vec![1.0, 0.0, 0.0], Some(1))
+            .await
+            .unwrap();
+        assert_eq!(results.len(), 1);
+
+        // Many close chunks of one session don't crowd the others out of the limit.
+        db.replace_session_embeddings(
+            &pricing.id,
+            "test",
+            (0..50)
+                .map(|i| (chunk("price", Some(i * 1000)), vec![1.0, 0.0, 0.0]))
+                .collect(),
+        )
+        .await
+        .unwrap();
+        let results = db
+            .semantic_search_sessions(&user.id, "test", vec![1.0, 0.0, 0.0], Some(2))
+            .await
+            .unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].matches.len(), MATCHES_PER_SESSION);
+        assert_eq!(results[1].session.id, offsite.id);
+
+        // Embeddings of another model are never compared.
+        let results = db
+            .semantic_search_sessions(&user.id, "other", vec![1.0, 0.0, 0.0], None)
+            .await
+            .unwrap();
+        assert!(results.is_empty());
+
+        db.delete_session(&pricing.id).await.unwrap();
+        let results = db
+            .semantic_search_sessions(&user.id, "test", vec![1.0, 0.0, 0.0], None)
+            .await
+            .unwrap();
+        assert_eq!(results.len(), 1);
+    }
+}
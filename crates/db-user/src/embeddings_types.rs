@@ -0,0 +1,28 @@
+use crate::{user_common_derives, SearchField, Session};
+
+user_common_derives! {
+    /// A span of a session small enough to be embedded on its own.
+    pub struct SessionChunk {
+        pub field: SearchField,
+        pub text: String,
+        /// Only set for transcript chunks with timestamps.
+        pub start_ms: Option<u64>,
+        pub end_ms: Option<u64>,
+    }
+}
+
+user_common_derives! {
+    pub struct SemanticMatch {
+        pub chunk: SessionChunk,
+        /// Cosine similarity with the query, higher is closer.
+        pub score: f32,
+    }
+}
+
+user_common_derives! {
+    pub struct SemanticSearchResult {
+        pub session: Session,
+        /// Closest chunks of the session, closest first.
+        pub matches: Vec<SemanticMatch>,
+    }
+}
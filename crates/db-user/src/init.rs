@@ -398,6 +398,7 @@ pub async fn seed(db: &UserDatabase, user_id: impl Into<String>) -> Result<(), c
 
     for (i, session) in sessions.iter().enumerate() {
         let s = db.upsert_session(session.clone()).await?;
+        db.append_words(&s.id, session.words.clone()).await?;
 
         if i == 0 {
             db.session_add_participant(s.id, &alex.id).await?;
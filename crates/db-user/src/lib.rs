@@ -6,6 +6,8 @@ mod chat_messages_ops;
 mod chat_messages_types;
 mod config_ops;
 mod config_types;
+mod embeddings_ops;
+mod embeddings_types;
 mod events_ops;
 mod events_types;
 mod extensions_ops;
@@ -14,12 +16,17 @@ mod humans_ops;
 mod humans_types;
 mod organizations_ops;
 mod organizations_types;
+mod search_ops;
+mod search_types;
 mod sessions_ops;
 mod sessions_types;
 mod tags_ops;
 mod tags_types;
 mod templates_ops;
 mod templates_types;
+mod voiceprints_ops;
+mod voiceprints_types;
+mod words_ops;
 
 #[allow(unused)]
 pub use calendars_ops::*;
@@ -38,6 +45,10 @@ pub use config_ops::*;
 #[allow(unused)]
 pub use config_types::*;
 #[allow(unused)]
+pub use embeddings_ops::*;
+#[allow(unused)]
+pub use embeddings_types::*;
+#[allow(unused)]
 pub use events_ops::*;
 #[allow(unused)]
 pub use events_types::*;
@@ -54,6 +65,10 @@ pub use organizations_ops::*;
 #[allow(unused)]
 pub use organizations_types::*;
 #[allow(unused)]
+pub use search_ops::*;
+#[allow(unused)]
+pub use search_types::*;
+#[allow(unused)]
 pub use sessions_ops::*;
 #[allow(unused)]
 pub use sessions_types::*;
@@ -65,6 +80,12 @@ pub use tags_types::*;
 pub use templates_ops::*;
 #[allow(unused)]
 pub use templates_types::*;
+#[allow(unused)]
+pub use voiceprints_ops::*;
+#[allow(unused)]
+pub use voiceprints_types::*;
+#[allow(unused)]
+pub use words_ops::*;
 
 pub mod init;
 
@@ -129,7 +150,7 @@ impl std::ops::Deref for UserDatabase {
 }
 
 // Append only. Do not reorder.
-const MIGRATIONS: [&str; 15] = [
+const MIGRATIONS: [&str; 20] = [
     include_str!("./calendars_migration.sql"),
     include_str!("./configs_migration.sql"),
     include_str!("./events_migration.sql"),
@@ -145,6 +166,11 @@ const MIGRATIONS: [&str; 15] = [
     include_str!("./tag_sessions_migration.sql"),
     include_str!("./calendars_migration_1.sql"),
     include_str!("./sessions_migration_1.sql"),
+    include_str!("./voiceprints_migration.sql"),
+    include_str!("./words_migration.sql"),
+    include_str!("./words_migration_1.sql"),
+    include_str!("./search_migration.sql"),
+    include_str!("./embeddings_migration.sql"),
 ];
 
 pub async fn migrate(db: &UserDatabase) -> Result<(), crate::Error> {
@@ -152,6 +178,7 @@ pub async fn migrate(db: &UserDatabase) -> Result<(), crate::Error> {
     hypr_db_core::migrate(&conn, MIGRATIONS.to_vec()).await?;
 
     hypr_db_script::conversation_to_words::run(&conn).await;
+    db.ensure_search_index().await?;
 
     Ok(())
 }
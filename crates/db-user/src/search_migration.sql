@@ -0,0 +1,6 @@
+CREATE VIRTUAL TABLE IF NOT EXISTS session_search USING fts5(
+  session_id UNINDEXED,
+  field UNINDEXED,
+  content,
+  tokenize = 'unicode61 remove_diacritics 2'
+);
@@ -0,0 +1,515 @@
+use std::{collections::HashMap, str::FromStr};
+
+use super::{SearchField, SearchSnippet, Session, SessionSearchResult, SnippetPart, UserDatabase};
+
+// Private use characters, so they can't collide with anything in the notes.
+const HIGHLIGHT_START: char = '\u{E000}';
+const HIGHLIGHT_END: char = '\u{E001}';
+const SNIPPET_TOKENS: usize = 24;
+
+impl UserDatabase {
+    /// Sessions matching every term of `query` in their title, memos or transcript, best match first.
+    pub async fn search_sessions(
+        &self,
+        user_id: impl Into<String>,
+        query: impl AsRef<str>,
+        limit: Option<u8>,
+    ) -> Result<Vec<SessionSearchResult>, crate::Error> {
+        let Some(query) = to_match_query(query.as_ref()) else {
+            return Ok(vec![]);
+        };
+
+        let conn = self.conn()?;
+
+        let sql = format!(
+            "SELECT
+                session_search.session_id,
+                session_search.field,
+                snippet(session_search, 2, '{}', '{}', '…', {}),
+                bm25(session_search)
+            FROM session_search
+            JOIN sessions ON sessions.id = session_search.session_id
+            WHERE session_search MATCH ? AND sessions.user_id = ?
+            ORDER BY bm25(session_search)",
+            HIGHLIGHT_START, HIGHLIGHT_END, SNIPPET_TOKENS
+        );
+        let mut rows = conn.query(&sql, vec![query, user_id.into()]).await?;
+
+        // BM25 scores are negative, lower is better. A session ranks by its best matching field.
+        let mut scores: Vec<(String, f64)> = Vec::new();
+        let mut snippets: HashMap<String, Vec<SearchSnippet>> = HashMap::new();
+
+        while let Some(row) = rows.next().await? {
+            let session_id: String = row.get(0)?;
+            let Ok(field) = SearchField::from_str(row.get_str(1)?) else {
+                continue;
+            };
+            let snippet: String = row.get(2)?;
+            let score = row.get::<f64>(3)? * field.weight();
+
+            match scores.iter_mut().find(|(id, _)| id == &session_id) {
+                Some((_, best)) => *best = best.min(score),
+                None => scores.push((session_id.clone(), score)),
+            }
+
+            let session_snippets = snippets.entry(session_id).or_default();
+            if session_snippets.iter().all(|s| s.field != field) {
+                session_snippets.push(SearchSnippet {
+                    field,
+                    parts: to_snippet_parts(&snippet),
+                });
+            }
+        }
+
+        scores.sort_by(|a, b| a.1.total_cmp(&b.1));
+        scores.truncate(limit.unwrap_or(20) as usize);
+
+        let mut items = Vec::with_capacity(scores.len());
+        for (session_id, _) in scores {
+            let Some(session) = self.get_session_without_words(&session_id).await? else {
+                continue;
+            };
+
+            items.push(SessionSearchResult {
+                session,
+                snippets: snippets.remove(&session_id).unwrap_or_default(),
+            });
+        }
+        Ok(items)
+    }
+
+    /// Indexes every session from scratch, for databases created before the index existed.
+    pub async fn rebuild_search_index(&self) -> Result<(), crate::Error> {
+        let conn = self.conn()?;
+        conn.execute("DELETE FROM session_search", ()).await?;
+
+        let mut rows = conn.query("SELECT * FROM sessions", ()).await?;
+        let mut sessions = Vec::new();
+        while let Some(row) = rows.next().await? {
+            sessions.push(Session::from_row(&row)?);
+        }
+
+        for session in sessions {
+            index_session(&conn, &session).await?;
+            index_transcript(&conn, &session.id).await?;
+        }
+
+        Ok(())
+    }
+
+    pub(crate) async fn ensure_search_index(&self) -> Result<(), crate::Error> {
+        let conn = self.conn()?;
+
+        let mut rows = conn
+            .query(
+                "SELECT
+                    (SELECT COUNT(*) FROM session_search),
+                    (SELECT COUNT(*) FROM sessions)",
+                (),
+            )
+            .await?;
+        let row = rows.next().await?.unwrap();
+        let (indexed, sessions): (i64, i64) = (row.get(0)?, row.get(1)?);
+
+        if indexed == 0 && sessions > 0 {
+            self.rebuild_search_index().await?;
+        }
+        Ok(())
+    }
+}
+
+/// Replaces the indexed title and memos of the session. The transcript is indexed as words are stored.
+pub(crate) async fn index_session(
+    conn: &libsql::Connection,
+    session: &Session,
+) -> Result<(), crate::Error> {
+    conn.execute(
+        "DELETE FROM session_search WHERE session_id = ? AND field != ?",
+        vec![session.id.clone(), SearchField::Transcript.to_string()],
+    )
+    .await?;
+
+    let fields = [
+        (SearchField::Title, session.title.clone()),
+        (SearchField::RawMemo, html_to_text(&session.raw_memo_html)),
+        (
+            SearchField::EnhancedMemo,
+            session
+                .enhanced_memo_html
+                .as_deref()
+                .map(html_to_text)
+                .unwrap_or_default(),
+        ),
+    ];
+
+    for (field, content) in fields {
+        insert_content(conn, &session.id, field, &content).await?;
+    }
+    Ok(())
+}
+
+/// Replaces the indexed transcript of the session with the words stored for it, so there is
+/// always a single transcript row however often words are appended.
+pub(crate) async fn index_transcript(
+    conn: &libsql::Connection,
+    session_id: &str,
+) -> Result<(), crate::Error> {
+    unindex_session(conn, session_id, Some(SearchField::Transcript)).await?;
+
+    let mut rows = conn
+        .query(
+            "SELECT text FROM words WHERE session_id = ? ORDER BY seq ASC",
+            vec![session_id.to_string()],
+        )
+        .await?;
+
+    let mut texts = Vec::new();
+    while let Some(row) = rows.next().await? {
+        texts.push(row.get::<String>(0)?.trim().to_string());
+    }
+
+    insert_content(conn, session_id, SearchField::Transcript, &texts.join(" ")).await
+}
+
+/// Removes the session from the index, or only its transcript when `field` is given.
+pub(crate) async fn unindex_session(
+    conn: &libsql::Connection,
+    session_id: &str,
+    field: Option<SearchField>,
+) -> Result<(), crate::Error> {
+    match field {
+        None => {
+            conn.execute(
+                "DELETE FROM session_search WHERE session_id = ?",
+                vec![session_id.to_string()],
+            )
+            .await?
+        }
+        Some(field) => {
+            conn.execute(
+                "DELETE FROM session_search WHERE session_id = ? AND field = ?",
+                vec![session_id.to_string(), field.to_string()],
+            )
+            .await?
+        }
+    };
+    Ok(())
+}
+
+async fn insert_content(
+    conn: &libsql::Connection,
+    session_id: &str,
+    field: SearchField,
+    content: &str,
+) -> Result<(), crate::Error> {
+    if content.trim().is_empty() {
+        return Ok(());
+    }
+
+    conn.execute(
+        "INSERT INTO session_search (session_id, field, content) VALUES (?, ?, ?)",
+        vec![session_id.to_string(), field.to_string(), segment(content)],
+    )
+    .await?;
+    Ok(())
+}
+
+fn is_cjk(c: char) -> bool {
+    matches!(c,
+        '\u{3040}'..='\u{30FF}'   // Hiragana, Katakana
+        | '\u{3400}'..='\u{4DBF}' // CJK Extension A
+        | '\u{4E00}'..='\u{9FFF}' // CJK Unified Ideographs
+        | '\u{AC00}'..='\u{D7AF}' // Hangul Syllables
+        | '\u{F900}'..='\u{FAFF}' // CJK Compatibility Ideographs
+        | '\u{20000}'..='\u{2FFFF}'
+    )
+}
+
+/// The tokenizer keeps a run of CJK characters together as one token, so a search for "会议" would
+/// only match a sentence that is exactly "会议". Splitting them into one token each lets phrase
+/// queries match any part of the run.
+fn segment(text: &str) -> String {
+    let mut out = String::with_capacity(text.len() * 2);
+
+    for c in text.chars() {
+        if is_cjk(c) {
+            if !out.is_empty() && !out.ends_with(' ') {
+                out.push(' ');
+            }
+            out.push(c);
+            out.push(' ');
+        } else if c != ' ' || !out.ends_with(' ') {
+            // Runs of spaces collapse, so the space pushed after a CJK character is never doubled.
+            out.push(c);
+        }
+    }
+
+    out
+}
+
+/// Undoes [`segment`]. Spaces the original text had next to CJK characters are lost.
+fn desegment(text: &str) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let is_marker = |c: &char| *c == HIGHLIGHT_START || *c == HIGHLIGHT_END;
+
+    let mut out = String::with_capacity(text.len());
+    for (i, &c) in chars.iter().enumerate() {
+        if c == ' ' {
+            let prev = chars[..i].iter().rev().find(|c| !is_marker(c));
+            let next = chars[i + 1..].iter().find(|c| !is_marker(c));
+
+            if prev.is_some_and(|c| is_cjk(*c)) || next.is_some_and(|c| is_cjk(*c)) {
+                continue;
+            }
+        }
+        out.push(c);
+    }
+    out
+}
+
+fn to_snippet_parts(snippet: &str) -> Vec<SnippetPart> {
+    let mut parts: Vec<SnippetPart> = Vec::new();
+    let mut highlighted = false;
+
+    for c in desegment(snippet).chars() {
+        if c == HIGHLIGHT_START || c == HIGHLIGHT_END {
+            highlighted = c == HIGHLIGHT_START;
+            continue;
+        }
+
+        // Adjacent highlighted tokens read as one match.
+        match parts.last_mut() {
+            Some(part) if part.highlighted == highlighted => part.text.push(c),
+            _ => parts.push(SnippetPart {
+                text: c.to_string(),
+                highlighted,
+            }),
+        }
+    }
+
+    parts
+}
+
+/// Every whitespace separated term has to match. Terms with CJK characters match as phrases
+/// anywhere in a run, others as prefixes of a word.
+fn to_match_query(query: &str) -> Option<String> {
+    let terms: Vec<String> = query
+        .split_whitespace()
+        .map(|term| {
+            let phrase = segment(term).trim().replace('"', "\"\"");
+
+            if term.chars().any(is_cjk) {
+                format!("\"{}\"", phrase)
+            } else {
+                format!("\"{}\"*", phrase)
+            }
+        })
+        .collect();
+
+    if terms.is_empty() {
+        None
+    } else {
+        Some(terms.join(" "))
+    }
+}
+
+pub(crate) fn html_to_text(html: &str) -> String {
+    const BLOCK_TAGS: &[&str] = &[
+        "p",
+        "br",
+        "div",
+        "li",
+        "ul",
+        "ol",
+        "h1",
+        "h2",
+        "h3",
+        "h4",
+        "h5",
+        "h6",
+        "blockquote",
+        "pre",
+        "tr",
+        "td",
+        "th",
+        "hr",
+    ];
+
+    let mut out = String::with_capacity(html.len());
+    let mut tag: Option<String> = None;
+
+    for c in html.chars() {
+        match (&mut tag, c) {
+            (None, '<') => tag = Some(String::new()),
+            (Some(name), '>') => {
+                let name = name
+                    .trim_start_matches('/')
+                    .split(|c: char| c.is_whitespace() || c == '/')
+                    .next()
+                    .unwrap_or_default()
+                    .to_lowercase();
+
+                if BLOCK_TAGS.contains(&name.as_str()) {
+                    out.push(' ');
+                }
+                tag = None;
+            }
+            (Some(name), c) => name.push(c),
+            (None, c) => out.push(c),
+        }
+    }
+
+    out.replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{tests::setup_db, Human};
+    use hypr_listener_interface::Word;
+
+    #[test]
+    fn test_segment() {
+        assert_eq!(
+            segment("今天的会议 about Rust"),
+            "今 天 的 会 议 about Rust"
+        );
+        assert_eq!(
+            desegment(&format!(
+                "今 天 的 {}会 议{} about Rust",
+                HIGHLIGHT_START, HIGHLIGHT_END
+            )),
+            format!("今天的{}会议{}about Rust", HIGHLIGHT_START, HIGHLIGHT_END)
+        );
+        assert_eq!(
+            to_match_query("会议 rust \"x"),
+            Some("\"会 议\" \"rust\"* \"\"\"x\"*".to_string())
+        );
+        assert_eq!(to_match_query("   "), None);
+    }
+
+    #[test]
+    fn test_html_to_text() {
+        assert_eq!(
+            html_to_text("<h1>Title</h1><p>one <b>bold</b>&amp;more</p><ul><li>a</li></ul>"),
+            " Title  one bold&more   a  "
+        );
+    }
+
+    #[tokio::test]
+    async fn test_search_sessions() {
+        let db = setup_db().await;
+        let user = db.upsert_human(Human::default()).await.unwrap();
+
+        let new_session = |title: &str, raw_memo_html: &str| Session {
+            id: uuid::Uuid::new_v4().to_string(),
+            user_id: user.id.clone(),
+            created_at: chrono::Utc::now(),
+            visited_at: chrono::Utc::now(),
+            calendar_event_id: None,
+            title: title.to_string(),
+            raw_memo_html: raw_memo_html.to_string(),
+            enhanced_memo_html: None,
+            conversations: vec![],
+            words: vec![],
+        };
+
+        let roadmap = db
+            .upsert_session(new_session("Roadmap review", "<p>Q3 planning</p>"))
+            .await
+            .unwrap();
+        let standup = db
+            .upsert_session(new_session("周一例会", "<p>讨论了产品路线图</p>"))
+            .await
+            .unwrap();
+
+        let words = |texts: &[&str]| -> Vec<Word> {
+            texts
+                .iter()
+                .map(|text| Word {
+                    text: text.to_string(),
+                    speaker: None,
+                    confidence: None,
+                    start_ms: None,
+                    end_ms: None,
+                })
+                .collect()
+        };
+        db.append_words(&standup.id, words(&["we", "should", "review"]))
+            .await
+            .unwrap();
+        db.append_words(&standup.id, words(&["the", "roadmap"]))
+            .await
+            .unwrap();
+
+        // Appends keep a single transcript row, so terms from different appends match together.
+        let mut rows = db
+            .conn()
+            .unwrap()
+            .query(
+                "SELECT COUNT(*) FROM session_search WHERE session_id = ? AND field = ?",
+                vec![standup.id.clone(), SearchField::Transcript.to_string()],
+            )
+            .await
+            .unwrap();
+        let count: i64 = rows.next().await.unwrap().unwrap().get(0).unwrap();
+        assert_eq!(count, 1);
+        let results = db
+            .search_sessions(&user.id, "should roadmap", None)
+            .await
+            .unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].session.id, standup.id);
+        assert!(results[0].session.words.is_empty());
+
+        // Both match, but the title match ranks first.
+        let results = db.search_sessions(&user.id, "road", None).await.unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].session.id, roadmap.id);
+        assert_eq!(results[1].snippets[0].field, SearchField::Transcript);
+
+        let results = db.search_sessions(&user.id, "路线", None).await.unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].session.id, standup.id);
+        assert_eq!(
+            results[0].snippets[0].parts,
+            vec![
+                SnippetPart {
+                    text: "讨论了产品".to_string(),
+                    highlighted: false,
+                },
+                SnippetPart {
+                    text: "路线".to_string(),
+                    highlighted: true,
+                },
+                SnippetPart {
+                    text: "图".to_string(),
+                    highlighted: false,
+                },
+            ]
+        );
+
+        let mut standup = standup;
+        standup.raw_memo_html = "".to_string();
+        db.upsert_session(standup.clone()).await.unwrap();
+        assert!(db
+            .search_sessions(&user.id, "路线", None)
+            .await
+            .unwrap()
+            .is_empty());
+
+        db.delete_session(&roadmap.id).await.unwrap();
+        assert_eq!(
+            db.search_sessions(&user.id, "road", None)
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+    }
+}
@@ -0,0 +1,53 @@
+use crate::{user_common_derives, Session};
+
+user_common_derives! {
+    #[derive(Copy, strum::EnumString, strum::Display)]
+    pub enum SearchField {
+        #[serde(rename = "title")]
+        #[strum(serialize = "title")]
+        Title,
+        #[serde(rename = "rawMemo")]
+        #[strum(serialize = "raw_memo")]
+        RawMemo,
+        #[serde(rename = "enhancedMemo")]
+        #[strum(serialize = "enhanced_memo")]
+        EnhancedMemo,
+        #[serde(rename = "transcript")]
+        #[strum(serialize = "transcript")]
+        Transcript,
+    }
+}
+
+impl SearchField {
+    // Multiplies the BM25 score of a match, so a title hit outranks the same hit in a long transcript.
+    pub(crate) fn weight(&self) -> f64 {
+        match self {
+            SearchField::Title => 4.0,
+            SearchField::EnhancedMemo => 2.0,
+            SearchField::RawMemo => 2.0,
+            SearchField::Transcript => 1.0,
+        }
+    }
+}
+
+user_common_derives! {
+    pub struct SnippetPart {
+        pub text: String,
+        pub highlighted: bool,
+    }
+}
+
+user_common_derives! {
+    pub struct SearchSnippet {
+        pub field: SearchField,
+        pub parts: Vec<SnippetPart>,
+    }
+}
+
+user_common_derives! {
+    pub struct SessionSearchResult {
+        pub session: Session,
+        /// Best match in each field that matched, best field first.
+        pub snippets: Vec<SearchSnippet>,
+    }
+}
@@ -16,7 +16,19 @@ impl UserDatabase {
             title = '' AND
             raw_memo_html = '' AND 
             (enhanced_memo_html IS NULL OR enhanced_memo_html = '') AND 
-            conversations = '[]'",
+            conversations = '[]' AND
+            NOT EXISTS (SELECT 1 FROM words WHERE words.session_id = sessions.id)",
+            (),
+        )
+        .await?;
+
+        conn.execute(
+            "DELETE FROM session_search WHERE session_id NOT IN (SELECT id FROM sessions)",
+            (),
+        )
+        .await?;
+        conn.execute(
+            "DELETE FROM session_embeddings WHERE session_id NOT IN (SELECT id FROM sessions)",
             (),
         )
         .await?;
@@ -32,27 +44,6 @@ impl UserDatabase {
         Ok(words)
     }
 
-    pub async fn get_words(
-        &self,
-        session_id: impl Into<String>,
-    ) -> Result<Vec<hypr_listener_interface::Word>, crate::Error> {
-        let conn = self.conn()?;
-        let mut rows = conn
-            .query(
-                "SELECT words FROM sessions WHERE id = ?",
-                vec![session_id.into()],
-            )
-            .await?;
-
-        match rows.next().await? {
-            None => Ok(vec![]),
-            Some(row) => {
-                let words_str: String = row.get(0)?;
-                Ok(serde_json::from_str(&words_str).unwrap_or_default())
-            }
-        }
-    }
-
     pub async fn get_session(
         &self,
         filter: GetSessionFilter,
@@ -83,12 +74,30 @@ impl UserDatabase {
         match rows.next().await? {
             None => Ok(None),
             Some(row) => {
-                let item = Session::from_row(&row)?;
+                let mut item = Session::from_row(&row)?;
+                item.words = self.get_words(&item.id).await?;
                 Ok(Some(item))
             }
         }
     }
 
+    /// The session without its words, for callers that only show its details.
+    pub(crate) async fn get_session_without_words(
+        &self,
+        id: impl Into<String>,
+    ) -> Result<Option<Session>, crate::Error> {
+        let conn = self.conn()?;
+
+        let mut rows = conn
+            .query("SELECT * FROM sessions WHERE id = ?", vec![id.into()])
+            .await?;
+
+        match rows.next().await? {
+            None => Ok(None),
+            Some(row) => Ok(Some(Session::from_row(&row)?)),
+        }
+    }
+
     pub async fn visit_session(&self, id: impl Into<String>) -> Result<(), crate::Error> {
         let conn = self.conn()?;
 
@@ -103,11 +112,21 @@ impl UserDatabase {
     pub async fn delete_session(&self, id: impl Into<String>) -> Result<(), crate::Error> {
         let conn = self.conn()?;
 
-        conn.execute("DELETE FROM sessions WHERE id = ?", vec![id.into()])
+        let id = id.into();
+        crate::search_ops::unindex_session(&conn, &id, None).await?;
+        conn.execute(
+            "DELETE FROM session_embeddings WHERE session_id = ?",
+            vec![id.clone()],
+        )
+        .await?;
+        conn.execute("DELETE FROM words WHERE session_id = ?", vec![id.clone()])
+            .await?;
+        conn.execute("DELETE FROM sessions WHERE id = ?", vec![id])
             .await?;
         Ok(())
     }
 
+    /// Sessions come without their words, which can be long. `get_session` loads them.
     pub async fn list_sessions(
         &self,
         filter: Option<ListSessionFilter>,
@@ -165,6 +184,13 @@ impl UserDatabase {
                 )
                 .await?
             }
+            Some(ListSessionFilter {
+                common: ListSessionFilterCommon { user_id, limit },
+                specific: ListSessionFilterSpecific::FullText { query },
+            }) => {
+                let results = self.search_sessions(user_id, query, limit).await?;
+                return Ok(results.into_iter().map(|r| r.session).collect());
+            }
             None => {
                 conn.query(
                     "SELECT * FROM sessions ORDER BY created_at DESC LIMIT 100",
@@ -182,6 +208,7 @@ impl UserDatabase {
         Ok(items)
     }
 
+    /// Words are left as they are. They only change through `append_words` and `replace_words`.
     pub async fn upsert_session(&self, session: Session) -> Result<Session, crate::Error> {
         let conn = self.conn()?;
 
@@ -196,8 +223,7 @@ impl UserDatabase {
                     title,
                     raw_memo_html,
                     enhanced_memo_html,
-                    conversations,
-                    words
+                    conversations
                 ) VALUES (
                     :id,
                     :created_at,
@@ -207,8 +233,7 @@ impl UserDatabase {
                     :title,
                     :raw_memo_html,
                     :enhanced_memo_html,
-                    :conversations,
-                    :words
+                    :conversations
                 )
                 ON CONFLICT(id) DO UPDATE SET
                     created_at = :created_at,
@@ -218,8 +243,7 @@ impl UserDatabase {
                     title = :title,
                     raw_memo_html = :raw_memo_html,
                     enhanced_memo_html = :enhanced_memo_html,
-                    conversations = :conversations,
-                    words = :words
+                    conversations = :conversations
                 RETURNING *",
                 libsql::named_params! {
                     ":id": session.id.clone(),
@@ -231,13 +255,14 @@ impl UserDatabase {
                     ":raw_memo_html": session.raw_memo_html.clone(),
                     ":enhanced_memo_html": session.enhanced_memo_html.clone(),
                     ":conversations": "[]",
-                    ":words": serde_json::to_string(&session.words).unwrap(),
                 },
             )
             .await?;
 
         let row = rows.next().await?.unwrap();
-        let session = Session::from_row(&row)?;
+        let mut session = Session::from_row(&row)?;
+        crate::search_ops::index_session(&conn, &session).await?;
+        session.words = self.get_words(&session.id).await?;
         Ok(session)
     }
 
@@ -341,7 +366,7 @@ impl UserDatabase {
 
 #[cfg(test)]
 mod tests {
-    use crate::{tests::setup_db, Human, Session};
+    use crate::{tests::setup_db, GetSessionFilter, Human, Session};
 
     #[tokio::test]
     async fn test_sessions() {
@@ -377,7 +402,16 @@ mod tests {
             }],
         };
 
-        let mut session = db.upsert_session(session).await.unwrap();
+        let words = session.words.clone();
+        let session = db.upsert_session(session).await.unwrap();
+        assert_eq!(session.words.len(), 0);
+
+        db.append_words(&session.id, words).await.unwrap();
+        let mut session = db
+            .get_session(GetSessionFilter::Id(session.id))
+            .await
+            .unwrap()
+            .unwrap();
         assert_eq!(session.raw_memo_html, "raw_memo_html_1");
         assert_eq!(session.enhanced_memo_html, None);
         assert_eq!(session.title, "test");
@@ -385,6 +419,7 @@ mod tests {
 
         let sessions = db.list_sessions(None).await.unwrap();
         assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0].words.len(), 0);
 
         session.raw_memo_html = "raw_memo_html_2".to_string();
         let session = db.upsert_session(session).await.unwrap();
@@ -41,10 +41,8 @@ impl Session {
             raw_memo_html: row.get(6).expect("raw_memo_html"),
             enhanced_memo_html: row.get(7).expect("enhanced_memo_html"),
             conversations: vec![],
-            words: row
-                .get_str(9)
-                .map(|s| serde_json::from_str(s).unwrap())
-                .unwrap(),
+            // Stored in the `words` table. The `words` column is only kept for older versions.
+            words: vec![],
         })
     }
 
@@ -89,6 +87,9 @@ user_common_derives! {
     pub enum ListSessionFilterSpecific {
         #[serde(rename = "search")]
         Search { query: String },
+        /// Titles, memos and transcripts, ranked by relevance. See `search_sessions` for snippets.
+        #[serde(rename = "fullText")]
+        FullText { query: String },
         #[serde(rename = "recentlyVisited")]
         RecentlyVisited {},
         #[serde(rename = "dateRange")]
@@ -0,0 +1,121 @@
+use hypr_db_core::SqlTable;
+
+use super::{UserDatabase, Voiceprint};
+
+impl UserDatabase {
+    pub async fn upsert_voiceprint(
+        &self,
+        voiceprint: Voiceprint,
+    ) -> Result<Voiceprint, crate::Error> {
+        let conn = self.conn()?;
+
+        let sql = format!(
+            "INSERT INTO {} (
+                id,
+                human_id,
+                created_at,
+                embedding
+            ) VALUES (?, ?, ?, ?)
+            ON CONFLICT (id) DO UPDATE SET
+                human_id = excluded.human_id,
+                embedding = excluded.embedding
+            RETURNING *",
+            Voiceprint::sql_table()
+        );
+
+        let mut rows = conn
+            .query(
+                &sql,
+                vec![
+                    voiceprint.id,
+                    voiceprint.human_id,
+                    voiceprint.created_at.to_rfc3339(),
+                    serde_json::to_string(&voiceprint.embedding).unwrap(),
+                ],
+            )
+            .await?;
+
+        let row = rows.next().await?.unwrap();
+        let voiceprint = Voiceprint::from_row(&row)?;
+        Ok(voiceprint)
+    }
+
+    pub async fn delete_voiceprint(&self, id: impl Into<String>) -> Result<(), crate::Error> {
+        let conn = self.conn()?;
+
+        let sql = format!("DELETE FROM {} WHERE id = ?", Voiceprint::sql_table());
+        conn.query(&sql, vec![id.into()]).await?;
+        Ok(())
+    }
+
+    /// Voiceprints of one human, or of everyone when `human_id` is `None`.
+    pub async fn list_voiceprints(
+        &self,
+        human_id: Option<String>,
+    ) -> Result<Vec<Voiceprint>, crate::Error> {
+        let conn = self.conn()?;
+
+        let mut rows = match human_id {
+            None => {
+                let sql = format!(
+                    "SELECT * FROM {} ORDER BY created_at ASC",
+                    Voiceprint::sql_table()
+                );
+                conn.query(&sql, ()).await?
+            }
+            Some(human_id) => {
+                let sql = format!(
+                    "SELECT * FROM {} WHERE human_id = ? ORDER BY created_at ASC",
+                    Voiceprint::sql_table()
+                );
+                conn.query(&sql, vec![human_id]).await?
+            }
+        };
+
+        let mut items = Vec::new();
+        while let Some(row) = rows.next().await? {
+            let item = Voiceprint::from_row(&row)?;
+            items.push(item);
+        }
+        Ok(items)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{tests::setup_db, Human, Voiceprint};
+
+    #[tokio::test]
+    async fn test_voiceprints() {
+        let db = setup_db().await;
+
+        let human = db.upsert_human(Human::default()).await.unwrap();
+        let other = db.upsert_human(Human::default()).await.unwrap();
+
+        let voiceprint = db
+            .upsert_voiceprint(Voiceprint::new(&human.id, vec![0.1, 0.2, 0.3]))
+            .await
+            .unwrap();
+        assert_eq!(voiceprint.embedding, vec![0.1, 0.2, 0.3]);
+
+        db.upsert_voiceprint(Voiceprint::new(&other.id, vec![0.3, 0.2, 0.1]))
+            .await
+            .unwrap();
+
+        assert_eq!(db.list_voiceprints(None).await.unwrap().len(), 2);
+        assert_eq!(
+            db.list_voiceprints(Some(human.id.clone()))
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+
+        db.delete_voiceprint(&voiceprint.id).await.unwrap();
+        assert!(db
+            .list_voiceprints(Some(human.id))
+            .await
+            .unwrap()
+            .is_empty());
+    }
+}
@@ -0,0 +1,42 @@
+use chrono::{DateTime, Utc};
+
+use crate::user_common_derives;
+
+user_common_derives! {
+    #[sql_table("voiceprints")]
+    pub struct Voiceprint {
+        pub id: String,
+        pub human_id: String,
+        pub created_at: DateTime<Utc>,
+        /// Speaker embedding, compared by cosine similarity.
+        pub embedding: Vec<f32>,
+    }
+}
+
+impl Voiceprint {
+    pub fn new(human_id: impl Into<String>, embedding: Vec<f32>) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            human_id: human_id.into(),
+            created_at: Utc::now(),
+            embedding,
+        }
+    }
+
+    pub fn from_row(row: &libsql::Row) -> Result<Self, serde::de::value::Error> {
+        Ok(Self {
+            id: row.get(0).expect("id"),
+            human_id: row.get(1).expect("human_id"),
+            created_at: {
+                let str = row.get_str(2).expect("created_at");
+                DateTime::parse_from_rfc3339(str)
+                    .unwrap()
+                    .with_timezone(&Utc)
+            },
+            embedding: row
+                .get_str(3)
+                .map(|s| serde_json::from_str(s).unwrap())
+                .unwrap(),
+        })
+    }
+}
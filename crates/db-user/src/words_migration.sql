@@ -0,0 +1,11 @@
+CREATE TABLE IF NOT EXISTS words (
+  session_id TEXT NOT NULL,
+  seq INTEGER NOT NULL,
+  text TEXT NOT NULL,
+  speaker TEXT DEFAULT NULL,
+  confidence REAL DEFAULT NULL,
+  start_ms INTEGER DEFAULT NULL,
+  end_ms INTEGER DEFAULT NULL,
+  PRIMARY KEY (session_id, seq),
+  FOREIGN KEY (session_id) REFERENCES sessions(id) ON DELETE CASCADE
+);
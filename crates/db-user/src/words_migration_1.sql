@@ -0,0 +1,23 @@
+INSERT
+  OR IGNORE INTO words (
+    session_id,
+    seq,
+    text,
+    speaker,
+    confidence,
+    start_ms,
+    end_ms
+  )
+SELECT
+  s.id,
+  CAST(w.key AS INTEGER),
+  json_extract(w.value, '$.text'),
+  json_extract(w.value, '$.speaker'),
+  json_extract(w.value, '$.confidence'),
+  json_extract(w.value, '$.start_ms'),
+  json_extract(w.value, '$.end_ms')
+FROM
+  sessions s,
+  json_each(s.words) w
+WHERE
+  json_valid(s.words);
@@ -0,0 +1,233 @@
+use hypr_listener_interface::Word;
+
+use super::UserDatabase;
+
+impl UserDatabase {
+    /// Adds words after the ones already stored for the session, without touching the rest.
+    pub async fn append_words(
+        &self,
+        session_id: impl Into<String>,
+        words: Vec<Word>,
+    ) -> Result<(), crate::Error> {
+        if words.is_empty() {
+            return Ok(());
+        }
+
+        let session_id = session_id.into();
+        let conn = self.conn()?;
+        let tx = conn.transaction().await?;
+
+        let next_seq: i64 = {
+            let mut rows = tx
+                .query(
+                    "SELECT COALESCE(MAX(seq) + 1, 0) FROM words WHERE session_id = ?",
+                    vec![session_id.clone()],
+                )
+                .await?;
+            let row = rows.next().await?.unwrap();
+            row.get(0)?
+        };
+
+        for (i, word) in words.into_iter().enumerate() {
+            insert_word(&tx, &session_id, next_seq + i as i64, word).await?;
+        }
+
+        crate::search_ops::index_transcript(&tx, &session_id).await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Replaces every word of the session, for edits made to the transcript as a whole.
+    pub async fn replace_words(
+        &self,
+        session_id: impl Into<String>,
+        words: Vec<Word>,
+    ) -> Result<(), crate::Error> {
+        let session_id = session_id.into();
+        let conn = self.conn()?;
+        let tx = conn.transaction().await?;
+
+        tx.execute(
+            "DELETE FROM words WHERE session_id = ?",
+            vec![session_id.clone()],
+        )
+        .await?;
+
+        for (i, word) in words.into_iter().enumerate() {
+            insert_word(&tx, &session_id, i as i64, word).await?;
+        }
+
+        crate::search_ops::index_transcript(&tx, &session_id).await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    pub async fn get_words(
+        &self,
+        session_id: impl Into<String>,
+    ) -> Result<Vec<Word>, crate::Error> {
+        let conn = self.conn()?;
+
+        let mut rows = conn
+            .query(
+                "SELECT text, speaker, confidence, start_ms, end_ms FROM words
+                WHERE session_id = ?
+                ORDER BY seq ASC",
+                vec![session_id.into()],
+            )
+            .await?;
+
+        let mut items = Vec::new();
+        while let Some(row) = rows.next().await? {
+            items.push(word_from_row(&row)?);
+        }
+        Ok(items)
+    }
+
+    /// Words starting within `[start_ms, end_ms)`. Words without timestamps are never included.
+    pub async fn get_words_in_range(
+        &self,
+        session_id: impl Into<String>,
+        start_ms: u64,
+        end_ms: u64,
+    ) -> Result<Vec<Word>, crate::Error> {
+        let conn = self.conn()?;
+
+        let mut rows = conn
+            .query(
+                "SELECT text, speaker, confidence, start_ms, end_ms FROM words
+                WHERE session_id = ? AND start_ms >= ? AND start_ms < ?
+                ORDER BY seq ASC",
+                (session_id.into(), start_ms as i64, end_ms as i64),
+            )
+            .await?;
+
+        let mut items = Vec::new();
+        while let Some(row) = rows.next().await? {
+            items.push(word_from_row(&row)?);
+        }
+        Ok(items)
+    }
+}
+
+async fn insert_word(
+    tx: &libsql::Transaction,
+    session_id: &str,
+    seq: i64,
+    word: Word,
+) -> Result<(), crate::Error> {
+    tx.execute(
+        "INSERT INTO words (
+            session_id,
+            seq,
+            text,
+            speaker,
+            confidence,
+            start_ms,
+            end_ms
+        ) VALUES (
+            :session_id,
+            :seq,
+            :text,
+            :speaker,
+            :confidence,
+            :start_ms,
+            :end_ms
+        )",
+        libsql::named_params! {
+            ":session_id": session_id,
+            ":seq": seq,
+            ":text": word.text,
+            ":speaker": word.speaker.map(|s| serde_json::to_string(&s).unwrap()),
+            ":confidence": word.confidence.map(|c| c as f64),
+            ":start_ms": word.start_ms.map(|ms| ms as i64),
+            ":end_ms": word.end_ms.map(|ms| ms as i64),
+        },
+    )
+    .await?;
+
+    Ok(())
+}
+
+fn word_from_row(row: &libsql::Row) -> Result<Word, crate::Error> {
+    Ok(Word {
+        text: row.get(0)?,
+        speaker: row
+            .get::<Option<String>>(1)?
+            .and_then(|s| serde_json::from_str(&s).ok()),
+        confidence: row.get::<Option<f64>>(2)?.map(|c| c as f32),
+        start_ms: row.get::<Option<i64>>(3)?.map(|ms| ms as u64),
+        end_ms: row.get::<Option<i64>>(4)?.map(|ms| ms as u64),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{tests::setup_db, Human, Session};
+    use hypr_listener_interface::{SpeakerIdentity, Word};
+
+    fn word(text: &str, start_ms: u64) -> Word {
+        Word {
+            text: text.to_string(),
+            speaker: Some(SpeakerIdentity::Unassigned { index: 0 }),
+            confidence: Some(0.5),
+            start_ms: Some(start_ms),
+            end_ms: Some(start_ms + 100),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_words() {
+        let db = setup_db().await;
+
+        let user = db.upsert_human(Human::default()).await.unwrap();
+        let session = db
+            .upsert_session(Session {
+                id: uuid::Uuid::new_v4().to_string(),
+                user_id: user.id.clone(),
+                created_at: chrono::Utc::now(),
+                visited_at: chrono::Utc::now(),
+                calendar_event_id: None,
+                title: "test".to_string(),
+                raw_memo_html: "".to_string(),
+                enhanced_memo_html: None,
+                conversations: vec![],
+                words: vec![],
+            })
+            .await
+            .unwrap();
+
+        db.append_words(&session.id, vec![word("hello", 0), word("world", 200)])
+            .await
+            .unwrap();
+        db.append_words(&session.id, vec![word("again", 400)])
+            .await
+            .unwrap();
+
+        let words = db.get_words(&session.id).await.unwrap();
+        assert_eq!(
+            words,
+            vec![word("hello", 0), word("world", 200), word("again", 400)]
+        );
+
+        let words = db.get_words_in_range(&session.id, 100, 400).await.unwrap();
+        assert_eq!(words, vec![word("world", 200)]);
+
+        // Saving the session doesn't touch its words.
+        let session = db.upsert_session(session).await.unwrap();
+        assert_eq!(session.words.len(), 3);
+
+        db.replace_words(&session.id, vec![word("edited", 0)])
+            .await
+            .unwrap();
+        assert_eq!(
+            db.get_words(&session.id).await.unwrap(),
+            vec![word("edited", 0)]
+        );
+
+        db.delete_session(&session.id).await.unwrap();
+        assert!(db.get_words(&session.id).await.unwrap().is_empty());
+    }
+}
@@ -1,10 +1,18 @@
-use hypr_onnx::ort;
+use hypr_onnx::{ndarray, ort};
 use serde::{ser::Serializer, Serialize};
 
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
     #[error(transparent)]
     OrtError(#[from] ort::Error),
+    #[error(transparent)]
+    ShapeError(#[from] ndarray::ShapeError),
+    #[error(transparent)]
+    FftError(#[from] realfft::FftError),
+    #[error("Invalid output")]
+    InvalidOutput,
+    #[error("Unsupported sample rate: {0}, expected 16000")]
+    UnsupportedSampleRate(u32),
 }
 
 impl Serialize for Error {
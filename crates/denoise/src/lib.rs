@@ -1,42 +1,177 @@
 mod error;
+mod stream;
 
 pub use error::*;
+pub use stream::*;
+
+use std::sync::Arc;
 
-#[allow(unused)]
 use hypr_onnx::{
-    load_model, ndarray as nd,
-    ort::{session::Session, value::Tensor},
+    load_model,
+    ndarray::{Array3, Array4},
+    ort::{self, session::Session},
 };
-
-#[allow(unused)]
 use realfft::{num_complex::Complex, ComplexToReal, RealFftPlanner, RealToComplex};
 
 const MODEL_1_BYTES: &[u8] = include_bytes!("../data/model_1.onnx");
 const MODEL_2_BYTES: &[u8] = include_bytes!("../data/model_2.onnx");
 
-#[allow(unused)]
-const BLOCK_LEN: usize = 512;
+pub const SAMPLE_RATE: u32 = 16000;
 
-#[allow(unused)]
+const BLOCK_LEN: usize = 512;
 const BLOCK_SHIFT: usize = 128;
+const FFT_BINS: usize = BLOCK_LEN / 2 + 1;
+// Two LSTM layers, each with a hidden and a cell state of 128 units.
+const STATE_SHAPE: (usize, usize, usize, usize) = (1, 2, 128, 2);
+
+/// Number of samples the output lags behind the input.
+pub const LATENCY: usize = BLOCK_LEN - BLOCK_SHIFT;
 
-#[allow(unused)]
 pub struct DTLN {
     model_1: Session,
     model_2: Session,
+    model_1_outputs: [String; 2],
+    model_2_outputs: [String; 2],
+    states_1: Array4<f32>,
+    states_2: Array4<f32>,
+    fft: Arc<dyn RealToComplex<f32>>,
+    ifft: Arc<dyn ComplexToReal<f32>>,
+    in_buffer: Vec<f32>,
+    out_buffer: Vec<f32>,
+    // Input not yet making up a whole block shift.
+    pending: Vec<f32>,
 }
 
 impl DTLN {
     pub fn new() -> Result<Self, crate::Error> {
+        let model_1 = load_model(MODEL_1_BYTES)?;
+        let model_2 = load_model(MODEL_2_BYTES)?;
+
+        let output_names = |session: &Session| -> Result<[String; 2], crate::Error> {
+            match session.outputs.as_slice() {
+                [first, second, ..] => Ok([first.name.clone(), second.name.clone()]),
+                _ => Err(crate::Error::InvalidOutput),
+            }
+        };
+        let model_1_outputs = output_names(&model_1)?;
+        let model_2_outputs = output_names(&model_2)?;
+
+        let mut planner = RealFftPlanner::<f32>::new();
+
         Ok(Self {
-            model_1: load_model(&MODEL_1_BYTES)?,
-            model_2: load_model(&MODEL_2_BYTES)?,
+            model_1,
+            model_2,
+            model_1_outputs,
+            model_2_outputs,
+            states_1: Array4::zeros(STATE_SHAPE),
+            states_2: Array4::zeros(STATE_SHAPE),
+            fft: planner.plan_fft_forward(BLOCK_LEN),
+            ifft: planner.plan_fft_inverse(BLOCK_LEN),
+            in_buffer: vec![0.0; BLOCK_LEN],
+            out_buffer: vec![0.0; BLOCK_LEN],
+            pending: Vec::with_capacity(BLOCK_SHIFT),
         })
     }
 
-    // https://github.com/breizhn/DTLN-aec/blob/main/run_aec.py
-    pub fn process(&self, input: &[f32]) -> Result<Vec<f32>, crate::Error> {
-        Ok(vec![0.0; input.len()])
+    /// Denoises the next part of a 16kHz mono stream.
+    ///
+    /// Only whole block shifts are processed, the rest is kept for the next call. The output is
+    /// delayed by [`LATENCY`] samples.
+    // https://github.com/breizhn/DTLN/blob/master/real_time_processing_onnx.py
+    pub fn process(&mut self, input: &[f32]) -> Result<Vec<f32>, crate::Error> {
+        self.pending.extend_from_slice(input);
+
+        let blocks = self.pending.len() / BLOCK_SHIFT;
+        let mut output = Vec::with_capacity(blocks * BLOCK_SHIFT);
+
+        for i in 0..blocks {
+            self.in_buffer.copy_within(BLOCK_SHIFT.., 0);
+            self.in_buffer[BLOCK_LEN - BLOCK_SHIFT..]
+                .copy_from_slice(&self.pending[i * BLOCK_SHIFT..(i + 1) * BLOCK_SHIFT]);
+
+            self.process_block()?;
+            output.extend_from_slice(&self.out_buffer[..BLOCK_SHIFT]);
+        }
+
+        self.pending.drain(..blocks * BLOCK_SHIFT);
+        Ok(output)
+    }
+
+    pub fn reset(&mut self) {
+        self.states_1.fill(0.0);
+        self.states_2.fill(0.0);
+        self.in_buffer.fill(0.0);
+        self.out_buffer.fill(0.0);
+        self.pending.clear();
+    }
+
+    fn process_block(&mut self) -> Result<(), crate::Error> {
+        let mut spectrum = self.fft.make_output_vec();
+        let mut block = self.in_buffer.clone();
+        self.fft.process(&mut block, &mut spectrum)?;
+
+        // First stage: a mask on the STFT magnitude, applied to the complex spectrum to keep the phase.
+        let magnitude = Array3::from_shape_vec(
+            (1, 1, FFT_BINS),
+            spectrum.iter().map(|c| c.norm()).collect(),
+        )?;
+
+        let result = self
+            .model_1
+            .run(ort::inputs![magnitude.view(), self.states_1.view()]?)?;
+
+        let mask = result
+            .get(&self.model_1_outputs[0])
+            .ok_or(Error::InvalidOutput)?
+            .try_extract_tensor::<f32>()?;
+        for (bin, m) in spectrum.iter_mut().zip(mask.iter()) {
+            *bin *= *m;
+        }
+
+        self.states_1 = result
+            .get(&self.model_1_outputs[1])
+            .ok_or(Error::InvalidOutput)?
+            .try_extract_tensor::<f32>()?
+            .to_owned()
+            .into_shape_with_order(STATE_SHAPE)?;
+
+        // The inverse transform rejects non-zero imaginary parts in the DC and Nyquist bins.
+        spectrum[0] = Complex::new(spectrum[0].re, 0.0);
+        spectrum[FFT_BINS - 1] = Complex::new(spectrum[FFT_BINS - 1].re, 0.0);
+
+        let mut estimated = self.ifft.make_output_vec();
+        self.ifft.process(&mut spectrum, &mut estimated)?;
+        // Unlike numpy's `irfft`, realfft doesn't normalize.
+        for sample in estimated.iter_mut() {
+            *sample /= BLOCK_LEN as f32;
+        }
+
+        // Second stage: refines the estimated block in a learned time-domain representation.
+        let estimated = Array3::from_shape_vec((1, 1, BLOCK_LEN), estimated)?;
+
+        let result = self
+            .model_2
+            .run(ort::inputs![estimated.view(), self.states_2.view()]?)?;
+
+        let out_block = result
+            .get(&self.model_2_outputs[0])
+            .ok_or(Error::InvalidOutput)?
+            .try_extract_tensor::<f32>()?;
+
+        self.out_buffer.copy_within(BLOCK_SHIFT.., 0);
+        self.out_buffer[BLOCK_LEN - BLOCK_SHIFT..].fill(0.0);
+        for (out, sample) in self.out_buffer.iter_mut().zip(out_block.iter()) {
+            *out += *sample;
+        }
+
+        self.states_2 = result
+            .get(&self.model_2_outputs[1])
+            .ok_or(Error::InvalidOutput)?
+            .try_extract_tensor::<f32>()?
+            .to_owned()
+            .into_shape_with_order(STATE_SHAPE)?;
+
+        Ok(())
     }
 }
 
@@ -44,8 +179,44 @@ impl DTLN {
 mod tests {
     use super::*;
 
+    fn to_f32(bytes: &[u8]) -> Vec<f32> {
+        bytes
+            .chunks_exact(2)
+            .map(|chunk| i16::from_le_bytes([chunk[0], chunk[1]]) as f32 / 32768.0)
+            .collect()
+    }
+
+    fn snr_db(clean: &[f32], processed: &[f32]) -> f32 {
+        let clean = &clean[..clean.len().min(processed.len())];
+        let signal: f32 = clean.iter().map(|s| s * s).sum();
+        let noise: f32 = clean
+            .iter()
+            .zip(processed.iter())
+            .map(|(c, p)| (c - p) * (c - p))
+            .sum();
+        10.0 * (signal / noise).log10()
+    }
+
     #[test]
     fn test_dtln() {
-        let _dtln = DTLN::new().unwrap();
+        let clean = to_f32(hypr_data::english_1::AUDIO);
+
+        let mut seed = 42u32;
+        let noisy: Vec<f32> = clean
+            .iter()
+            .map(|s| {
+                seed = seed.wrapping_mul(1664525).wrapping_add(1013904223);
+                let noise = (seed >> 8) as f32 / (1 << 24) as f32 - 0.5;
+                s + noise * 0.2
+            })
+            .collect();
+
+        let mut dtln = DTLN::new().unwrap();
+        let denoised = dtln.process(&noisy).unwrap();
+        assert_eq!(denoised.len(), noisy.len() / BLOCK_SHIFT * BLOCK_SHIFT);
+
+        let before = snr_db(&clean, &noisy);
+        let after = snr_db(&clean, &denoised[LATENCY..]);
+        assert!(after > before, "{} <= {}", after, before);
     }
 }
@@ -0,0 +1,108 @@
+use std::collections::VecDeque;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures_util::Stream;
+use kalosm_sound::AsyncSource;
+
+use crate::{DTLN, SAMPLE_RATE};
+
+// Enough to amortize the model calls without adding much to the model's own latency.
+const BATCH_SAMPLES: usize = 512;
+
+pub trait DenoiseExt: AsyncSource + Sized {
+    /// Removes background noise from a 16kHz mono source, delaying it by [`crate::LATENCY`] samples.
+    fn denoise(self) -> Result<DenoisedStream<Self>, crate::Error>
+    where
+        Self: Unpin,
+    {
+        DenoisedStream::new(self)
+    }
+}
+
+impl<T: AsyncSource> DenoiseExt for T {}
+
+pub struct DenoisedStream<S: AsyncSource + Unpin> {
+    source: S,
+    dtln: DTLN,
+    input: Vec<f32>,
+    output: VecDeque<f32>,
+    finished: bool,
+}
+
+impl<S: AsyncSource + Unpin> DenoisedStream<S> {
+    pub fn new(source: S) -> Result<Self, crate::Error> {
+        let sample_rate = source.sample_rate();
+        if sample_rate != SAMPLE_RATE {
+            return Err(crate::Error::UnsupportedSampleRate(sample_rate));
+        }
+
+        Ok(Self {
+            source,
+            dtln: DTLN::new()?,
+            input: Vec::with_capacity(BATCH_SAMPLES),
+            output: VecDeque::with_capacity(BATCH_SAMPLES),
+            finished: false,
+        })
+    }
+
+    fn flush(&mut self) {
+        match self.dtln.process(&self.input) {
+            Ok(denoised) => self.output.extend(denoised),
+            // Noisy audio is still better than a gap in the stream.
+            Err(e) => {
+                tracing::warn!("dtln_process_error: {:?}", e);
+                self.output.extend(self.input.iter().copied());
+            }
+        }
+        self.input.clear();
+    }
+}
+
+impl<S: AsyncSource + Unpin> Stream for DenoisedStream<S> {
+    type Item = f32;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+
+        loop {
+            if let Some(sample) = this.output.pop_front() {
+                return Poll::Ready(Some(sample));
+            }
+
+            if this.finished {
+                return Poll::Ready(None);
+            }
+
+            let next = {
+                let mut stream = std::pin::pin!(this.source.as_stream());
+                stream.as_mut().poll_next(cx)
+            };
+
+            match next {
+                Poll::Ready(Some(sample)) => {
+                    this.input.push(sample);
+
+                    if this.input.len() == BATCH_SAMPLES {
+                        this.flush();
+                    }
+                }
+                Poll::Ready(None) => {
+                    this.finished = true;
+                    this.flush();
+                }
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
+
+impl<S: AsyncSource + Unpin> AsyncSource for DenoisedStream<S> {
+    fn as_stream(&mut self) -> impl Stream<Item = f32> + '_ {
+        self
+    }
+
+    fn sample_rate(&self) -> u32 {
+        SAMPLE_RATE
+    }
+}
@@ -0,0 +1,105 @@
+use chrono::{DateTime, Utc};
+use hypr_listener_interface::Word;
+
+use crate::{
+    transcript::{speaker_label, utterances},
+    Error, ExportSession,
+};
+
+/// Bumped on every change that could break a consumer, i.e. anything but new optional fields.
+pub const EXPORT_SCHEMA_VERSION: u32 = 1;
+
+/// The JSON export. Kept separate from the database types so those can change freely.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+pub struct ExportDocument {
+    pub version: u32,
+    pub id: String,
+    pub title: String,
+    pub created_at: DateTime<Utc>,
+    pub event: Option<ExportEvent>,
+    pub participants: Vec<ExportParticipant>,
+    pub tags: Vec<String>,
+    /// Markdown.
+    pub raw_memo: String,
+    /// Markdown.
+    pub enhanced_memo: Option<String>,
+    pub transcript: Vec<ExportUtterance>,
+    pub words: Vec<Word>,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+pub struct ExportEvent {
+    pub name: String,
+    pub start_date: DateTime<Utc>,
+    pub end_date: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+pub struct ExportParticipant {
+    pub id: String,
+    pub full_name: Option<String>,
+    pub email: Option<String>,
+    pub job_title: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+pub struct ExportUtterance {
+    pub speaker: String,
+    pub text: String,
+    pub start_ms: Option<u64>,
+    pub end_ms: Option<u64>,
+}
+
+impl ExportDocument {
+    pub fn new(input: &ExportSession) -> Result<Self, Error> {
+        let session = &input.session;
+
+        Ok(Self {
+            version: EXPORT_SCHEMA_VERSION,
+            id: session.id.clone(),
+            title: session.title.clone(),
+            created_at: session.created_at,
+            event: input.event.as_ref().map(|e| ExportEvent {
+                name: e.name.clone(),
+                start_date: e.start_date,
+                end_date: e.end_date,
+            }),
+            participants: input
+                .participants
+                .iter()
+                .map(|h| ExportParticipant {
+                    id: h.id.clone(),
+                    full_name: h.full_name.clone(),
+                    email: h.email.clone(),
+                    job_title: h.job_title.clone(),
+                })
+                .collect(),
+            tags: input.tags.iter().map(|t| t.name.clone()).collect(),
+            raw_memo: hypr_buffer::html_to_md(&session.raw_memo_html)?,
+            enhanced_memo: session
+                .enhanced_memo_html
+                .as_ref()
+                .map(hypr_buffer::html_to_md)
+                .transpose()?,
+            transcript: utterances(&session.words)
+                .into_iter()
+                .map(|u| ExportUtterance {
+                    speaker: speaker_label(&u.speaker),
+                    text: u.text,
+                    start_ms: u.start_ms,
+                    end_ms: u.end_ms,
+                })
+                .collect(),
+            words: session.words.clone(),
+        })
+    }
+}
+
+pub fn render_json(input: &ExportSession) -> Result<String, Error> {
+    Ok(serde_json::to_string_pretty(&ExportDocument::new(input)?)?)
+}
+
+/// JSON Schema of [`ExportDocument`], for consumers validating exports.
+pub fn json_schema() -> String {
+    serde_json::to_string_pretty(&schemars::schema_for!(ExportDocument)).unwrap()
+}
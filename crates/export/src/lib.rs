@@ -0,0 +1,330 @@
+mod json;
+mod markdown;
+mod subtitles;
+mod transcript;
+
+mod error;
+pub use error::*;
+
+pub use json::*;
+pub use markdown::*;
+pub use subtitles::*;
+pub use transcript::*;
+
+use hypr_db_user::{Event, GetSessionFilter, Human, Session, Tag, UserDatabase};
+
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    serde::Serialize,
+    serde::Deserialize,
+    specta::Type,
+    strum::Display,
+    strum::EnumString,
+)]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum ExportFormat {
+    Markdown,
+    Text,
+    Srt,
+    Vtt,
+    Json,
+}
+
+impl ExportFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Markdown => "md",
+            ExportFormat::Text => "txt",
+            ExportFormat::Srt => "srt",
+            ExportFormat::Vtt => "vtt",
+            ExportFormat::Json => "json",
+        }
+    }
+}
+
+/// Everything an export is rendered from.
+#[derive(Debug, Clone)]
+pub struct ExportSession {
+    pub session: Session,
+    pub event: Option<Event>,
+    pub participants: Vec<Human>,
+    pub tags: Vec<Tag>,
+}
+
+impl ExportSession {
+    pub async fn load(
+        db: &UserDatabase,
+        session_id: impl Into<String>,
+    ) -> Result<Option<Self>, Error> {
+        let session_id = session_id.into();
+
+        let Some(session) = db
+            .get_session(GetSessionFilter::Id(session_id.clone()))
+            .await?
+        else {
+            return Ok(None);
+        };
+
+        Ok(Some(Self {
+            event: db.session_get_event(&session_id).await?,
+            participants: db.session_list_participants(&session_id).await?,
+            tags: db.list_session_tags(&session_id).await?,
+            session,
+        }))
+    }
+
+    fn title(&self) -> &str {
+        match self.session.title.trim() {
+            "" => "Untitled",
+            title => title,
+        }
+    }
+
+    fn metadata(&self) -> Vec<(&'static str, String)> {
+        let mut items = Vec::new();
+
+        let date = self
+            .event
+            .as_ref()
+            .map_or(self.session.created_at, |e| e.start_date);
+        items.push(("Date", date.format("%Y-%m-%d %H:%M UTC").to_string()));
+
+        if let Some(event) = &self.event {
+            items.push(("Event", event.name.clone()));
+        }
+
+        let participants: Vec<_> = self
+            .participants
+            .iter()
+            .filter_map(|h| h.full_name.clone().or_else(|| h.email.clone()))
+            .collect();
+        if !participants.is_empty() {
+            items.push(("Participants", participants.join(", ")));
+        }
+
+        if !self.tags.is_empty() {
+            let tags: Vec<_> = self.tags.iter().map(|t| t.name.as_str()).collect();
+            items.push(("Tags", tags.join(", ")));
+        }
+
+        items
+    }
+
+    /// The enhanced memo if there is one, the raw memo otherwise.
+    fn memo_markdown(&self) -> Result<Option<String>, Error> {
+        let html = self
+            .session
+            .enhanced_memo_html
+            .as_deref()
+            .filter(|html| !html.trim().is_empty())
+            .unwrap_or(&self.session.raw_memo_html);
+
+        let md = hypr_buffer::html_to_md(html)?;
+        Ok(if md.trim().is_empty() { None } else { Some(md) })
+    }
+}
+
+pub fn export(input: &ExportSession, format: ExportFormat) -> Result<String, Error> {
+    match format {
+        ExportFormat::Markdown => render_markdown(input),
+        ExportFormat::Text => render_text(input),
+        ExportFormat::Srt => Ok(render_srt(&input.session.words)),
+        ExportFormat::Vtt => Ok(render_vtt(&input.session.words)),
+        ExportFormat::Json => render_json(input),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+    use hypr_listener_interface::{SpeakerIdentity, Word};
+
+    fn fixture() -> ExportSession {
+        let mut words: Vec<Word> = serde_json::from_str(hypr_data::english_3::WORDS_JSON).unwrap();
+        words.truncate(24);
+
+        // One speaker matched to a human, the other left as diarized.
+        for word in words.iter_mut() {
+            if word.speaker == Some(SpeakerIdentity::Unassigned { index: 1 }) {
+                word.speaker = Some(SpeakerIdentity::Assigned {
+                    id: "michael".to_string(),
+                    label: "Michael Scott".to_string(),
+                });
+            }
+        }
+
+        let start = Utc.with_ymd_and_hms(2025, 3, 4, 9, 30, 0).unwrap();
+
+        ExportSession {
+            session: Session {
+                id: "session".to_string(),
+                created_at: start,
+                visited_at: start,
+                user_id: "user".to_string(),
+                calendar_event_id: Some("event".to_string()),
+                title: "PowerPoint training".to_string(),
+                raw_memo_html: "<p>powerpoint?</p>".to_string(),
+                enhanced_memo_html: Some(
+                    "<h1>Summary</h1><ul><li><p>Ryan introduced <strong>Michael</strong></p></li><li><p>Training is about PowerPoint</p></li></ul>"
+                        .to_string(),
+                ),
+                conversations: vec![],
+                words,
+            },
+            event: Some(Event {
+                id: "event".to_string(),
+                user_id: "user".to_string(),
+                tracking_id: "tracking".to_string(),
+                calendar_id: None,
+                name: "Weekly training".to_string(),
+                note: "".to_string(),
+                start_date: start,
+                end_date: start + chrono::Duration::hours(1),
+                google_event_url: None,
+            }),
+            participants: vec![Human {
+                id: "michael".to_string(),
+                organization_id: None,
+                is_user: false,
+                full_name: Some("Michael Scott".to_string()),
+                email: Some("michael@dundermifflin.com".to_string()),
+                job_title: None,
+                linkedin_username: None,
+            }],
+            tags: vec![Tag {
+                id: "tag".to_string(),
+                name: "training".to_string(),
+            }],
+        }
+    }
+
+    #[test]
+    fn test_markdown() {
+        insta::assert_snapshot!(export(&fixture(), ExportFormat::Markdown).unwrap(), @r###"
+        # PowerPoint training
+
+        - **Date:** 2025-03-04 09:30 UTC
+        - **Event:** Weekly training
+        - **Participants:** Michael Scott
+        - **Tags:** training
+
+        # Summary
+
+        - Ryan introduced **Michael**
+        - Training is about PowerPoint
+
+        # Transcript
+
+        **Speaker 0** (00:00:00)
+
+        -okay michael why don't you start us off
+
+        **Michael Scott** (00:00:02)
+
+        -that wasn't much of an introduction -ladies and gentlemen
+
+        **Speaker 0** (00:00:08)
+
+        your boss michael
+
+        **Michael Scott** (00:00:09)
+
+        scott still lame okay
+        "###);
+    }
+
+    #[test]
+    fn test_text() {
+        insta::assert_snapshot!(export(&fixture(), ExportFormat::Text).unwrap(), @r###"
+        PowerPoint training
+
+        Date: 2025-03-04 09:30 UTC
+        Event: Weekly training
+        Participants: Michael Scott
+        Tags: training
+
+        # Summary
+
+        - Ryan introduced **Michael**
+        - Training is about PowerPoint
+
+        Transcript
+
+        [00:00:00] Speaker 0: -okay michael why don't you start us off
+        [00:00:02] Michael Scott: -that wasn't much of an introduction -ladies and gentlemen
+        [00:00:08] Speaker 0: your boss michael
+        [00:00:09] Michael Scott: scott still lame okay
+        "###);
+    }
+
+    #[test]
+    fn test_srt() {
+        insta::assert_snapshot!(export(&fixture(), ExportFormat::Srt).unwrap(), @r###"
+        1
+        00:00:00,320 --> 00:00:02,560
+        Speaker 0: -okay michael
+        why don't you start us off
+
+        2
+        00:00:02,560 --> 00:00:06,080
+        Michael Scott: -that wasn't
+        much of an introduction
+
+        3
+        00:00:07,279 --> 00:00:08,480
+        -ladies and gentlemen
+
+        4
+        00:00:08,559 --> 00:00:09,840
+        Speaker 0: your boss michael
+
+        5
+        00:00:09,840 --> 00:00:12,080
+        Michael Scott: scott still lame okay
+        "###);
+    }
+
+    #[test]
+    fn test_vtt() {
+        insta::assert_snapshot!(export(&fixture(), ExportFormat::Vtt).unwrap(), @r###"
+        WEBVTT
+
+        00:00:00.320 --> 00:00:02.560
+        <v Speaker 0>-okay michael why don't you start us off
+
+        00:00:02.560 --> 00:00:06.080
+        <v Michael Scott>-that wasn't much of an introduction
+
+        00:00:07.279 --> 00:00:08.480
+        <v Michael Scott>-ladies and gentlemen
+
+        00:00:08.559 --> 00:00:09.840
+        <v Speaker 0>your boss michael
+
+        00:00:09.840 --> 00:00:12.080
+        <v Michael Scott>scott still lame okay
+        "###);
+    }
+
+    #[test]
+    fn test_json() {
+        let json = export(&fixture(), ExportFormat::Json).unwrap();
+        let document: ExportDocument = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(document.version, EXPORT_SCHEMA_VERSION);
+        assert_eq!(document, ExportDocument::new(&fixture()).unwrap());
+        assert_eq!(document.transcript.len(), 4);
+        assert_eq!(document.raw_memo, "powerpoint?\n");
+    }
+
+    #[test]
+    fn test_format() {
+        assert_eq!("srt".parse::<ExportFormat>().unwrap(), ExportFormat::Srt);
+        assert_eq!(ExportFormat::Markdown.to_string(), "markdown");
+    }
+}
@@ -0,0 +1,100 @@
+use crate::{
+    transcript::{speaker_label, timestamp, utterances},
+    Error, ExportSession,
+};
+
+pub fn render_markdown(input: &ExportSession) -> Result<String, Error> {
+    let mut out = format!("# {}\n\n", input.title());
+
+    for (key, value) in input.metadata() {
+        out.push_str(&format!("- **{}:** {}\n", key, value));
+    }
+
+    if let Some(memo) = input.memo_markdown()? {
+        out.push('\n');
+        out.push_str(&memo);
+    }
+
+    let items = utterances(&input.session.words);
+    if !items.is_empty() {
+        out.push_str("\n# Transcript\n");
+
+        for item in items {
+            out.push_str(&format!("\n**{}**", speaker_label(&item.speaker)));
+            if let Some(start_ms) = item.start_ms {
+                out.push_str(&format!(" ({})", timestamp(start_ms, None)));
+            }
+            out.push_str(&format!("\n\n{}\n", item.text));
+        }
+    }
+
+    Ok(out)
+}
+
+pub fn render_text(input: &ExportSession) -> Result<String, Error> {
+    let mut out = format!("{}\n\n", input.title());
+
+    for (key, value) in input.metadata() {
+        out.push_str(&format!("{}: {}\n", key, value));
+    }
+
+    if let Some(memo) = input.memo_markdown()? {
+        out.push('\n');
+        out.push_str(&unescape(&memo));
+    }
+
+    let items = utterances(&input.session.words);
+    if !items.is_empty() {
+        out.push_str("\nTranscript\n\n");
+
+        for item in items {
+            if let Some(start_ms) = item.start_ms {
+                out.push_str(&format!("[{}] ", timestamp(start_ms, None)));
+            }
+            out.push_str(&format!(
+                "{}: {}\n",
+                speaker_label(&item.speaker),
+                item.text
+            ));
+        }
+    }
+
+    Ok(out)
+}
+
+/// Markdown is already close to how notes are written by hand, minus the backslash escapes and
+/// hard line breaks.
+fn unescape(md: &str) -> String {
+    let mut out = String::with_capacity(md.len());
+    let mut chars = md.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            match chars.peek() {
+                Some(&next) if next.is_ascii_punctuation() => {
+                    out.push(next);
+                    chars.next();
+                    continue;
+                }
+                Some('\n') => continue,
+                _ => {}
+            }
+        }
+        out.push(c);
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unescape() {
+        assert_eq!(
+            unescape("snake\\_case \\*and\\* \\\\\\\nend"),
+            "snake_case *and* \\\nend"
+        );
+    }
+}
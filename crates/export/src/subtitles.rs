@@ -0,0 +1,167 @@
+use hypr_listener_interface::{SpeakerIdentity, Word};
+
+use crate::transcript::{speaker_label, timestamp};
+
+// Roughly what a viewer can read at once: two lines of 42 characters for at most 6 seconds.
+const MAX_LINE_CHARS: usize = 42;
+const MAX_CUE_CHARS: usize = MAX_LINE_CHARS * 2;
+const MAX_CUE_MS: u64 = 6000;
+const MAX_GAP_MS: u64 = 1000;
+
+#[derive(Debug, Clone, PartialEq)]
+struct Cue {
+    speaker: Option<SpeakerIdentity>,
+    text: String,
+    start_ms: u64,
+    end_ms: u64,
+}
+
+/// Words without timestamps can't be placed on the timeline, so they are left out.
+fn cues(words: &[Word]) -> Vec<Cue> {
+    let mut items: Vec<Cue> = Vec::new();
+
+    for word in words {
+        let text = word.text.trim();
+        let Some(start_ms) = word.start_ms else {
+            continue;
+        };
+        if text.is_empty() {
+            continue;
+        }
+        let end_ms = word.end_ms.unwrap_or(start_ms).max(start_ms);
+
+        match items.last_mut() {
+            Some(last)
+                if last.speaker == word.speaker
+                    && start_ms.saturating_sub(last.end_ms) <= MAX_GAP_MS
+                    && end_ms.saturating_sub(last.start_ms) <= MAX_CUE_MS
+                    && last.text.chars().count() + 1 + text.chars().count() <= MAX_CUE_CHARS =>
+            {
+                last.text.push(' ');
+                last.text.push_str(text);
+                last.end_ms = last.end_ms.max(end_ms);
+            }
+            _ => items.push(Cue {
+                speaker: word.speaker.clone(),
+                text: text.to_string(),
+                start_ms,
+                end_ms,
+            }),
+        }
+    }
+
+    items
+}
+
+/// Splits text too long for one line at the space closest to its middle.
+fn wrap(text: &str) -> String {
+    let len = text.chars().count();
+    if len <= MAX_LINE_CHARS {
+        return text.to_string();
+    }
+
+    let middle = len / 2;
+    let split = text
+        .char_indices()
+        .enumerate()
+        .filter(|(_, (_, c))| *c == ' ')
+        .min_by_key(|(i, _)| i.abs_diff(middle))
+        .map(|(_, (byte, _))| byte);
+
+    match split {
+        Some(byte) => format!("{}\n{}", &text[..byte], &text[byte + 1..]),
+        None => text.to_string(),
+    }
+}
+
+pub fn render_srt(words: &[Word]) -> String {
+    let mut out = String::new();
+    let mut previous: Option<&Option<SpeakerIdentity>> = None;
+
+    let items = cues(words);
+    for (i, cue) in items.iter().enumerate() {
+        // SRT has no notion of speakers, so the label is only written when the speaker changes.
+        let text = if previous != Some(&cue.speaker) && cue.speaker.is_some() {
+            format!("{}: {}", speaker_label(&cue.speaker), cue.text)
+        } else {
+            cue.text.clone()
+        };
+        previous = Some(&cue.speaker);
+
+        out.push_str(&format!(
+            "{}\n{} --> {}\n{}\n\n",
+            i + 1,
+            timestamp(cue.start_ms, Some(',')),
+            timestamp(cue.end_ms, Some(',')),
+            wrap(&text)
+        ));
+    }
+
+    out
+}
+
+pub fn render_vtt(words: &[Word]) -> String {
+    let mut out = String::from("WEBVTT\n\n");
+
+    for cue in cues(words) {
+        let text = escape_vtt(&wrap(&cue.text));
+        let text = match cue.speaker {
+            Some(_) => format!("<v {}>{}", escape_vtt(&speaker_label(&cue.speaker)), text),
+            None => text,
+        };
+
+        out.push_str(&format!(
+            "{} --> {}\n{}\n\n",
+            timestamp(cue.start_ms, Some('.')),
+            timestamp(cue.end_ms, Some('.')),
+            text
+        ));
+    }
+
+    out
+}
+
+fn escape_vtt(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn word(text: &str, start_ms: u64, end_ms: u64) -> Word {
+        Word {
+            text: text.to_string(),
+            speaker: Some(SpeakerIdentity::Unassigned { index: 0 }),
+            confidence: None,
+            start_ms: Some(start_ms),
+            end_ms: Some(end_ms),
+        }
+    }
+
+    #[test]
+    fn test_cues() {
+        let items = cues(&[
+            word("a", 0, 100),
+            word("b", 200, 300),
+            // Long pause.
+            word("c", 2000, 2100),
+            word("d", 2100, 9000),
+        ]);
+
+        let texts: Vec<_> = items.iter().map(|c| c.text.as_str()).collect();
+        assert_eq!(texts, vec!["a b", "c", "d"]);
+        assert_eq!((items[0].start_ms, items[0].end_ms), (0, 300));
+    }
+
+    #[test]
+    fn test_wrap() {
+        assert_eq!(wrap("short line"), "short line");
+        assert_eq!(
+            wrap("this line is definitely longer than forty two characters"),
+            "this line is definitely longer\nthan forty two characters"
+        );
+    }
+}
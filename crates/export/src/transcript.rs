@@ -0,0 +1,94 @@
+use hypr_listener_interface::{SpeakerIdentity, Word};
+
+/// Consecutive words of the same speaker.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Utterance {
+    pub speaker: Option<SpeakerIdentity>,
+    pub text: String,
+    pub start_ms: Option<u64>,
+    pub end_ms: Option<u64>,
+}
+
+pub fn utterances(words: &[Word]) -> Vec<Utterance> {
+    let mut items: Vec<Utterance> = Vec::new();
+
+    for word in words {
+        let text = word.text.trim();
+        if text.is_empty() {
+            continue;
+        }
+
+        match items.last_mut() {
+            Some(last) if last.speaker == word.speaker => {
+                last.text.push(' ');
+                last.text.push_str(text);
+                last.start_ms = last.start_ms.or(word.start_ms);
+                last.end_ms = word.end_ms.or(last.end_ms);
+            }
+            _ => items.push(Utterance {
+                speaker: word.speaker.clone(),
+                text: text.to_string(),
+                start_ms: word.start_ms,
+                end_ms: word.end_ms,
+            }),
+        }
+    }
+
+    items
+}
+
+/// Same labels as the transcript view: the assigned human, or the diarized speaker's index.
+pub fn speaker_label(speaker: &Option<SpeakerIdentity>) -> String {
+    match speaker {
+        Some(SpeakerIdentity::Assigned { label, .. }) if !label.is_empty() => label.clone(),
+        Some(SpeakerIdentity::Assigned { .. }) => "Unknown".to_string(),
+        Some(SpeakerIdentity::Unassigned { index }) => format!("Speaker {}", index),
+        None => "Unknown".to_string(),
+    }
+}
+
+/// `HH:MM:SS`, followed by `separator` and milliseconds when given.
+pub fn timestamp(ms: u64, separator: Option<char>) -> String {
+    let (h, m, s) = (ms / 3_600_000, ms / 60_000 % 60, ms / 1000 % 60);
+
+    match separator {
+        Some(separator) => format!("{:02}:{:02}:{:02}{}{:03}", h, m, s, separator, ms % 1000),
+        None => format!("{:02}:{:02}:{:02}", h, m, s),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn word(text: &str, index: u8, start_ms: u64) -> Word {
+        Word {
+            text: text.to_string(),
+            speaker: Some(SpeakerIdentity::Unassigned { index }),
+            confidence: None,
+            start_ms: Some(start_ms),
+            end_ms: Some(start_ms + 100),
+        }
+    }
+
+    #[test]
+    fn test_utterances() {
+        let items = utterances(&[
+            word("hello", 0, 0),
+            word("there", 0, 200),
+            word(" ", 1, 300),
+            word("hi", 1, 400),
+        ]);
+
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].text, "hello there");
+        assert_eq!((items[0].start_ms, items[0].end_ms), (Some(0), Some(300)));
+        assert_eq!(items[1].text, "hi");
+    }
+
+    #[test]
+    fn test_timestamp() {
+        assert_eq!(timestamp(3_723_045, None), "01:02:03");
+        assert_eq!(timestamp(3_723_045, Some(',')), "01:02:03,045");
+    }
+}
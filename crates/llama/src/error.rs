@@ -19,7 +19,11 @@ pub enum Error {
     #[error(transparent)]
     DecodeError(#[from] llama_cpp_2::DecodeError),
     #[error(transparent)]
+    EmbeddingsError(#[from] llama_cpp_2::EmbeddingsError),
+    #[error(transparent)]
     TaskSendError(#[from] tokio::sync::mpsc::error::SendError<crate::Task>),
+    #[error(transparent)]
+    TaskRecvError(#[from] tokio::sync::oneshot::error::RecvError),
 }
 
 impl Serialize for Error {
@@ -22,6 +22,8 @@ pub use types::*;
 
 const DEFAULT_MAX_INPUT_TOKENS: u32 = 1024 * 8;
 const DEFAULT_MAX_OUTPUT_TOKENS: u32 = 1024;
+// Non-causal embedding models need the whole sequence in a single ubatch.
+const DEFAULT_MAX_EMBEDDING_TOKENS: u32 = 2048;
 
 static LLAMA_BACKEND: OnceLock<Arc<LlamaBackend>> = OnceLock::new();
 
@@ -34,6 +36,10 @@ pub enum Task {
         request: LlamaRequest,
         response_sender: tokio::sync::mpsc::UnboundedSender<String>,
     },
+    Embed {
+        texts: Vec<String>,
+        response_sender: tokio::sync::oneshot::Sender<Result<Vec<Vec<f32>>, crate::Error>>,
+    },
 }
 
 impl Llama {
@@ -47,8 +53,10 @@ impl Llama {
             })
             .clone();
 
-        let fmt = model_path.gguf_chat_format()?.unwrap();
-        let tpl = LlamaChatTemplate::new(fmt.as_ref()).unwrap();
+        // Embedding models have no chat template, and can only serve `Task::Embed`.
+        let tpl = model_path
+            .gguf_chat_format()?
+            .map(|fmt| LlamaChatTemplate::new(fmt.as_ref()).unwrap());
 
         let params = LlamaModelParams::default();
         let model = LlamaModel::load_from_file(&backend, model_path, &params)?;
@@ -63,8 +71,12 @@ impl Llama {
                             request,
                             response_sender,
                         } => {
+                            let Some(tpl) = tpl.as_ref() else {
+                                continue;
+                            };
+
                             let prompt = model
-                                .apply_chat_template(&tpl, &request.messages, true)
+                                .apply_chat_template(tpl, &request.messages, true)
                                 .unwrap();
 
                             let mut ctx = model
@@ -142,6 +154,12 @@ impl Llama {
 
                             drop(response_sender);
                         }
+                        Task::Embed {
+                            texts,
+                            response_sender,
+                        } => {
+                            let _ = response_sender.send(embed(&backend, &model, &texts));
+                        }
                     }
                 }
             }
@@ -167,6 +185,61 @@ impl Llama {
 
         Ok(stream::filter_tag(Box::pin(stream), "headers"))
     }
+
+    /// One L2-normalized embedding per text, so cosine similarity is a dot product.
+    /// Texts longer than the context are truncated.
+    pub async fn embed(&self, texts: Vec<String>) -> Result<Vec<Vec<f32>>, crate::Error> {
+        let (response_sender, response_receiver) = tokio::sync::oneshot::channel();
+
+        let task = Task::Embed {
+            texts,
+            response_sender,
+        };
+
+        self.task_sender.send(task)?;
+        response_receiver.await?
+    }
+}
+
+fn embed(
+    backend: &LlamaBackend,
+    model: &LlamaModel,
+    texts: &[String],
+) -> Result<Vec<Vec<f32>>, crate::Error> {
+    let mut ctx = model.new_context(
+        backend,
+        LlamaContextParams::default()
+            .with_n_ctx(std::num::NonZeroU32::new(DEFAULT_MAX_EMBEDDING_TOKENS))
+            .with_n_batch(DEFAULT_MAX_EMBEDDING_TOKENS)
+            .with_n_ubatch(DEFAULT_MAX_EMBEDDING_TOKENS)
+            .with_embeddings(true),
+    )?;
+
+    let mut batch = LlamaBatch::new(DEFAULT_MAX_EMBEDDING_TOKENS as usize, 1);
+    let mut embeddings = Vec::with_capacity(texts.len());
+
+    for text in texts {
+        let mut tokens = model.str_to_token(text, AddBos::Always)?;
+        tokens.truncate(DEFAULT_MAX_EMBEDDING_TOKENS as usize);
+
+        batch.clear();
+        batch.add_sequence(&tokens, 0, false)?;
+
+        ctx.clear_kv_cache();
+        ctx.decode(&mut batch)?;
+
+        embeddings.push(normalize(ctx.embeddings_seq_ith(0)?));
+    }
+
+    Ok(embeddings)
+}
+
+fn normalize(embedding: &[f32]) -> Vec<f32> {
+    let norm = embedding.iter().map(|v| v * v).sum::<f32>().sqrt();
+    if norm == 0.0 {
+        return embedding.to_vec();
+    }
+    embedding.iter().map(|v| v / norm).collect()
 }
 
 #[cfg(test)]
@@ -224,4 +297,33 @@ mod tests {
 
         run(&llama, request, true).await;
     }
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(normalize(&[3.0, 4.0]), vec![0.6, 0.8]);
+        assert_eq!(normalize(&[0.0, 0.0]), vec![0.0, 0.0]);
+    }
+
+    // cargo test test_embed -p llama -- --nocapture --ignored
+    #[ignore]
+    #[tokio::test]
+    async fn test_embed() {
+        let model_path = dirs::data_dir()
+            .unwrap()
+            .join("com.hyprnote.dev")
+            .join("embed.gguf");
+        let llama = Llama::new(model_path).unwrap();
+
+        let embeddings = llama
+            .embed(vec![
+                "search_document: We agreed to raise the price of the pro plan.".to_string(),
+                "search_document: The office will be closed on Friday.".to_string(),
+                "search_query: pricing change".to_string(),
+            ])
+            .await
+            .unwrap();
+
+        let dot = |a: &[f32], b: &[f32]| a.iter().zip(b).map(|(x, y)| x * y).sum::<f32>();
+        assert!(dot(&embeddings[2], &embeddings[0]) > dot(&embeddings[2], &embeddings[1]));
+    }
 }
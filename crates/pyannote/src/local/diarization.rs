@@ -0,0 +1,434 @@
+use anyhow::Result;
+
+use super::{embedding::EmbeddingExtractor, segmentation::Segmenter};
+
+#[derive(Debug, Clone)]
+pub struct DiarizationConfig {
+    /// Cosine distance below which two speech turns are considered the same speaker.
+    pub threshold: f32,
+    /// Turns shorter than this (in seconds) are labeled, but never start or move a cluster.
+    pub min_turn_secs: f64,
+}
+
+impl Default for DiarizationConfig {
+    fn default() -> Self {
+        Self {
+            threshold: 0.5,
+            min_turn_secs: 0.5,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpeakerTurn {
+    /// Seconds from the start of the processed samples.
+    pub start: f64,
+    pub end: f64,
+    pub speaker: usize,
+}
+
+impl SpeakerTurn {
+    fn overlap(&self, start: f64, end: f64) -> f64 {
+        (self.end.min(end) - self.start.max(start)).max(0.0)
+    }
+}
+
+/// Speaker of the turn that overlaps `start..end` the most.
+pub fn speaker_at(turns: &[SpeakerTurn], start: f64, end: f64) -> Option<usize> {
+    turns
+        .iter()
+        .map(|t| (t.overlap(start, end), t.speaker))
+        .filter(|(overlap, _)| *overlap > 0.0)
+        .max_by(|(a, _), (b, _)| a.total_cmp(b))
+        .map(|(_, speaker)| speaker)
+}
+
+pub fn cosine_distance(a: &[f32], b: &[f32]) -> f32 {
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm_a = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let norm_b = b.iter().map(|x| x * x).sum::<f32>().sqrt();
+
+    if norm_a == 0.0 || norm_b == 0.0 {
+        return 1.0;
+    }
+    1.0 - dot / (norm_a * norm_b)
+}
+
+/// Average-linkage agglomerative clustering. Clusters are merged while the closest pair is
+/// nearer than `threshold`; labels are numbered in order of first appearance.
+pub fn cluster(embeddings: &[Vec<f32>], threshold: f32) -> Vec<usize> {
+    let n = embeddings.len();
+
+    // Average distance between clusters. A merged cluster keeps the lower of the two slots, and
+    // only that row and column change.
+    let mut distances: Vec<Vec<f32>> = embeddings
+        .iter()
+        .map(|a| embeddings.iter().map(|b| cosine_distance(a, b)).collect())
+        .collect();
+    let mut clusters: Vec<Vec<usize>> = (0..n).map(|i| vec![i]).collect();
+
+    let nearest = |distances: &[Vec<f32>], clusters: &[Vec<usize>], i: usize| {
+        (0..n)
+            .filter(|&j| j != i && !clusters[j].is_empty())
+            .map(|j| (j, distances[i][j]))
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+    };
+
+    // Nearest other cluster of each one, so finding the closest pair doesn't scan every pair.
+    let mut neighbors: Vec<Option<(usize, f32)>> =
+        (0..n).map(|i| nearest(&distances, &clusters, i)).collect();
+
+    loop {
+        let closest = (0..n)
+            .filter(|&i| !clusters[i].is_empty())
+            .filter_map(|i| neighbors[i].map(|(j, d)| (i.min(j), i.max(j), d)))
+            .min_by(|(_, _, a), (_, _, b)| a.total_cmp(b));
+
+        let (i, j) = match closest {
+            Some((i, j, distance)) if distance < threshold => (i, j),
+            _ => break,
+        };
+
+        let (size_i, size_j) = (clusters[i].len() as f32, clusters[j].len() as f32);
+        for k in 0..n {
+            if k != i && k != j && !clusters[k].is_empty() {
+                let d = (size_i * distances[i][k] + size_j * distances[j][k]) / (size_i + size_j);
+                distances[i][k] = d;
+                distances[k][i] = d;
+            }
+        }
+
+        let merged = std::mem::take(&mut clusters[j]);
+        clusters[i].extend(merged);
+
+        for k in 0..n {
+            if clusters[k].is_empty() {
+                continue;
+            }
+
+            match neighbors[k] {
+                Some((m, _)) if k == i || m == i || m == j => {
+                    neighbors[k] = nearest(&distances, &clusters, k);
+                }
+                Some((_, d)) if distances[k][i] < d => neighbors[k] = Some((i, distances[k][i])),
+                _ => {}
+            }
+        }
+    }
+
+    let mut clusters: Vec<Vec<usize>> = clusters.into_iter().filter(|c| !c.is_empty()).collect();
+    clusters.sort_by_key(|c| c.iter().copied().min());
+
+    let mut labels = vec![0; n];
+    for (label, members) in clusters.iter().enumerate() {
+        for &i in members {
+            labels[i] = label;
+        }
+    }
+    labels
+}
+
+/// Incremental clustering for realtime use. Each speaker is a running-mean centroid, and an
+/// embedding either joins the nearest one or starts a new speaker.
+#[derive(Debug, Clone, Default)]
+pub struct SpeakerClusters {
+    threshold: f32,
+    centroids: Vec<(Vec<f32>, usize)>,
+}
+
+impl SpeakerClusters {
+    pub fn new(threshold: f32) -> Self {
+        Self {
+            threshold,
+            centroids: vec![],
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.centroids.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.centroids.is_empty()
+    }
+
+    /// Nearest speaker and its distance, without updating anything.
+    pub fn nearest(&self, embedding: &[f32]) -> Option<(usize, f32)> {
+        self.centroids
+            .iter()
+            .enumerate()
+            .map(|(i, (centroid, _))| (i, cosine_distance(centroid, embedding)))
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+    }
+
+    pub fn assign(&mut self, embedding: &[f32]) -> usize {
+        match self.nearest(embedding) {
+            Some((i, distance)) if distance < self.threshold => {
+                self.add(i, embedding);
+                i
+            }
+            _ => {
+                self.add(self.centroids.len(), embedding);
+                self.centroids.len() - 1
+            }
+        }
+    }
+
+    /// Adds an embedding to speaker `index`, which may be the next new speaker.
+    fn add(&mut self, index: usize, embedding: &[f32]) {
+        if index == self.centroids.len() {
+            self.centroids.push((embedding.to_vec(), 1));
+            return;
+        }
+
+        let (centroid, count) = &mut self.centroids[index];
+        *count += 1;
+        for (c, e) in centroid.iter_mut().zip(embedding) {
+            *c += (e - *c) / *count as f32;
+        }
+    }
+
+    pub fn centroid(&self, speaker: usize) -> Option<&[f32]> {
+        self.centroids.get(speaker).map(|(c, _)| c.as_slice())
+    }
+
+    pub fn clear(&mut self) {
+        self.centroids.clear();
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Diarization {
+    pub turns: Vec<SpeakerTurn>,
+    /// Embedding of each speaker, indexed by [`SpeakerTurn::speaker`].
+    pub speakers: Vec<Vec<f32>>,
+}
+
+struct EmbeddedTurn {
+    start: f64,
+    end: f64,
+    embedding: Vec<f32>,
+}
+
+impl EmbeddedTurn {
+    fn duration(&self) -> f64 {
+        self.end - self.start
+    }
+}
+
+/// Segmentation, then one embedding per speech turn, then clustering.
+pub struct Diarizer {
+    segmenter: Segmenter,
+    extractor: EmbeddingExtractor,
+    sample_rate: u32,
+    config: DiarizationConfig,
+    clusters: SpeakerClusters,
+}
+
+impl Diarizer {
+    pub fn new(sample_rate: u32, config: DiarizationConfig) -> Result<Self> {
+        Ok(Self {
+            segmenter: Segmenter::new(sample_rate)?,
+            extractor: EmbeddingExtractor::new(),
+            sample_rate,
+            clusters: SpeakerClusters::new(config.threshold),
+            config,
+        })
+    }
+
+    pub fn config(&self) -> &DiarizationConfig {
+        &self.config
+    }
+
+    /// Diarizes the next chunk of a live stream. Speaker indices are stable across calls.
+    pub fn process(&mut self, samples: &[i16]) -> Result<Vec<SpeakerTurn>> {
+        let turns = self.embed(samples)?;
+
+        let mut labeled = Vec::with_capacity(turns.len());
+        for turn in turns {
+            let speaker = if turn.duration() >= self.config.min_turn_secs {
+                Some(self.clusters.assign(&turn.embedding))
+            } else {
+                self.clusters.nearest(&turn.embedding).map(|(i, _)| i)
+            };
+
+            if let Some(speaker) = speaker {
+                labeled.push(SpeakerTurn {
+                    start: turn.start,
+                    end: turn.end,
+                    speaker,
+                });
+            }
+        }
+
+        Ok(labeled)
+    }
+
+    /// Embeddings of the speakers seen so far by [`Diarizer::process`], averaged over their turns.
+    pub fn speakers(&self) -> Vec<&[f32]> {
+        (0..self.clusters.len())
+            .filter_map(|i| self.clusters.centroid(i))
+            .collect()
+    }
+
+    /// Diarizes a whole recording at once, independently of the speakers seen by
+    /// [`Diarizer::process`].
+    pub fn diarize(&mut self, samples: &[i16]) -> Result<Diarization> {
+        let turns = self.embed(samples)?;
+
+        let (long, short): (Vec<_>, Vec<_>) = turns
+            .into_iter()
+            .partition(|t| t.duration() >= self.config.min_turn_secs);
+
+        let embeddings: Vec<Vec<f32>> = long.iter().map(|t| t.embedding.clone()).collect();
+        let labels = cluster(&embeddings, self.config.threshold);
+
+        // Short turns join whichever resulting speaker they are closest to.
+        let mut speakers = SpeakerClusters::new(self.config.threshold);
+        for (turn, &label) in long.iter().zip(&labels) {
+            speakers.add(label, &turn.embedding);
+        }
+
+        let mut result: Vec<SpeakerTurn> = long
+            .iter()
+            .zip(labels)
+            .map(|(t, speaker)| SpeakerTurn {
+                start: t.start,
+                end: t.end,
+                speaker,
+            })
+            .collect();
+
+        result.extend(short.iter().filter_map(|t| {
+            speakers
+                .nearest(&t.embedding)
+                .map(|(speaker, _)| SpeakerTurn {
+                    start: t.start,
+                    end: t.end,
+                    speaker,
+                })
+        }));
+
+        result.sort_by(|a, b| a.start.total_cmp(&b.start));
+
+        Ok(Diarization {
+            turns: result,
+            speakers: speakers.centroids.into_iter().map(|(c, _)| c).collect(),
+        })
+    }
+
+    /// Embedding of `samples`, e.g. the audio of a speaker the user is enrolling.
+    pub fn embedding(&mut self, samples: &[i16]) -> Result<Vec<f32>> {
+        self.extractor.compute(samples)
+    }
+
+    pub fn reset(&mut self) {
+        self.clusters.clear();
+    }
+
+    fn embed(&mut self, samples: &[i16]) -> Result<Vec<EmbeddedTurn>> {
+        let segments = self.segmenter.process(samples, self.sample_rate)?;
+
+        let mut turns = Vec::with_capacity(segments.len());
+        for segment in segments {
+            if segment.samples.is_empty() {
+                continue;
+            }
+
+            let embedding = self.extractor.compute(&segment.samples)?;
+            turns.push(EmbeddedTurn {
+                start: segment.start,
+                end: segment.end,
+                embedding,
+            });
+        }
+
+        Ok(turns)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cluster() {
+        let embeddings = vec![
+            vec![1.0, 0.0, 0.0],
+            vec![0.0, 1.0, 0.0],
+            vec![0.9, 0.1, 0.0],
+            vec![0.1, 0.9, 0.1],
+            vec![0.0, 0.0, 1.0],
+        ];
+
+        assert_eq!(cluster(&embeddings, 0.5), vec![0, 1, 0, 1, 2]);
+        assert_eq!(cluster(&embeddings, 0.0), vec![0, 1, 2, 3, 4]);
+        assert_eq!(cluster(&[], 0.5), Vec::<usize>::new());
+    }
+
+    #[test]
+    fn test_cluster_many() {
+        // Three speakers, each turn's embedding a little off its speaker's axis.
+        let embeddings: Vec<Vec<f32>> = (0..300)
+            .map(|i| {
+                let mut embedding = vec![0.05 * ((i * 7) % 5) as f32; 3];
+                embedding[i % 3] = 1.0;
+                embedding
+            })
+            .collect();
+
+        let labels = cluster(&embeddings, 0.5);
+        assert!(labels.iter().enumerate().all(|(i, &label)| label == i % 3));
+    }
+
+    #[test]
+    fn test_speaker_clusters() {
+        let mut clusters = SpeakerClusters::new(0.5);
+
+        assert_eq!(clusters.assign(&[1.0, 0.0]), 0);
+        assert_eq!(clusters.assign(&[0.0, 1.0]), 1);
+        assert_eq!(clusters.assign(&[0.8, 0.2]), 0);
+        assert_eq!(clusters.assign(&[0.1, 0.9]), 1);
+        assert_eq!(clusters.len(), 2);
+    }
+
+    #[test]
+    fn test_speaker_at() {
+        let turns = vec![
+            SpeakerTurn {
+                start: 0.0,
+                end: 2.0,
+                speaker: 0,
+            },
+            SpeakerTurn {
+                start: 2.0,
+                end: 5.0,
+                speaker: 1,
+            },
+        ];
+
+        assert_eq!(speaker_at(&turns, 0.5, 1.0), Some(0));
+        assert_eq!(speaker_at(&turns, 1.5, 3.0), Some(1));
+        assert_eq!(speaker_at(&turns, 6.0, 7.0), None);
+    }
+
+    #[test]
+    fn test_diarize_english_1() {
+        let audio: Vec<i16> = hypr_data::english_1::AUDIO
+            .chunks_exact(2)
+            .map(|chunk| i16::from_le_bytes([chunk[0], chunk[1]]))
+            .collect();
+
+        let mut diarizer = Diarizer::new(16000, DiarizationConfig::default()).unwrap();
+        let diarization = diarizer.diarize(&audio).unwrap();
+        assert!(!diarization.turns.is_empty());
+
+        for turn in &diarization.turns {
+            assert!(turn.speaker < diarization.speakers.len());
+        }
+
+        for pair in diarization.turns.windows(2) {
+            assert!(pair[0].start <= pair[1].start);
+        }
+    }
+}
@@ -1,2 +1,4 @@
+pub mod diarization;
 pub mod embedding;
 pub mod segmentation;
+pub mod voiceprint;
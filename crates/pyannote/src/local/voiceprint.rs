@@ -0,0 +1,148 @@
+use super::diarization::cosine_distance;
+
+/// A known person, with the embeddings enrolled for them.
+#[derive(Debug, Clone)]
+pub struct VoiceprintCandidate {
+    pub id: String,
+    pub label: String,
+    pub embeddings: Vec<Vec<f32>>,
+    /// Whether the person is expected in the recording, e.g. a participant of the session.
+    pub is_participant: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct VoiceprintMatcher {
+    candidates: Vec<VoiceprintCandidate>,
+    /// Minimum cosine similarity for a match.
+    threshold: f32,
+    /// Added to the similarity of participants, so they win close calls.
+    participant_bonus: f32,
+}
+
+impl VoiceprintMatcher {
+    pub fn new(candidates: Vec<VoiceprintCandidate>) -> Self {
+        Self {
+            candidates,
+            threshold: 0.6,
+            participant_bonus: 0.1,
+        }
+    }
+
+    pub fn threshold(mut self, threshold: f32) -> Self {
+        self.threshold = threshold;
+        self
+    }
+
+    pub fn participant_bonus(mut self, participant_bonus: f32) -> Self {
+        self.participant_bonus = participant_bonus;
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.candidates.iter().all(|c| c.embeddings.is_empty())
+    }
+
+    fn score(&self, candidate: &VoiceprintCandidate, embedding: &[f32]) -> Option<f32> {
+        let similarity = candidate
+            .embeddings
+            .iter()
+            .map(|e| 1.0 - cosine_distance(e, embedding))
+            .max_by(|a, b| a.total_cmp(b))?;
+
+        let bonus = if candidate.is_participant {
+            self.participant_bonus
+        } else {
+            0.0
+        };
+
+        Some(similarity + bonus).filter(|score| *score >= self.threshold)
+    }
+
+    pub fn best_match(&self, embedding: &[f32]) -> Option<&VoiceprintCandidate> {
+        self.candidates
+            .iter()
+            .filter_map(|c| self.score(c, embedding).map(|score| (c, score)))
+            .max_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(c, _)| c)
+    }
+
+    /// Matches each speaker to at most one candidate, and each candidate to at most one
+    /// speaker, best scores first.
+    pub fn assign<E: AsRef<[f32]>>(&self, speakers: &[E]) -> Vec<Option<&VoiceprintCandidate>> {
+        let mut scores: Vec<(usize, usize, f32)> = speakers
+            .iter()
+            .enumerate()
+            .flat_map(|(s, embedding)| {
+                self.candidates
+                    .iter()
+                    .enumerate()
+                    .filter_map(move |(c, candidate)| {
+                        self.score(candidate, embedding.as_ref())
+                            .map(|score| (s, c, score))
+                    })
+            })
+            .collect();
+        scores.sort_by(|a, b| b.2.total_cmp(&a.2));
+
+        let mut assigned = vec![None; speakers.len()];
+        let mut taken = vec![false; self.candidates.len()];
+
+        for (s, c, _) in scores {
+            if assigned[s].is_none() && !taken[c] {
+                assigned[s] = Some(&self.candidates[c]);
+                taken[c] = true;
+            }
+        }
+
+        assigned
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candidate(id: &str, embedding: Vec<f32>, is_participant: bool) -> VoiceprintCandidate {
+        VoiceprintCandidate {
+            id: id.to_string(),
+            label: id.to_string(),
+            embeddings: vec![embedding],
+            is_participant,
+        }
+    }
+
+    #[test]
+    fn test_best_match() {
+        let matcher = VoiceprintMatcher::new(vec![
+            candidate("alice", vec![1.0, 0.0, 0.0], false),
+            candidate("bob", vec![0.0, 1.0, 0.0], false),
+        ]);
+
+        assert_eq!(matcher.best_match(&[0.9, 0.1, 0.0]).unwrap().id, "alice");
+        assert_eq!(matcher.best_match(&[0.1, 0.9, 0.0]).unwrap().id, "bob");
+        assert!(matcher.best_match(&[0.1, 0.1, 1.0]).is_none());
+    }
+
+    #[test]
+    fn test_participant_prior() {
+        let embedding = [1.0, 0.9];
+
+        let matcher = VoiceprintMatcher::new(vec![
+            candidate("alice", vec![1.0, 0.8], false),
+            candidate("bob", vec![1.0, 1.4], true),
+        ]);
+        assert_eq!(matcher.best_match(&embedding).unwrap().id, "bob");
+
+        let matcher = matcher.participant_bonus(0.0);
+        assert_eq!(matcher.best_match(&embedding).unwrap().id, "alice");
+    }
+
+    #[test]
+    fn test_assign_is_one_to_one() {
+        let matcher = VoiceprintMatcher::new(vec![candidate("alice", vec![1.0, 0.0], false)]);
+
+        let assigned = matcher.assign(&[vec![0.8, 0.2], vec![1.0, 0.0]]);
+        assert!(assigned[0].is_none());
+        assert_eq!(assigned[1].unwrap().id, "alice");
+    }
+}
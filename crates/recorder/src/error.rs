@@ -0,0 +1,28 @@
+use serde::{ser::Serializer, Serialize};
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error(transparent)]
+    IoError(#[from] std::io::Error),
+    #[error(transparent)]
+    HoundError(#[from] hound::Error),
+    #[cfg(feature = "opus")]
+    #[error(transparent)]
+    OpusError(#[from] audiopus::Error),
+    #[cfg(feature = "opus")]
+    #[error(transparent)]
+    OggError(#[from] ogg::OggReadError),
+    #[error("invalid recording: {0}")]
+    InvalidRecording(String),
+    #[error("expected interleaved frames of {0} channels")]
+    InvalidFrameLength(u16),
+}
+
+impl Serialize for Error {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(self.to_string().as_ref())
+    }
+}
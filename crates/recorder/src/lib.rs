@@ -0,0 +1,333 @@
+mod error;
+#[cfg(feature = "opus")]
+mod opus;
+mod reader;
+mod segment;
+mod wav;
+
+pub use error::*;
+#[cfg(feature = "opus")]
+pub use opus::*;
+pub use reader::*;
+pub use segment::*;
+pub use wav::*;
+
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecordingFormat {
+    Wav,
+    #[cfg(feature = "opus")]
+    Opus,
+}
+
+impl RecordingFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            RecordingFormat::Wav => "wav",
+            #[cfg(feature = "opus")]
+            RecordingFormat::Opus => "ogg",
+        }
+    }
+
+    pub fn from_extension(ext: &str) -> Option<Self> {
+        match ext {
+            "wav" => Some(RecordingFormat::Wav),
+            #[cfg(feature = "opus")]
+            "ogg" => Some(RecordingFormat::Opus),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RecorderConfig {
+    pub sample_rate: u32,
+    pub channels: u16,
+    pub format: RecordingFormat,
+    /// A new file is started once the current one holds this much audio.
+    pub segment_duration: Duration,
+    /// Headers are rewritten at least this often, bounding what a crash can lose.
+    pub flush_interval: Duration,
+}
+
+impl Default for RecorderConfig {
+    fn default() -> Self {
+        Self {
+            sample_rate: 16000,
+            channels: 2,
+            format: RecordingFormat::Wav,
+            segment_duration: Duration::from_secs(60 * 10),
+            flush_interval: Duration::from_secs(5),
+        }
+    }
+}
+
+enum SegmentWriter {
+    Wav(hound::WavWriter<std::io::BufWriter<std::fs::File>>),
+    #[cfg(feature = "opus")]
+    Opus(OpusWriter),
+}
+
+impl SegmentWriter {
+    fn write(&mut self, interleaved: &[f32]) -> Result<(), Error> {
+        match self {
+            SegmentWriter::Wav(w) => {
+                for &sample in interleaved {
+                    w.write_sample(sample)?;
+                }
+            }
+            #[cfg(feature = "opus")]
+            SegmentWriter::Opus(w) => w.write(interleaved)?,
+        }
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<(), Error> {
+        match self {
+            SegmentWriter::Wav(w) => w.flush()?,
+            #[cfg(feature = "opus")]
+            SegmentWriter::Opus(w) => w.flush()?,
+        }
+        Ok(())
+    }
+
+    fn finalize(self) -> Result<(), Error> {
+        match self {
+            SegmentWriter::Wav(w) => w.finalize()?,
+            #[cfg(feature = "opus")]
+            SegmentWriter::Opus(w) => w.finalize()?,
+        }
+        Ok(())
+    }
+}
+
+/// Writes interleaved `f32` frames into a directory of rotating segment files.
+///
+/// Opening a directory that already holds a recording repairs segments left behind by a crash
+/// and continues with the next segment, so [`Recorder::frames`] stays a valid offset into the
+/// whole recording.
+pub struct Recorder {
+    dir: PathBuf,
+    config: RecorderConfig,
+    writer: Option<SegmentWriter>,
+    next_index: usize,
+    frames: u64,
+    segment_frames: u64,
+    unflushed_frames: u64,
+}
+
+impl Recorder {
+    pub fn open(dir: impl AsRef<Path>, config: RecorderConfig) -> Result<Self, Error> {
+        let dir = dir.as_ref().to_path_buf();
+        std::fs::create_dir_all(&dir)?;
+
+        let mut frames = 0;
+        let mut next_index = 0;
+
+        for path in list_segments(&dir)? {
+            match repair(&path) {
+                Ok(n) => frames += n,
+                Err(e) => tracing::error!(path = ?path, "segment_repair_error: {:?}", e),
+            }
+
+            if let Some(index) = segment_index(&path) {
+                next_index = index + 1;
+            }
+        }
+
+        Ok(Self {
+            dir,
+            config,
+            writer: None,
+            next_index,
+            frames,
+            segment_frames: 0,
+            unflushed_frames: 0,
+        })
+    }
+
+    /// Total frames in the recording, including segments written before this recorder was opened.
+    pub fn frames(&self) -> u64 {
+        self.frames
+    }
+
+    pub fn config(&self) -> &RecorderConfig {
+        &self.config
+    }
+
+    pub fn write(&mut self, interleaved: &[f32]) -> Result<(), Error> {
+        let channels = self.config.channels as usize;
+        if interleaved.len() % channels != 0 {
+            return Err(Error::InvalidFrameLength(self.config.channels));
+        }
+
+        let segment_len = self.duration_to_frames(self.config.segment_duration).max(1);
+        let flush_len = self.duration_to_frames(self.config.flush_interval);
+
+        let mut rest = interleaved;
+        while !rest.is_empty() {
+            if self.writer.is_none() {
+                self.writer = Some(self.create_segment()?);
+            }
+
+            let available = (segment_len - self.segment_frames) as usize;
+            let take = (rest.len() / channels).min(available);
+            let (head, tail) = rest.split_at(take * channels);
+
+            self.writer.as_mut().unwrap().write(head)?;
+            self.frames += take as u64;
+            self.segment_frames += take as u64;
+            self.unflushed_frames += take as u64;
+            rest = tail;
+
+            if self.segment_frames >= segment_len {
+                self.writer.take().unwrap().finalize()?;
+                self.segment_frames = 0;
+                self.unflushed_frames = 0;
+            }
+        }
+
+        if self.unflushed_frames >= flush_len {
+            self.flush()?;
+        }
+
+        Ok(())
+    }
+
+    pub fn flush(&mut self) -> Result<(), Error> {
+        if let Some(writer) = self.writer.as_mut() {
+            writer.flush()?;
+        }
+        self.unflushed_frames = 0;
+        Ok(())
+    }
+
+    pub fn finalize(mut self) -> Result<(), Error> {
+        if let Some(writer) = self.writer.take() {
+            writer.finalize()?;
+        }
+        Ok(())
+    }
+
+    fn duration_to_frames(&self, duration: Duration) -> u64 {
+        (duration.as_secs_f64() * self.config.sample_rate as f64) as u64
+    }
+
+    fn create_segment(&mut self) -> Result<SegmentWriter, Error> {
+        let path = segment_path(&self.dir, self.next_index, self.config.format);
+        self.next_index += 1;
+
+        tracing::info!(path = ?path, "recorder_segment");
+
+        let writer = match self.config.format {
+            RecordingFormat::Wav => SegmentWriter::Wav(hound::WavWriter::create(
+                path,
+                hound::WavSpec {
+                    channels: self.config.channels,
+                    sample_rate: self.config.sample_rate,
+                    bits_per_sample: 32,
+                    sample_format: hound::SampleFormat::Float,
+                },
+            )?),
+            #[cfg(feature = "opus")]
+            RecordingFormat::Opus => SegmentWriter::Opus(OpusWriter::create(
+                path,
+                self.config.sample_rate,
+                self.config.channels,
+                self.next_index as u32,
+            )?),
+        };
+
+        Ok(writer)
+    }
+}
+
+impl Drop for Recorder {
+    fn drop(&mut self) {
+        if let Some(writer) = self.writer.take() {
+            if let Err(e) = writer.finalize() {
+                tracing::error!("recorder_finalize_error: {:?}", e);
+            }
+        }
+    }
+}
+
+/// Repairs a segment of any supported format, returning its length in frames.
+pub fn repair(path: impl AsRef<Path>) -> Result<u64, Error> {
+    let path = path.as_ref();
+
+    match path
+        .extension()
+        .and_then(|e| e.to_str())
+        .and_then(RecordingFormat::from_extension)
+    {
+        Some(RecordingFormat::Wav) => repair_wav(path),
+        #[cfg(feature = "opus")]
+        Some(RecordingFormat::Opus) => repair_ogg(path),
+        None => Err(Error::InvalidRecording(format!("{:?}", path))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> RecorderConfig {
+        RecorderConfig {
+            sample_rate: 100,
+            channels: 2,
+            format: RecordingFormat::Wav,
+            segment_duration: Duration::from_secs(1),
+            flush_interval: Duration::from_millis(100),
+        }
+    }
+
+    #[test]
+    fn test_rotation() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let mut recorder = Recorder::open(dir.path(), config()).unwrap();
+        recorder.write(&vec![0.1; 250 * 2]).unwrap();
+        assert_eq!(recorder.frames(), 250);
+        recorder.finalize().unwrap();
+
+        let durations: Vec<u32> = list_segments(dir.path())
+            .unwrap()
+            .into_iter()
+            .map(|p| hound::WavReader::open(p).unwrap().duration())
+            .collect();
+        assert_eq!(durations, vec![100, 100, 50]);
+
+        let recorder = Recorder::open(dir.path(), config()).unwrap();
+        assert_eq!(recorder.frames(), 250);
+    }
+
+    #[test]
+    fn test_recover_after_crash() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let mut recorder = Recorder::open(dir.path(), config()).unwrap();
+        recorder.write(&[0.1; 30 * 2]).unwrap();
+        // Never finalized, like a killed process.
+        std::mem::forget(recorder);
+
+        let mut recorder = Recorder::open(dir.path(), config()).unwrap();
+        assert_eq!(recorder.frames(), 30);
+
+        recorder.write(&[0.1; 10 * 2]).unwrap();
+        assert_eq!(recorder.frames(), 40);
+        recorder.finalize().unwrap();
+
+        assert_eq!(list_segments(dir.path()).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_invalid_frame_length() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let mut recorder = Recorder::open(dir.path(), config()).unwrap();
+        assert!(recorder.write(&[0.0; 3]).is_err());
+    }
+}
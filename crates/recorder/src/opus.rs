@@ -0,0 +1,380 @@
+use std::fs::{File, OpenOptions};
+use std::io::{BufWriter, Read, Write};
+use std::path::Path;
+
+use audiopus::{
+    coder::{Decoder, Encoder},
+    packet::Packet,
+    Application, Channels, MutSignals, SampleRate,
+};
+use ogg::{
+    reading::PacketReader,
+    writing::{PacketWriteEndInfo, PacketWriter},
+};
+
+use crate::Error;
+
+// Ogg Opus granule positions are always expressed at 48kHz.
+const GRANULE_RATE: u32 = 48000;
+const FRAME_MS: u32 = 20;
+const MAX_PACKET_BYTES: usize = 4000;
+
+/// Ogg Opus writer (RFC 7845). Pages are self-delimiting, so a killed writer leaves a file
+/// that is readable up to its last complete page.
+pub struct OpusWriter {
+    writer: PacketWriter<BufWriter<File>>,
+    encoder: Encoder,
+    serial: u32,
+    channels: u16,
+    scale: u64,
+    frame_len: usize,
+    pre_skip: u64,
+    pending: Vec<f32>,
+    packet: Vec<u8>,
+    // The last encoded packet is held back so `flush` can end the page on it.
+    held: Option<(Box<[u8]>, u64)>,
+    encoded_frames: u64,
+    written_frames: u64,
+}
+
+impl OpusWriter {
+    pub fn create(
+        path: impl AsRef<Path>,
+        sample_rate: u32,
+        channels: u16,
+        serial: u32,
+    ) -> Result<Self, Error> {
+        let encoder = Encoder::new(
+            SampleRate::try_from(sample_rate as i32)?,
+            Channels::try_from(channels as i32)?,
+            Application::Voip,
+        )?;
+
+        let scale = (GRANULE_RATE / sample_rate) as u64;
+        let pre_skip = encoder.lookahead()? as u64 * scale;
+
+        let mut writer = PacketWriter::new(BufWriter::new(File::create(path)?));
+
+        let mut head = Vec::with_capacity(19);
+        head.extend_from_slice(b"OpusHead");
+        head.push(1);
+        head.push(channels as u8);
+        head.extend_from_slice(&(pre_skip as u16).to_le_bytes());
+        head.extend_from_slice(&sample_rate.to_le_bytes());
+        head.extend_from_slice(&0i16.to_le_bytes());
+        head.push(0);
+        writer.write_packet(
+            head.into_boxed_slice(),
+            serial,
+            PacketWriteEndInfo::EndPage,
+            0,
+        )?;
+
+        let vendor = b"hyprnote";
+        let mut tags = Vec::with_capacity(8 + 4 + vendor.len() + 4);
+        tags.extend_from_slice(b"OpusTags");
+        tags.extend_from_slice(&(vendor.len() as u32).to_le_bytes());
+        tags.extend_from_slice(vendor);
+        tags.extend_from_slice(&0u32.to_le_bytes());
+        writer.write_packet(
+            tags.into_boxed_slice(),
+            serial,
+            PacketWriteEndInfo::EndPage,
+            0,
+        )?;
+
+        Ok(Self {
+            writer,
+            encoder,
+            serial,
+            channels,
+            scale,
+            frame_len: (sample_rate * FRAME_MS / 1000) as usize,
+            pre_skip,
+            pending: Vec::new(),
+            packet: vec![0u8; MAX_PACKET_BYTES],
+            held: None,
+            encoded_frames: 0,
+            written_frames: 0,
+        })
+    }
+
+    pub fn write(&mut self, interleaved: &[f32]) -> Result<(), Error> {
+        self.pending.extend_from_slice(interleaved);
+        self.written_frames += (interleaved.len() / self.channels as usize) as u64;
+
+        let packet_samples = self.frame_len * self.channels as usize;
+        while self.pending.len() >= packet_samples {
+            let frame: Vec<f32> = self.pending.drain(..packet_samples).collect();
+            self.encode(&frame)?;
+        }
+
+        Ok(())
+    }
+
+    /// Ends the current Ogg page so everything encoded so far survives a crash.
+    pub fn flush(&mut self) -> Result<(), Error> {
+        if let Some((packet, granule)) = self.held.take() {
+            self.writer
+                .write_packet(packet, self.serial, PacketWriteEndInfo::EndPage, granule)?;
+        }
+        self.writer.inner_mut().flush()?;
+        Ok(())
+    }
+
+    pub fn finalize(mut self) -> Result<(), Error> {
+        let packet_samples = self.frame_len * self.channels as usize;
+
+        // Push the encoder's lookahead out with silence so the tail of the recording is decodable.
+        let lookahead = (self.pre_skip / self.scale) as usize * self.channels as usize;
+        self.pending.extend(std::iter::repeat_n(0.0, lookahead));
+        let padded = self.pending.len().div_ceil(packet_samples).max(1) * packet_samples;
+        self.pending.resize(padded, 0.0);
+
+        let pending = std::mem::take(&mut self.pending);
+        for frame in pending.chunks(packet_samples) {
+            self.encode(frame)?;
+        }
+
+        if let Some((packet, _)) = self.held.take() {
+            let end_granule = self.pre_skip + self.written_frames * self.scale;
+            self.writer.write_packet(
+                packet,
+                self.serial,
+                PacketWriteEndInfo::EndStream,
+                end_granule,
+            )?;
+        }
+
+        self.writer.inner_mut().flush()?;
+        Ok(())
+    }
+
+    fn encode(&mut self, frame: &[f32]) -> Result<(), Error> {
+        let len = self.encoder.encode_float(frame, &mut self.packet)?;
+        self.encoded_frames += self.frame_len as u64;
+
+        let packet = self.packet[..len].to_vec().into_boxed_slice();
+        let granule = self.encoded_frames * self.scale;
+
+        if let Some((packet, granule)) = self.held.replace((packet, granule)) {
+            self.writer.write_packet(
+                packet,
+                self.serial,
+                PacketWriteEndInfo::NormalPacket,
+                granule,
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Drops a trailing partial Ogg page left behind by a killed writer.
+///
+/// Returns the number of frames in the file after the repair.
+pub fn repair_ogg(path: impl AsRef<Path>) -> Result<u64, Error> {
+    let mut data = Vec::new();
+    File::open(&path)?.read_to_end(&mut data)?;
+
+    let mut pos = 0usize;
+    let mut pre_skip = 0u64;
+    let mut scale = 1u64;
+    let mut granule = 0u64;
+
+    while pos + 27 <= data.len() && &data[pos..pos + 4] == b"OggS" {
+        let segments = data[pos + 26] as usize;
+        let table_end = pos + 27 + segments;
+        if table_end > data.len() {
+            break;
+        }
+
+        let body_len: usize = data[pos + 27..table_end].iter().map(|&l| l as usize).sum();
+        let page_end = table_end + body_len;
+        if page_end > data.len() {
+            break;
+        }
+
+        let body = &data[table_end..page_end];
+        if body.starts_with(b"OpusHead") && body.len() >= 16 {
+            pre_skip = u16::from_le_bytes([body[10], body[11]]) as u64;
+            let rate = u32::from_le_bytes([body[12], body[13], body[14], body[15]]);
+            scale = (GRANULE_RATE / rate.max(1)).max(1) as u64;
+        }
+
+        let page_granule = u64::from_le_bytes(data[pos + 6..pos + 14].try_into().unwrap());
+        if page_granule != u64::MAX {
+            granule = page_granule;
+        }
+
+        pos = page_end;
+    }
+
+    if pos == 0 {
+        return Err(Error::InvalidRecording("not an Ogg file".into()));
+    }
+
+    if pos < data.len() {
+        let file = OpenOptions::new().write(true).open(&path)?;
+        file.set_len(pos as u64)?;
+        file.sync_all()?;
+
+        tracing::warn!(dropped_bytes = data.len() - pos, "ogg_repaired");
+    }
+
+    Ok(granule.saturating_sub(pre_skip) / scale)
+}
+
+/// Decodes an Ogg Opus file written by [`OpusWriter`], returning its sample rate, channel count
+/// and interleaved samples with the encoder delay removed.
+pub fn read_ogg(path: impl AsRef<Path>) -> Result<(u32, u16, Vec<f32>), Error> {
+    let mut reader = PacketReader::new(File::open(path)?);
+
+    let head = reader
+        .read_packet()?
+        .filter(|p| p.data.starts_with(b"OpusHead") && p.data.len() >= 19)
+        .ok_or_else(|| Error::InvalidRecording("missing OpusHead".into()))?;
+
+    let channels = head.data[9] as u16;
+    let pre_skip = u16::from_le_bytes([head.data[10], head.data[11]]) as u64;
+    let sample_rate = u32::from_le_bytes(head.data[12..16].try_into().unwrap());
+    let scale = (GRANULE_RATE / sample_rate.max(1)).max(1) as u64;
+
+    let mut decoder = Decoder::new(
+        SampleRate::try_from(sample_rate as i32)?,
+        Channels::try_from(channels as i32)?,
+    )?;
+
+    // OpusTags
+    reader.read_packet()?;
+
+    let max_frame = (sample_rate as usize * 120 / 1000) * channels as usize;
+    let mut buffer = vec![0.0f32; max_frame];
+    let mut samples = Vec::new();
+    let mut end_granule = 0u64;
+
+    while let Some(packet) = reader.read_packet()? {
+        let frames = decoder.decode_float(
+            Some(Packet::try_from(packet.data.as_slice())?),
+            MutSignals::try_from(buffer.as_mut_slice())?,
+            false,
+        )?;
+        samples.extend_from_slice(&buffer[..frames * channels as usize]);
+
+        if packet.absgp_page() != u64::MAX {
+            end_granule = packet.absgp_page();
+        }
+    }
+
+    let skip = ((pre_skip / scale) as usize * channels as usize).min(samples.len());
+    samples.drain(..skip);
+
+    let total = (end_granule.saturating_sub(pre_skip) / scale) as usize * channels as usize;
+    samples.truncate(total);
+
+    Ok((sample_rate, channels, samples))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE_RATE: u32 = 16000;
+
+    fn sine(frames: usize, channels: u16) -> Vec<f32> {
+        (0..frames)
+            .flat_map(|i| {
+                let v = (i as f32 * 440.0 * std::f32::consts::TAU / SAMPLE_RATE as f32).sin();
+                std::iter::repeat_n(v * 0.5, channels as usize)
+            })
+            .collect()
+    }
+
+    fn rms(samples: &[f32]) -> f32 {
+        (samples.iter().map(|s| s * s).sum::<f32>() / samples.len() as f32).sqrt()
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("audio.ogg");
+
+        let input = sine(SAMPLE_RATE as usize, 2);
+        let mut writer = OpusWriter::create(&path, SAMPLE_RATE, 2, 1).unwrap();
+        // Chunks that don't line up with Opus frames.
+        for chunk in input.chunks(2 * 1234) {
+            writer.write(chunk).unwrap();
+        }
+        writer.finalize().unwrap();
+
+        let (sample_rate, channels, output) = read_ogg(&path).unwrap();
+        assert_eq!(sample_rate, SAMPLE_RATE);
+        assert_eq!(channels, 2);
+        assert_eq!(output.len(), input.len());
+        assert!((rms(&output) - rms(&input)).abs() < 0.05);
+
+        assert_eq!(repair_ogg(&path).unwrap(), SAMPLE_RATE as u64);
+    }
+
+    #[test]
+    fn test_flush_ends_page() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("audio.ogg");
+
+        let mut writer = OpusWriter::create(&path, SAMPLE_RATE, 1, 1).unwrap();
+        writer.write(&sine(SAMPLE_RATE as usize, 1)).unwrap();
+        writer.flush().unwrap();
+        // Simulate a crash: no finalize, and half a page after the flushed ones.
+        std::mem::forget(writer);
+        {
+            let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+            file.write_all(b"OggS\0\0\0").unwrap();
+        }
+
+        let frames = repair_ogg(&path).unwrap();
+        // Everything but the encoder's lookahead is on disk.
+        assert!(frames > SAMPLE_RATE as u64 * 9 / 10);
+        assert!(frames <= SAMPLE_RATE as u64);
+
+        let (_, _, output) = read_ogg(&path).unwrap();
+        assert_eq!(output.len() as u64, frames);
+    }
+
+    #[test]
+    fn test_repair_truncated() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("audio.ogg");
+
+        let mut writer = OpusWriter::create(&path, SAMPLE_RATE, 1, 1).unwrap();
+        for chunk in sine(SAMPLE_RATE as usize * 2, 1).chunks(SAMPLE_RATE as usize / 2) {
+            writer.write(chunk).unwrap();
+            writer.flush().unwrap();
+        }
+        writer.finalize().unwrap();
+
+        let len = std::fs::metadata(&path).unwrap().len();
+        OpenOptions::new()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_len(len - 10)
+            .unwrap();
+
+        let frames = repair_ogg(&path).unwrap();
+        assert!(frames > 0);
+        assert!(frames < SAMPLE_RATE as u64 * 2);
+        assert!(std::fs::metadata(&path).unwrap().len() < len - 10);
+
+        let (_, _, output) = read_ogg(&path).unwrap();
+        assert_eq!(output.len() as u64, frames);
+        assert_eq!(repair_ogg(&path).unwrap(), frames);
+    }
+
+    #[test]
+    fn test_repair_invalid() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("audio.ogg");
+
+        std::fs::write(&path, b"RIFF").unwrap();
+        assert!(repair_ogg(&path).is_err());
+    }
+}
@@ -0,0 +1,116 @@
+use std::path::Path;
+
+use crate::{list_segments, Error, RecordingFormat};
+
+/// Samples of a recording, downmixed to mono.
+#[derive(Debug, Clone, Default)]
+pub struct MonoRecording {
+    pub sample_rate: u32,
+    pub samples: Vec<f32>,
+}
+
+impl MonoRecording {
+    pub fn duration_secs(&self) -> f64 {
+        if self.sample_rate == 0 {
+            return 0.0;
+        }
+        self.samples.len() as f64 / self.sample_rate as f64
+    }
+}
+
+/// Reads every segment of the recording in `dir`, in playback order.
+pub fn read_mono(dir: impl AsRef<Path>) -> Result<MonoRecording, Error> {
+    let mut recording = MonoRecording::default();
+
+    for path in list_segments(dir)? {
+        let (sample_rate, samples) = read_segment_mono(&path)?;
+
+        if recording.sample_rate == 0 {
+            recording.sample_rate = sample_rate;
+        } else if recording.sample_rate != sample_rate {
+            return Err(Error::InvalidRecording(format!(
+                "{:?} is {}Hz, expected {}Hz",
+                path, sample_rate, recording.sample_rate
+            )));
+        }
+
+        recording.samples.extend(samples);
+    }
+
+    Ok(recording)
+}
+
+/// Reads a single segment, returning its sample rate and mono samples.
+pub fn read_segment_mono(path: impl AsRef<Path>) -> Result<(u32, Vec<f32>), Error> {
+    let path = path.as_ref();
+
+    match path
+        .extension()
+        .and_then(|e| e.to_str())
+        .and_then(RecordingFormat::from_extension)
+    {
+        Some(RecordingFormat::Wav) => read_wav_mono(path),
+        #[cfg(feature = "opus")]
+        Some(RecordingFormat::Opus) => crate::read_ogg(path)
+            .map(|(rate, channels, samples)| (rate, downmix(&samples, channels))),
+        None => Err(Error::InvalidRecording(format!("{:?}", path))),
+    }
+}
+
+fn read_wav_mono(path: &Path) -> Result<(u32, Vec<f32>), Error> {
+    let mut reader = hound::WavReader::open(path)?;
+    let spec = reader.spec();
+
+    let samples: Vec<f32> = match spec.sample_format {
+        hound::SampleFormat::Float => reader.samples::<f32>().collect::<Result<_, _>>()?,
+        hound::SampleFormat::Int => {
+            let scale = (1i64 << (spec.bits_per_sample - 1)) as f32;
+            reader
+                .samples::<i32>()
+                .map(|s| s.map(|s| s as f32 / scale))
+                .collect::<Result<_, _>>()?
+        }
+    };
+
+    Ok((spec.sample_rate, downmix(&samples, spec.channels)))
+}
+
+fn downmix(interleaved: &[f32], channels: u16) -> Vec<f32> {
+    let channels = channels.max(1) as usize;
+    if channels == 1 {
+        return interleaved.to_vec();
+    }
+
+    interleaved
+        .chunks_exact(channels)
+        .map(|frame| frame.iter().sum::<f32>() / channels as f32)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Recorder, RecorderConfig};
+
+    #[test]
+    fn test_read_mono() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let config = RecorderConfig {
+            sample_rate: 100,
+            segment_duration: std::time::Duration::from_secs(1),
+            ..Default::default()
+        };
+
+        let mut recorder = Recorder::open(dir.path(), config).unwrap();
+        let frames: Vec<f32> = (0..150).flat_map(|_| [0.2, 0.4]).collect();
+        recorder.write(&frames).unwrap();
+        recorder.finalize().unwrap();
+
+        let recording = read_mono(dir.path()).unwrap();
+        assert_eq!(recording.sample_rate, 100);
+        assert_eq!(recording.samples.len(), 150);
+        assert!(recording.samples.iter().all(|s| (s - 0.3).abs() < 1e-6));
+        assert_eq!(recording.duration_secs(), 1.5);
+    }
+}
@@ -0,0 +1,77 @@
+use std::path::{Path, PathBuf};
+
+use crate::{Error, RecordingFormat};
+
+/// Recordings made before segmentation was introduced are a single file with this name.
+pub const LEGACY_FILE_NAME: &str = "audio.wav";
+
+const SEGMENT_PREFIX: &str = "audio-";
+
+pub fn segment_path(dir: impl AsRef<Path>, index: usize, format: RecordingFormat) -> PathBuf {
+    dir.as_ref().join(format!(
+        "{}{:04}.{}",
+        SEGMENT_PREFIX,
+        index,
+        format.extension()
+    ))
+}
+
+/// Index of a segment file, `None` for the legacy single-file recording.
+pub fn segment_index(path: impl AsRef<Path>) -> Option<usize> {
+    let name = path.as_ref().file_name()?.to_str()?;
+    let (stem, ext) = name.rsplit_once('.')?;
+
+    RecordingFormat::from_extension(ext)?;
+    stem.strip_prefix(SEGMENT_PREFIX)?.parse().ok()
+}
+
+/// Lists the segments of the recording in `dir`, in playback order.
+pub fn list_segments(dir: impl AsRef<Path>) -> Result<Vec<PathBuf>, Error> {
+    let dir = dir.as_ref();
+    if !dir.exists() {
+        return Ok(vec![]);
+    }
+
+    let mut segments = vec![];
+
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+
+        if path.file_name().is_some_and(|n| n == LEGACY_FILE_NAME) {
+            segments.push((None, path));
+        } else if let Some(index) = segment_index(&path) {
+            segments.push((Some(index), path));
+        }
+    }
+
+    segments.sort_by_key(|(index, _)| *index);
+    Ok(segments.into_iter().map(|(_, path)| path).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_list_segments() {
+        let dir = tempfile::tempdir().unwrap();
+
+        for name in [
+            "audio-0001.wav",
+            "audio-0000.wav",
+            "audio.wav",
+            "audio.json",
+            "notes.txt",
+        ] {
+            std::fs::write(dir.path().join(name), b"").unwrap();
+        }
+
+        let names: Vec<_> = list_segments(dir.path())
+            .unwrap()
+            .into_iter()
+            .map(|p| p.file_name().unwrap().to_str().unwrap().to_string())
+            .collect();
+
+        assert_eq!(names, vec!["audio.wav", "audio-0000.wav", "audio-0001.wav"]);
+    }
+}
@@ -0,0 +1,128 @@
+use std::fs::OpenOptions;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::Path;
+
+use crate::Error;
+
+/// Fixes the RIFF and `data` chunk sizes of a WAV file whose writer never got to update them,
+/// e.g. because the app was killed mid-session. Trailing bytes that do not form a whole frame are dropped.
+///
+/// Returns the number of frames in the file after the repair.
+pub fn repair_wav(path: impl AsRef<Path>) -> Result<u64, Error> {
+    let mut file = OpenOptions::new().read(true).write(true).open(path)?;
+    let file_len = file.metadata()?.len();
+
+    let mut riff = [0u8; 12];
+    file.read_exact(&mut riff)
+        .map_err(|_| Error::InvalidRecording("missing RIFF header".into()))?;
+    if &riff[0..4] != b"RIFF" || &riff[8..12] != b"WAVE" {
+        return Err(Error::InvalidRecording("not a RIFF/WAVE file".into()));
+    }
+    let riff_len = u32::from_le_bytes([riff[4], riff[5], riff[6], riff[7]]) as u64;
+
+    let mut block_align: Option<u64> = None;
+    let mut pos = 12u64;
+
+    loop {
+        let mut header = [0u8; 8];
+        file.seek(SeekFrom::Start(pos))?;
+        file.read_exact(&mut header)
+            .map_err(|_| Error::InvalidRecording("missing data chunk".into()))?;
+
+        let id = &header[0..4];
+        let len = u32::from_le_bytes([header[4], header[5], header[6], header[7]]) as u64;
+
+        if id == b"fmt " {
+            let mut fmt = [0u8; 14];
+            file.read_exact(&mut fmt)?;
+            block_align = Some(u16::from_le_bytes([fmt[12], fmt[13]]) as u64);
+        }
+
+        if id != b"data" {
+            // Chunks are word aligned.
+            pos += 8 + len + (len & 1);
+            continue;
+        }
+
+        let block_align = block_align
+            .filter(|b| *b > 0)
+            .ok_or_else(|| Error::InvalidRecording("missing fmt chunk".into()))?;
+
+        let data_start = pos + 8;
+        let declared_end = data_start + len;
+
+        // Headers are consistent, and the data chunk is not followed by unaccounted bytes.
+        if riff_len + 8 == file_len && declared_end <= file_len {
+            return Ok(len / block_align);
+        }
+
+        let available = file_len.saturating_sub(data_start);
+        let data_len = (available - available % block_align).min(u32::MAX as u64 - 36);
+
+        file.set_len(data_start + data_len)?;
+        file.seek(SeekFrom::Start(pos + 4))?;
+        file.write_all(&(data_len as u32).to_le_bytes())?;
+        file.seek(SeekFrom::Start(4))?;
+        file.write_all(&((data_start + data_len - 8) as u32).to_le_bytes())?;
+        file.sync_all()?;
+
+        tracing::warn!(
+            declared_bytes = len,
+            recovered_bytes = data_len,
+            "wav_repaired"
+        );
+
+        return Ok(data_len / block_align);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spec() -> hound::WavSpec {
+        hound::WavSpec {
+            channels: 2,
+            sample_rate: 16000,
+            bits_per_sample: 32,
+            sample_format: hound::SampleFormat::Float,
+        }
+    }
+
+    #[test]
+    fn test_repair_unflushed() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("audio.wav");
+
+        let mut writer = hound::WavWriter::create(&path, spec()).unwrap();
+        for i in 0..1600 {
+            writer.write_sample(i as f32 / 1600.0).unwrap();
+            writer.write_sample(0.0f32).unwrap();
+        }
+        writer.flush().unwrap();
+        // Simulate a crash: the header still says 1600 frames, but more data and half a frame follow.
+        drop(writer);
+        {
+            let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+            for _ in 0..(320 * 2) {
+                file.write_all(&0.5f32.to_le_bytes()).unwrap();
+            }
+            file.write_all(&[0u8; 6]).unwrap();
+        }
+
+        assert_eq!(repair_wav(&path).unwrap(), 1600 + 320);
+
+        let reader = hound::WavReader::open(&path).unwrap();
+        assert_eq!(reader.duration(), 1600 + 320);
+        assert_eq!(repair_wav(&path).unwrap(), 1600 + 320);
+    }
+
+    #[test]
+    fn test_repair_invalid() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("audio.wav");
+
+        std::fs::write(&path, b"RIFF").unwrap();
+        assert!(repair_wav(&path).is_err());
+    }
+}
@@ -2,7 +2,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     #[cfg(feature = "generate")]
     {
         tonic_build::configure()
-            .build_server(false)
+            .build_server(true)
             .out_dir("./src/realtime/interface")
             .compile_protos(&["proto/rtzr.proto"], &["proto"])?;
     }
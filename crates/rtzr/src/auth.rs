@@ -0,0 +1,94 @@
+// https://developers.rtzr.ai/docs/authentications
+
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[derive(Debug, Clone, serde::Deserialize)]
+struct Token {
+    access_token: String,
+    /// Unix timestamp in seconds.
+    expire_at: u64,
+}
+
+/// Exchanges client credentials for access tokens, which are valid for a few hours.
+///
+/// Clones share the cached token, so keep one around instead of creating it per connection.
+#[derive(Debug, Clone)]
+pub struct Auth {
+    api_base: url::Url,
+    client_id: String,
+    client_secret: String,
+    client: reqwest::Client,
+    token: Arc<Mutex<Option<Token>>>,
+}
+
+impl Auth {
+    pub fn new(
+        api_base: url::Url,
+        client_id: impl Into<String>,
+        client_secret: impl Into<String>,
+    ) -> Self {
+        Self {
+            api_base,
+            client_id: client_id.into(),
+            client_secret: client_secret.into(),
+            client: reqwest::Client::new(),
+            token: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// Uses RTZR's API.
+    pub fn from_credentials(
+        client_id: impl Into<String>,
+        client_secret: impl Into<String>,
+    ) -> Self {
+        let api_base = crate::DEFAULT_API_BASE
+            .parse()
+            .expect("valid default api base");
+        Self::new(api_base, client_id, client_secret)
+    }
+
+    /// Returns the cached token, or requests a new one if it is about to expire.
+    pub async fn token(&self) -> Result<String, crate::Error> {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+
+        if let Some(token) = self.token.lock().unwrap().as_ref() {
+            if token.expire_at > now + 60 {
+                return Ok(token.access_token.clone());
+            }
+        }
+
+        let mut url = self.api_base.clone();
+        url.path_segments_mut()
+            .map_err(|_| crate::Error::InvalidApiBase(self.api_base.to_string()))?
+            .pop_if_empty()
+            .push("v1")
+            .push("authenticate");
+
+        let token = self
+            .client
+            .post(url)
+            .form(&[
+                ("client_id", &self.client_id),
+                ("client_secret", &self.client_secret),
+            ])
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<Token>()
+            .await?;
+
+        let access_token = token.access_token.clone();
+        *self.token.lock().unwrap() = Some(token);
+
+        Ok(access_token)
+    }
+
+    /// Value of the `authorization` header for authenticated requests.
+    pub async fn bearer(&self) -> Result<String, crate::Error> {
+        Ok(format!("bearer {}", self.token().await?))
+    }
+}
@@ -0,0 +1,30 @@
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error(transparent)]
+    TonicTransportError(#[from] tonic::transport::Error),
+    #[error(transparent)]
+    TonicErrorStatus(Box<tonic::Status>),
+    #[error(transparent)]
+    ReqwestError(#[from] reqwest::Error),
+    #[error(transparent)]
+    IoError(#[from] std::io::Error),
+    #[error(transparent)]
+    SerdeJsonError(#[from] serde_json::Error),
+    #[error(transparent)]
+    UrlParseError(#[from] url::ParseError),
+    #[error("invalid api base: {0}")]
+    InvalidApiBase(String),
+    #[error("missing client credentials")]
+    MissingCredentials,
+    #[error("invalid access token")]
+    InvalidToken,
+    #[error("transcription failed: {0}")]
+    TranscribeFailed(String),
+}
+
+// `tonic::Status` is large enough that clippy flags every `Result` carrying it.
+impl From<tonic::Status> for Error {
+    fn from(status: tonic::Status) -> Self {
+        Self::TonicErrorStatus(Box::new(status))
+    }
+}
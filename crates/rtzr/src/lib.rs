@@ -1 +1,10 @@
-mod realtime;
+mod auth;
+mod errors;
+
+pub use auth::*;
+pub use errors::*;
+
+pub mod realtime;
+pub mod recorded;
+
+pub const DEFAULT_API_BASE: &str = "https://openapi.vito.ai";
@@ -1 +1,372 @@
-mod interface;
+// https://developers.rtzr.ai/docs/stt-streaming/grpc
+
+pub mod interface;
+
+use bytes::Bytes;
+use futures_util::{Stream, StreamExt};
+
+use interface::{
+    decoder_config::AudioEncoding, decoder_request::StreamingRequest,
+    online_decoder_client::OnlineDecoderClient,
+};
+use tonic::{
+    metadata::{Ascii, MetadataValue},
+    service::interceptor::InterceptedService,
+    transport::{Channel, ClientTlsConfig, Endpoint},
+    Request, Status,
+};
+
+const DEFAULT_ENDPOINT: &str = "https://grpc-openapi.vito.ai:443";
+
+// https://docs.rs/tonic/latest/tonic/service/trait.Interceptor.html
+// 'Send' is required in the websocket handler context
+type Interceptor = Box<dyn FnMut(Request<()>) -> Result<Request<()>, Status> + Send>;
+
+#[derive(Debug)]
+pub struct Client {
+    inner: OnlineDecoderClient<InterceptedService<Channel, Interceptor>>,
+    config: interface::DecoderConfig,
+}
+
+#[derive(Debug, Default)]
+pub struct ClientBuilder {
+    api_base: Option<String>,
+    endpoint: Option<String>,
+    client_id: Option<String>,
+    client_secret: Option<String>,
+    auth: Option<crate::Auth>,
+    model_name: Option<String>,
+    keywords: Option<Vec<String>>,
+}
+
+impl ClientBuilder {
+    /// Where access tokens are requested. Defaults to RTZR's API.
+    pub fn api_base(mut self, api_base: impl Into<String>) -> Self {
+        self.api_base = Some(api_base.into());
+        self
+    }
+
+    /// The gRPC server. Defaults to RTZR's streaming endpoint.
+    pub fn endpoint(mut self, endpoint: impl Into<String>) -> Self {
+        self.endpoint = Some(endpoint.into());
+        self
+    }
+
+    pub fn client_id(mut self, client_id: impl Into<String>) -> Self {
+        self.client_id = Some(client_id.into());
+        self
+    }
+
+    pub fn client_secret(mut self, client_secret: impl Into<String>) -> Self {
+        self.client_secret = Some(client_secret.into());
+        self
+    }
+
+    /// Shares an existing token cache. Takes precedence over `api_base` and the credentials.
+    pub fn auth(mut self, auth: crate::Auth) -> Self {
+        self.auth = Some(auth);
+        self
+    }
+
+    /// `sommers_ja` for Japanese. The server uses its Korean model when not set.
+    pub fn model_name(mut self, model_name: impl Into<String>) -> Self {
+        self.model_name = Some(model_name.into());
+        self
+    }
+
+    pub fn keywords(mut self, keywords: impl Into<Vec<String>>) -> Self {
+        self.keywords = Some(keywords.into());
+        self
+    }
+
+    pub async fn build(self) -> Result<Client, crate::Error> {
+        let auth = match self.auth {
+            Some(auth) => auth,
+            None => crate::Auth::new(
+                self.api_base
+                    .as_deref()
+                    .unwrap_or(crate::DEFAULT_API_BASE)
+                    .parse()?,
+                self.client_id.ok_or(crate::Error::MissingCredentials)?,
+                self.client_secret.ok_or(crate::Error::MissingCredentials)?,
+            ),
+        };
+
+        let authorization: MetadataValue<Ascii> = auth
+            .bearer()
+            .await?
+            .parse()
+            .map_err(|_| crate::Error::InvalidToken)?;
+
+        let endpoint = self.endpoint.unwrap_or(DEFAULT_ENDPOINT.to_string());
+        let endpoint = if endpoint.starts_with("https://") {
+            Endpoint::from_shared(endpoint)?
+                .tls_config(ClientTlsConfig::new().with_native_roots())?
+        } else {
+            Endpoint::from_shared(endpoint)?
+        };
+        let channel = endpoint.connect().await?;
+
+        let inner =
+            OnlineDecoderClient::with_interceptor(channel, Self::make_interceptor(authorization));
+
+        let config = interface::DecoderConfig {
+            sample_rate: 16 * 1000,
+            encoding: AudioEncoding::Linear16.into(),
+            model_name: self.model_name,
+            use_itn: Some(true),
+            use_disfluency_filter: Some(false),
+            use_profanity_filter: Some(false),
+            stream_config: None,
+            keywords: self.keywords.unwrap_or_default(),
+        };
+
+        Ok(Client { inner, config })
+    }
+
+    fn make_interceptor(authorization: MetadataValue<Ascii>) -> Interceptor {
+        Box::new(move |mut req: Request<()>| {
+            req.metadata_mut()
+                .insert("authorization", authorization.clone());
+            Ok(req)
+        })
+    }
+}
+
+impl Client {
+    pub fn builder() -> ClientBuilder {
+        ClientBuilder::default()
+    }
+
+    /// Streams 16kHz mono PCM (16-bit little-endian) to the decoder.
+    pub async fn from_audio<S, E>(
+        &mut self,
+        audio: S,
+    ) -> Result<impl Stream<Item = Result<interface::DecoderResponse, crate::Error>>, crate::Error>
+    where
+        S: Stream<Item = Result<Bytes, E>> + Send + Unpin + 'static,
+        E: std::error::Error + Send + Sync + 'static,
+    {
+        // The first message must be the config, and every following one audio.
+        let config_request = interface::DecoderRequest {
+            streaming_request: Some(StreamingRequest::StreamingConfig(self.config.clone())),
+        };
+        let config_stream = futures_util::stream::once(async move { config_request });
+
+        let audio_request_stream = audio.filter_map(|chunk| async {
+            chunk.ok().map(|chunk| interface::DecoderRequest {
+                streaming_request: Some(StreamingRequest::AudioContent(chunk.to_vec())),
+            })
+        });
+
+        let response = self
+            .inner
+            .decode(config_stream.chain(audio_request_stream))
+            .await?
+            .into_inner()
+            .map(|message| Ok(message?));
+
+        Ok(response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::{Ipv4Addr, SocketAddr};
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    use interface::{
+        online_decoder_server::{OnlineDecoder, OnlineDecoderServer},
+        DecoderRequest, DecoderResponse, SpeechRecognitionAlternative, StreamingRecognitionResult,
+        WordInfo,
+    };
+    use tokio_stream::wrappers::TcpListenerStream;
+
+    /// Stand-in for RTZR: answers every audio chunk with an interim result, and the end of the
+    /// stream with a final one.
+    struct StandIn;
+
+    #[tonic::async_trait]
+    impl OnlineDecoder for StandIn {
+        type DecodeStream =
+            std::pin::Pin<Box<dyn Stream<Item = Result<DecoderResponse, Status>> + Send>>;
+
+        async fn decode(
+            &self,
+            request: Request<tonic::Streaming<DecoderRequest>>,
+        ) -> Result<tonic::Response<Self::DecodeStream>, Status> {
+            let authorization = request.metadata().get("authorization");
+            if authorization.and_then(|v| v.to_str().ok()) != Some("bearer token") {
+                return Err(Status::unauthenticated("invalid token"));
+            }
+
+            let mut requests = request.into_inner();
+            match requests.next().await {
+                Some(Ok(DecoderRequest {
+                    streaming_request: Some(StreamingRequest::StreamingConfig(config)),
+                })) if config.sample_rate == 16000 => {}
+                _ => return Err(Status::invalid_argument("expected config")),
+            }
+
+            let output = async_stream::try_stream! {
+                let mut received = 0;
+
+                while let Some(request) = requests.next().await {
+                    if let Some(StreamingRequest::AudioContent(audio)) = request?.streaming_request {
+                        received += audio.len();
+                        yield result("hello", false);
+                    }
+                }
+
+                if received > 0 {
+                    yield result("hello world", true);
+                }
+            };
+
+            Ok(tonic::Response::new(Box::pin(output)))
+        }
+    }
+
+    fn result(text: &str, is_final: bool) -> DecoderResponse {
+        let words = text
+            .split(' ')
+            .enumerate()
+            .map(|(i, word)| WordInfo {
+                start_at: 1000 + 500 * i as i64,
+                duration: 400,
+                text: word.to_string(),
+                confidence: 0.9,
+                speaker_tag: 0,
+            })
+            .collect();
+
+        DecoderResponse {
+            error: false,
+            results: vec![StreamingRecognitionResult {
+                alternatives: vec![SpeechRecognitionAlternative {
+                    text: text.to_string(),
+                    confidence: 0.9,
+                    words,
+                }],
+                is_final,
+                stability: 0.0,
+                duration: 900,
+                start_at: 1000,
+            }],
+            speech_event_type: 0,
+        }
+    }
+
+    async fn auth_server() -> (SocketAddr, Arc<AtomicUsize>) {
+        let requests = Arc::new(AtomicUsize::new(0));
+
+        let app = axum::Router::new()
+            .route(
+                "/v1/authenticate",
+                axum::routing::post(
+                    |axum::extract::State(requests): axum::extract::State<Arc<AtomicUsize>>| async move {
+                        requests.fetch_add(1, Ordering::SeqCst);
+                        axum::Json(
+                            serde_json::json!({ "access_token": "token", "expire_at": u64::MAX }),
+                        )
+                    },
+                ),
+            )
+            .with_state(requests.clone());
+
+        let listener = tokio::net::TcpListener::bind(SocketAddr::from((Ipv4Addr::LOCALHOST, 0)))
+            .await
+            .unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        (addr, requests)
+    }
+
+    async fn decoder_server() -> SocketAddr {
+        let listener = tokio::net::TcpListener::bind(SocketAddr::from((Ipv4Addr::LOCALHOST, 0)))
+            .await
+            .unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            tonic::transport::Server::builder()
+                .add_service(OnlineDecoderServer::new(StandIn))
+                .serve_with_incoming(TcpListenerStream::new(listener))
+                .await
+                .unwrap()
+        });
+
+        addr
+    }
+
+    #[tokio::test]
+    async fn test_stand_in() {
+        let (auth_addr, _) = auth_server().await;
+
+        let mut client = Client::builder()
+            .api_base(format!("http://{}", auth_addr))
+            .endpoint(format!("http://{}", decoder_server().await))
+            .client_id("id")
+            .client_secret("secret")
+            .build()
+            .await
+            .unwrap();
+
+        let audio = futures_util::stream::iter(
+            vec![Bytes::from(vec![0u8; 3200]); 2]
+                .into_iter()
+                .map(Ok::<_, std::io::Error>),
+        );
+
+        let responses: Vec<DecoderResponse> = client
+            .from_audio(audio)
+            .await
+            .unwrap()
+            .map(Result::unwrap)
+            .collect()
+            .await;
+
+        assert_eq!(
+            responses,
+            vec![
+                result("hello", false),
+                result("hello", false),
+                result("hello world", true)
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_shared_auth() {
+        let (auth_addr, requests) = auth_server().await;
+        let decoder_addr = decoder_server().await;
+
+        let auth = crate::Auth::new(
+            format!("http://{}", auth_addr).parse().unwrap(),
+            "id",
+            "secret",
+        );
+
+        for _ in 0..2 {
+            Client::builder()
+                .endpoint(format!("http://{}", decoder_addr))
+                .auth(auth.clone())
+                .build()
+                .await
+                .unwrap();
+        }
+
+        assert_eq!(requests.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_missing_credentials() {
+        let result = Client::builder().client_id("id").build().await;
+        assert!(matches!(result, Err(crate::Error::MissingCredentials)));
+    }
+}
@@ -0,0 +1,70 @@
+use super::{Status, SubmitResponse, TranscribeConfig, TranscribeResponse, Utterance};
+
+impl super::Client {
+    /// Uploads the file and waits until it is transcribed.
+    pub async fn transcribe_local_file(
+        &self,
+        file_path: impl AsRef<std::path::Path>,
+        config: &TranscribeConfig,
+    ) -> Result<Vec<Utterance>, crate::Error> {
+        let id = self.submit_local_file(file_path, config).await?;
+
+        loop {
+            let res = self.get_transcription(&id).await?;
+
+            match res.status {
+                Status::Completed => {
+                    return Ok(res.results.map(|r| r.utterances).unwrap_or_default())
+                }
+                Status::Failed => {
+                    return Err(crate::Error::TranscribeFailed(
+                        res.error.map(|e| e.message).unwrap_or_default(),
+                    ))
+                }
+                Status::Transcribing => tokio::time::sleep(self.poll_interval).await,
+            }
+        }
+    }
+
+    pub async fn submit_local_file(
+        &self,
+        file_path: impl AsRef<std::path::Path>,
+        config: &TranscribeConfig,
+    ) -> Result<String, crate::Error> {
+        let url = self.url(&["v1", "transcribe"]);
+
+        let form = reqwest::multipart::Form::new()
+            .text("config", serde_json::to_string(config)?)
+            .file("file", file_path)
+            .await?;
+
+        let res = self
+            .client
+            .post(url)
+            .header(reqwest::header::AUTHORIZATION, self.auth.bearer().await?)
+            .multipart(form)
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<SubmitResponse>()
+            .await?;
+
+        Ok(res.id)
+    }
+
+    pub async fn get_transcription(&self, id: &str) -> Result<TranscribeResponse, crate::Error> {
+        let url = self.url(&["v1", "transcribe", id]);
+
+        let res = self
+            .client
+            .get(url)
+            .header(reqwest::header::AUTHORIZATION, self.auth.bearer().await?)
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<TranscribeResponse>()
+            .await?;
+
+        Ok(res)
+    }
+}
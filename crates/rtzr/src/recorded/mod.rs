@@ -0,0 +1,181 @@
+mod local_file;
+
+mod types;
+pub use types::*;
+
+use std::time::Duration;
+
+#[derive(Debug, Default)]
+pub struct ClientBuilder {
+    api_base: Option<String>,
+    client_id: Option<String>,
+    client_secret: Option<String>,
+    poll_interval: Option<Duration>,
+}
+
+impl ClientBuilder {
+    /// Defaults to RTZR's API.
+    pub fn api_base(mut self, api_base: impl Into<String>) -> Self {
+        self.api_base = Some(api_base.into());
+        self
+    }
+
+    pub fn client_id(mut self, client_id: impl Into<String>) -> Self {
+        self.client_id = Some(client_id.into());
+        self
+    }
+
+    pub fn client_secret(mut self, client_secret: impl Into<String>) -> Self {
+        self.client_secret = Some(client_secret.into());
+        self
+    }
+
+    /// How often to check on a submitted file. RTZR recommends 5 seconds.
+    pub fn poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = Some(poll_interval);
+        self
+    }
+
+    pub fn build(self) -> Result<Client, crate::Error> {
+        let api_base: url::Url = self
+            .api_base
+            .as_deref()
+            .unwrap_or(crate::DEFAULT_API_BASE)
+            .parse()?;
+        if api_base.cannot_be_a_base() {
+            return Err(crate::Error::InvalidApiBase(api_base.to_string()));
+        }
+
+        Ok(Client {
+            auth: crate::Auth::new(
+                api_base.clone(),
+                self.client_id.ok_or(crate::Error::MissingCredentials)?,
+                self.client_secret.ok_or(crate::Error::MissingCredentials)?,
+            ),
+            api_base,
+            client: reqwest::Client::new(),
+            poll_interval: self.poll_interval.unwrap_or(Duration::from_secs(5)),
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Client {
+    api_base: url::Url,
+    auth: crate::Auth,
+    client: reqwest::Client,
+    poll_interval: Duration,
+}
+
+impl Client {
+    pub fn builder() -> ClientBuilder {
+        ClientBuilder::default()
+    }
+
+    fn url(&self, segments: &[&str]) -> url::Url {
+        let mut url = self.api_base.clone();
+        url.path_segments_mut()
+            .expect("checked in ClientBuilder::build")
+            .pop_if_empty()
+            .extend(segments);
+        url
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::{Ipv4Addr, SocketAddr};
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    use axum::{body::Bytes, extract::State, http::HeaderMap, routing, Json, Router};
+
+    /// Stand-in for RTZR: the first poll finds the file still transcribing, the second one done.
+    async fn server() -> SocketAddr {
+        let polls = Arc::new(AtomicUsize::new(0));
+
+        let app = Router::new()
+            .route(
+                "/v1/authenticate",
+                routing::post(|| async {
+                    Json(serde_json::json!({ "access_token": "token", "expire_at": u64::MAX }))
+                }),
+            )
+            .route(
+                "/v1/transcribe",
+                routing::post(|headers: HeaderMap, body: Bytes| async move {
+                    assert_eq!(headers["authorization"], "bearer token");
+                    assert!(!body.is_empty());
+                    Json(serde_json::json!({ "id": "job" }))
+                }),
+            )
+            .route(
+                "/v1/transcribe/job",
+                routing::get(|State(polls): State<Arc<AtomicUsize>>| async move {
+                    if polls.fetch_add(1, Ordering::SeqCst) == 0 {
+                        return Json(serde_json::json!({ "id": "job", "status": "transcribing" }));
+                    }
+
+                    Json(serde_json::json!({
+                        "id": "job",
+                        "status": "completed",
+                        "results": {
+                            "utterances": [
+                                { "start_at": 320, "duration": 1500, "msg": "안녕하세요", "spk": 0 },
+                                { "start_at": 2100, "duration": 900, "msg": "반갑습니다", "spk": 1 }
+                            ]
+                        }
+                    }))
+                }),
+            )
+            .with_state(polls);
+
+        let listener = tokio::net::TcpListener::bind(SocketAddr::from((Ipv4Addr::LOCALHOST, 0)))
+            .await
+            .unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        addr
+    }
+
+    #[tokio::test]
+    async fn test_stand_in() {
+        let client = Client::builder()
+            .api_base(format!("http://{}", server().await))
+            .client_id("id")
+            .client_secret("secret")
+            .poll_interval(Duration::from_millis(10))
+            .build()
+            .unwrap();
+
+        let utterances = client
+            .transcribe_local_file(
+                hypr_data::korean_2::AUDIO_PATH,
+                &TranscribeConfig::default(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(
+            utterances,
+            vec![
+                Utterance {
+                    start_at: 320,
+                    duration: 1500,
+                    msg: "안녕하세요".to_string(),
+                    spk: Some(0),
+                },
+                Utterance {
+                    start_at: 2100,
+                    duration: 900,
+                    msg: "반갑습니다".to_string(),
+                    spk: Some(1),
+                },
+            ]
+        );
+    }
+}
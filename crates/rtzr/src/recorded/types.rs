@@ -0,0 +1,54 @@
+// https://developers.rtzr.ai/docs/stt-file/
+
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+pub struct TranscribeConfig {
+    pub model_name: Option<String>,
+    pub use_diarization: bool,
+    pub use_itn: bool,
+    pub use_disfluency_filter: bool,
+    pub use_profanity_filter: bool,
+    pub keywords: Vec<String>,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct SubmitResponse {
+    pub id: String,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Status {
+    Transcribing,
+    Completed,
+    Failed,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct TranscribeResponse {
+    pub id: String,
+    pub status: Status,
+    pub results: Option<Results>,
+    pub error: Option<ResponseError>,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct Results {
+    pub utterances: Vec<Utterance>,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Utterance {
+    /// Milliseconds from the start of the file.
+    pub start_at: u64,
+    /// Milliseconds.
+    pub duration: u64,
+    pub msg: String,
+    /// Only set with `use_diarization`.
+    pub spk: Option<u8>,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct ResponseError {
+    pub code: String,
+    pub message: String,
+}
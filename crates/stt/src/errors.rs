@@ -8,4 +8,12 @@ pub enum Error {
     Clova(#[from] hypr_clova::Error),
     #[error("clova error {0}")]
     ClovaError(String),
+    #[error(transparent)]
+    Rtzr(#[from] hypr_rtzr::Error),
+    #[error("rtzr decoder error")]
+    RtzrError,
+    #[error("no provider available for language: {0}")]
+    UnsupportedLanguage(String),
+    #[error("invalid routing rule: {0}")]
+    InvalidRouting(String),
 }
@@ -1,7 +1,9 @@
 mod deepgram;
 mod errors;
+mod provider;
 
 pub use errors::*;
+pub use provider::*;
 
 #[cfg(feature = "realtime")]
 pub mod realtime;
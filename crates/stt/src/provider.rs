@@ -0,0 +1,218 @@
+use std::str::FromStr;
+
+use hypr_language::{Language, ISO639};
+
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    serde::Serialize,
+    serde::Deserialize,
+    strum::Display,
+    strum::EnumString,
+)]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum Provider {
+    Deepgram,
+    Clova,
+    Whisper,
+    Rtzr,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct Capabilities {
+    /// Words come with a speaker index.
+    pub diarization: bool,
+    /// Non-final results are sent while the speaker is still talking.
+    pub interim_results: bool,
+    /// Vocabulary can be boosted with keywords.
+    pub keywords: bool,
+}
+
+impl Provider {
+    pub fn capabilities(&self) -> Capabilities {
+        match self {
+            Provider::Deepgram => Capabilities {
+                diarization: true,
+                interim_results: true,
+                keywords: true,
+            },
+            Provider::Clova => Capabilities {
+                diarization: false,
+                interim_results: false,
+                keywords: true,
+            },
+            Provider::Whisper => Capabilities {
+                diarization: false,
+                interim_results: false,
+                keywords: false,
+            },
+            Provider::Rtzr => Capabilities {
+                diarization: false,
+                interim_results: true,
+                keywords: true,
+            },
+        }
+    }
+
+    pub fn supports(&self, language: &Language) -> bool {
+        match self {
+            Provider::Deepgram => language.clone().for_deepgram().is_ok(),
+            // Our Clova clients are always configured for Korean.
+            Provider::Clova => language.iso639() == ISO639::Ko,
+            Provider::Whisper => {
+                TryInto::<hypr_whisper::Language>::try_into(language.clone()).is_ok()
+            }
+            Provider::Rtzr => matches!(language.iso639(), ISO639::Ko | ISO639::Ja),
+        }
+    }
+}
+
+/// Which providers handle which language, in order of preference.
+///
+/// Parsed from comma separated rules like `ko=clova|deepgram,de=whisper,*=deepgram`,
+/// where `*` applies to languages without a rule of their own.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Routing {
+    rules: Vec<(ISO639, Vec<Provider>)>,
+    fallback: Vec<Provider>,
+}
+
+impl Default for Routing {
+    fn default() -> Self {
+        Self {
+            rules: vec![
+                (
+                    ISO639::Ko,
+                    vec![Provider::Clova, Provider::Rtzr, Provider::Deepgram],
+                ),
+                (ISO639::De, vec![Provider::Whisper, Provider::Deepgram]),
+                (ISO639::Zh, vec![Provider::Deepgram]),
+            ],
+            fallback: vec![],
+        }
+    }
+}
+
+impl Routing {
+    pub fn empty() -> Self {
+        Self {
+            rules: vec![],
+            fallback: vec![],
+        }
+    }
+
+    pub fn rule(mut self, language: ISO639, providers: impl Into<Vec<Provider>>) -> Self {
+        self.rules.retain(|(l, _)| *l != language);
+        self.rules.push((language, providers.into()));
+        self
+    }
+
+    pub fn fallback(mut self, providers: impl Into<Vec<Provider>>) -> Self {
+        self.fallback = providers.into();
+        self
+    }
+
+    /// Providers to try for `language`, skipping the ones that can't handle it or that `available` rejects.
+    pub fn route(
+        &self,
+        language: &Language,
+        available: impl Fn(Provider) -> bool,
+    ) -> Result<Vec<Provider>, crate::Error> {
+        let providers = self
+            .rules
+            .iter()
+            .find(|(l, _)| *l == language.iso639())
+            .map_or(&self.fallback, |(_, providers)| providers);
+
+        let providers: Vec<Provider> = providers
+            .iter()
+            .copied()
+            .filter(|p| p.supports(language) && available(*p))
+            .collect();
+
+        if providers.is_empty() {
+            return Err(crate::Error::UnsupportedLanguage(
+                language.iso639().code().to_string(),
+            ));
+        }
+
+        Ok(providers)
+    }
+}
+
+impl FromStr for Routing {
+    type Err = crate::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut routing = Routing::empty();
+
+        for rule in s.split(',').map(str::trim).filter(|r| !r.is_empty()) {
+            let invalid = || crate::Error::InvalidRouting(rule.to_string());
+
+            let (language, providers) = rule.split_once('=').ok_or_else(invalid)?;
+            let providers = providers
+                .split('|')
+                .map(|p| Provider::from_str(p.trim()).map_err(|_| invalid()))
+                .collect::<Result<Vec<_>, _>>()?;
+
+            routing = match language.trim() {
+                "*" => routing.fallback(providers),
+                code => routing.rule(ISO639::from_str(code).map_err(|_| invalid())?, providers),
+            };
+        }
+
+        Ok(routing)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_routing() {
+        let routing = Routing::default();
+
+        assert_eq!(
+            routing.route(&ISO639::Ko.into(), |_| true).unwrap(),
+            vec![Provider::Clova, Provider::Rtzr, Provider::Deepgram]
+        );
+        assert_eq!(
+            routing
+                .route(&ISO639::Ko.into(), |p| p == Provider::Deepgram)
+                .unwrap(),
+            vec![Provider::Deepgram]
+        );
+        assert!(matches!(
+            routing.route(&ISO639::Fr.into(), |_| true),
+            Err(crate::Error::UnsupportedLanguage(code)) if code == "fr"
+        ));
+    }
+
+    #[test]
+    fn test_parse_routing() {
+        let routing: Routing = "ko = clova|deepgram, *=deepgram|whisper".parse().unwrap();
+
+        assert_eq!(
+            routing,
+            Routing::empty()
+                .rule(ISO639::Ko, [Provider::Clova, Provider::Deepgram])
+                .fallback([Provider::Deepgram, Provider::Whisper])
+        );
+        // No rule for French, so the fallback applies, minus the unavailable Whisper.
+        assert_eq!(
+            routing
+                .route(&ISO639::Fr.into(), |p| p != Provider::Whisper)
+                .unwrap(),
+            vec![Provider::Deepgram]
+        );
+
+        assert!("ko=google".parse::<Routing>().is_err());
+        assert!("korean=clova".parse::<Routing>().is_err());
+        assert!("clova".parse::<Routing>().is_err());
+    }
+}
@@ -28,15 +28,15 @@ impl<S, E> RealtimeSpeechToText<S, E> for hypr_clova::realtime::Client {
                     clova::StreamResponse::TranscribeFailure(a) => Some(Err(
                         crate::Error::ClovaError(serde_json::to_string(&a).unwrap()),
                     )),
-                    clova::StreamResponse::TranscribeSuccess(r) => Some(Ok(ListenOutputChunk {
-                        words: vec![Word {
+                    clova::StreamResponse::TranscribeSuccess(r) => {
+                        Some(Ok(ListenOutputChunk::finalized(vec![Word {
                             text: r.transcription.text,
                             speaker: None,
                             start_ms: Some(r.transcription.start_timestamp * 1000),
                             end_ms: Some(r.transcription.end_timestamp * 1000),
                             confidence: Some(r.transcription.confidence as f32),
-                        }],
-                    })),
+                        }])))
+                    }
                     clova::StreamResponse::Config(_) => None,
                 },
             };
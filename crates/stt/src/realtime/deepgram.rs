@@ -57,7 +57,9 @@ impl<S, E> RealtimeSpeechToText<S, E> for crate::deepgram::DeepgramClient {
             let item = match result {
                 Err(e) => Some(Err(e.into())),
                 Ok(resp) => match resp {
-                    DeepgramStreamResponse::TranscriptResponse { channel, .. } => {
+                    DeepgramStreamResponse::TranscriptResponse {
+                        channel, is_final, ..
+                    } => {
                         let data = channel.alternatives.first().unwrap();
 
                         if data.words.is_empty() {
@@ -82,7 +84,11 @@ impl<S, E> RealtimeSpeechToText<S, E> for crate::deepgram::DeepgramClient {
                                 })
                                 .collect();
 
-                            Some(Ok(ListenOutputChunk { words }))
+                            Some(Ok(ListenOutputChunk {
+                                words,
+                                is_final,
+                                revision: 0,
+                            }))
                         }
                     }
                     _ => None,
@@ -4,9 +4,10 @@ use std::error::Error;
 
 mod clova;
 mod deepgram;
+mod rtzr;
 mod whisper;
 
-use crate::deepgram::DeepgramClient;
+use crate::{deepgram::DeepgramClient, Provider, Routing};
 use hypr_listener_interface::ListenOutputChunk;
 
 #[allow(dead_code)]
@@ -29,6 +30,11 @@ pub trait RealtimeSpeechToText<S, E> {
 pub struct ClientBuilder {
     pub deepgram_api_key: Option<String>,
     pub clova_api_key: Option<String>,
+    pub whisper_api_base: Option<String>,
+    pub whisper_api_key: Option<String>,
+    pub rtzr_client_id: Option<String>,
+    pub rtzr_client_secret: Option<String>,
+    pub routing: Option<Routing>,
 }
 
 impl ClientBuilder {
@@ -42,10 +48,34 @@ impl ClientBuilder {
         self
     }
 
+    pub fn whisper(mut self, api_base: impl Into<String>, api_key: impl Into<String>) -> Self {
+        self.whisper_api_base = Some(api_base.into());
+        self.whisper_api_key = Some(api_key.into());
+        self
+    }
+
+    pub fn rtzr(mut self, client_id: impl Into<String>, client_secret: impl Into<String>) -> Self {
+        self.rtzr_client_id = Some(client_id.into());
+        self.rtzr_client_secret = Some(client_secret.into());
+        self
+    }
+
+    pub fn routing(mut self, routing: Routing) -> Self {
+        self.routing = Some(routing);
+        self
+    }
+
     pub fn build(self) -> Client {
         Client {
             deepgram_api_key: self.deepgram_api_key,
             clova_api_key: self.clova_api_key,
+            whisper_api_base: self.whisper_api_base,
+            whisper_api_key: self.whisper_api_key,
+            rtzr_auth: self
+                .rtzr_client_id
+                .zip(self.rtzr_client_secret)
+                .map(|(id, secret)| hypr_rtzr::Auth::from_credentials(id, secret)),
+            routing: self.routing.unwrap_or_default(),
         }
     }
 }
@@ -55,12 +85,29 @@ pub enum MultiClient {
     Clova(hypr_clova::realtime::Client),
     Deepgram(DeepgramClient),
     Whisper(hypr_whisper::cloud::WhisperClient),
+    Rtzr(hypr_rtzr::realtime::Client),
+}
+
+impl MultiClient {
+    pub fn provider(&self) -> Provider {
+        match self {
+            MultiClient::Clova(_) => Provider::Clova,
+            MultiClient::Deepgram(_) => Provider::Deepgram,
+            MultiClient::Whisper(_) => Provider::Whisper,
+            MultiClient::Rtzr(_) => Provider::Rtzr,
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct Client {
     pub deepgram_api_key: Option<String>,
     pub clova_api_key: Option<String>,
+    pub whisper_api_base: Option<String>,
+    pub whisper_api_key: Option<String>,
+    /// Shared across connections so the access token is reused until it expires.
+    pub rtzr_auth: Option<hypr_rtzr::Auth>,
+    pub routing: Routing,
 }
 
 impl Client {
@@ -68,37 +115,85 @@ impl Client {
         ClientBuilder::default()
     }
 
-    pub async fn for_language(&self, language: hypr_language::Language) -> MultiClient {
-        match language.iso639() {
-            hypr_language::ISO639::Ko => {
+    /// Providers to try for `language`, limited to the ones we have credentials for.
+    pub fn providers(
+        &self,
+        language: &hypr_language::Language,
+    ) -> Result<Vec<Provider>, crate::Error> {
+        self.routing.route(language, |provider| match provider {
+            Provider::Deepgram => self.deepgram_api_key.is_some(),
+            Provider::Clova => self.clova_api_key.is_some(),
+            Provider::Whisper => self.whisper_api_base.is_some() && self.whisper_api_key.is_some(),
+            Provider::Rtzr => self.rtzr_auth.is_some(),
+        })
+    }
+
+    /// Connects to the first provider that works, falling back to the next one on failure.
+    pub async fn for_language(
+        &self,
+        language: hypr_language::Language,
+    ) -> Result<MultiClient, crate::Error> {
+        let mut last_error = None;
+
+        for provider in self.providers(&language)? {
+            match self.connect(provider, language.clone()).await {
+                Ok(client) => return Ok(client),
+                Err(e) => last_error = Some(e),
+            }
+        }
+
+        // `providers` never returns an empty list.
+        Err(last_error.unwrap())
+    }
+
+    async fn connect(
+        &self,
+        provider: Provider,
+        language: hypr_language::Language,
+    ) -> Result<MultiClient, crate::Error> {
+        match provider {
+            Provider::Clova => {
                 let clova = hypr_clova::realtime::Client::builder()
-                    .api_key(self.clova_api_key.as_ref().unwrap())
+                    .api_key(self.clova_api_key.clone().unwrap_or_default())
                     .keywords(vec!["하이퍼노트".to_string()])
                     .build()
-                    .await
-                    .unwrap();
-                MultiClient::Clova(clova)
+                    .await?;
+                Ok(MultiClient::Clova(clova))
             }
-            hypr_language::ISO639::De => {
+            Provider::Whisper => {
                 let whisper = hypr_whisper::cloud::WhisperClient::builder()
-                    .api_base(std::env::var("WHISPER_API_BASE").unwrap())
-                    .api_key(std::env::var("WHISPER_API_KEY").unwrap())
-                    .language(language.try_into().unwrap())
+                    .api_base(self.whisper_api_base.clone().unwrap_or_default())
+                    .api_key(self.whisper_api_key.clone().unwrap_or_default())
+                    .language(language.try_into()?)
                     .build();
-
-                MultiClient::Whisper(whisper)
+                Ok(MultiClient::Whisper(whisper))
             }
-            hypr_language::ISO639::Zh => {
+            Provider::Deepgram => {
                 let deepgram = DeepgramClient::builder()
-                    .api_key(self.deepgram_api_key.as_ref().unwrap())
+                    .api_key(self.deepgram_api_key.clone().unwrap_or_default())
                     .keywords(vec!["Hyprnote".to_string()])
                     .language(language)
-                    .build()
-                    .unwrap();
-
-                MultiClient::Deepgram(deepgram)
+                    .build()?;
+                Ok(MultiClient::Deepgram(deepgram))
+            }
+            Provider::Rtzr => {
+                let auth = self
+                    .rtzr_auth
+                    .clone()
+                    .ok_or(hypr_rtzr::Error::MissingCredentials)?;
+                let builder = hypr_rtzr::realtime::Client::builder()
+                    .auth(auth)
+                    .keywords(vec!["하이퍼노트".to_string()]);
+
+                // The default model is Korean.
+                let builder = match language.iso639() {
+                    hypr_language::ISO639::Ja => builder.model_name("sommers_ja"),
+                    _ => builder,
+                };
+
+                let rtzr = builder.build().await?;
+                Ok(MultiClient::Rtzr(rtzr))
             }
-            _ => panic!("Unsupported language: {:?}", language),
         }
     }
 }
@@ -119,6 +214,7 @@ where
             MultiClient::Clova(client) => Ok(Box::new(client.transcribe(stream).await?)),
             MultiClient::Deepgram(client) => Ok(Box::new(client.transcribe(stream).await?)),
             MultiClient::Whisper(client) => Ok(Box::new(client.transcribe(stream).await?)),
+            MultiClient::Rtzr(client) => Ok(Box::new(client.transcribe(stream).await?)),
         }
     }
 }
@@ -199,7 +295,8 @@ mod tests {
             .deepgram_api_key(std::env::var("DEEPGRAM_API_KEY").unwrap())
             .build()
             .for_language(hypr_language::ISO639::Zh.into())
-            .await;
+            .await
+            .unwrap();
 
         let mut transcript_stream = client.transcribe(audio_stream).await.unwrap();
 
@@ -228,7 +325,8 @@ mod tests {
             .clova_api_key(std::env::var("CLOVA_API_KEY").unwrap())
             .build()
             .for_language(hypr_language::ISO639::Ko.into())
-            .await;
+            .await
+            .unwrap();
 
         let mut transcript_stream = client.transcribe(audio_stream).await.unwrap();
 
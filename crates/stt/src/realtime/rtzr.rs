@@ -0,0 +1,159 @@
+use bytes::Bytes;
+use futures_util::{future, Stream, StreamExt};
+use std::error::Error;
+
+use super::RealtimeSpeechToText;
+
+pub use hypr_listener_interface::{ListenOutputChunk, SpeakerIdentity, Word};
+pub use hypr_rtzr::realtime::interface as rtzr;
+
+impl<S, E> RealtimeSpeechToText<S, E> for hypr_rtzr::realtime::Client {
+    async fn transcribe(
+        &mut self,
+        input_stream: S,
+    ) -> Result<
+        Box<dyn Stream<Item = Result<ListenOutputChunk, crate::Error>> + Send + Unpin>,
+        crate::Error,
+    >
+    where
+        S: Stream<Item = Result<Bytes, E>> + Send + Unpin + 'static,
+        E: Error + Send + Sync + 'static,
+    {
+        let output_stream = self.from_audio(input_stream).await?;
+
+        let stream = output_stream
+            .scan(0, |revision, item| {
+                let item = match item {
+                    Err(e) => vec![Err(e.into())],
+                    Ok(response) if response.error => vec![Err(crate::Error::RtzrError)],
+                    Ok(response) => response
+                        .results
+                        .iter()
+                        .filter_map(|result| {
+                            *revision += 1;
+                            chunk(result, *revision).map(Ok)
+                        })
+                        .collect(),
+                };
+
+                future::ready(Some(futures_util::stream::iter(item)))
+            })
+            .flatten();
+
+        Ok(Box::from(Box::pin(stream)))
+    }
+}
+
+/// Word timings are relative to the start of the stream. Without them, the whole result becomes
+/// a single word spanning the result.
+fn chunk(result: &rtzr::StreamingRecognitionResult, revision: u64) -> Option<ListenOutputChunk> {
+    let alternative = result.alternatives.first()?;
+
+    let words: Vec<Word> = if alternative.words.is_empty() {
+        let text = alternative.text.trim();
+        if text.is_empty() {
+            return None;
+        }
+
+        vec![Word {
+            text: text.to_string(),
+            speaker: None,
+            start_ms: Some(result.start_at.max(0) as u64),
+            end_ms: Some((result.start_at + result.duration).max(0) as u64),
+            confidence: (alternative.confidence > 0.0).then_some(alternative.confidence),
+        }]
+    } else {
+        alternative
+            .words
+            .iter()
+            .filter(|w| !w.text.trim().is_empty())
+            .map(|w| Word {
+                text: w.text.trim().to_string(),
+                // Tags start at 1, and are only set with diarization.
+                speaker: (w.speaker_tag > 0).then(|| SpeakerIdentity::Unassigned {
+                    index: (w.speaker_tag - 1) as u8,
+                }),
+                start_ms: Some(w.start_at.max(0) as u64),
+                end_ms: Some((w.start_at + w.duration).max(0) as u64),
+                confidence: (w.confidence > 0.0).then_some(w.confidence),
+            })
+            .collect()
+    };
+
+    Some(ListenOutputChunk {
+        words,
+        is_final: result.is_final,
+        revision,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(
+        text: &str,
+        words: Vec<rtzr::WordInfo>,
+        is_final: bool,
+    ) -> rtzr::StreamingRecognitionResult {
+        rtzr::StreamingRecognitionResult {
+            alternatives: vec![rtzr::SpeechRecognitionAlternative {
+                text: text.to_string(),
+                confidence: 0.0,
+                words,
+            }],
+            is_final,
+            stability: 0.0,
+            duration: 900,
+            start_at: 1000,
+        }
+    }
+
+    #[test]
+    fn test_chunk() {
+        let words = vec![
+            rtzr::WordInfo {
+                start_at: 1000,
+                duration: 400,
+                text: "안녕하세요".to_string(),
+                confidence: 0.9,
+                speaker_tag: 2,
+            },
+            rtzr::WordInfo {
+                start_at: 1500,
+                duration: 400,
+                text: " ".to_string(),
+                confidence: 0.0,
+                speaker_tag: 0,
+            },
+        ];
+
+        assert_eq!(
+            chunk(&result("안녕하세요", words, true), 3).unwrap(),
+            ListenOutputChunk {
+                words: vec![Word {
+                    text: "안녕하세요".to_string(),
+                    speaker: Some(SpeakerIdentity::Unassigned { index: 1 }),
+                    start_ms: Some(1000),
+                    end_ms: Some(1400),
+                    confidence: Some(0.9),
+                }],
+                is_final: true,
+                revision: 3,
+            }
+        );
+
+        assert_eq!(
+            chunk(&result(" 안녕 ", vec![], false), 1).unwrap().words,
+            vec![Word {
+                text: "안녕".to_string(),
+                speaker: None,
+                start_ms: Some(1000),
+                end_ms: Some(1900),
+                confidence: None,
+            }]
+        );
+
+        assert!(chunk(&result("", vec![], false), 1).is_none());
+    }
+}
@@ -22,15 +22,13 @@ impl<S, E> RealtimeSpeechToText<S, E> for WhisperClient {
         let audio_stream = Box::pin(audio.filter_map(|chunk| async { chunk.ok() }));
         let s1 = self.from_audio(audio_stream).await.unwrap();
         let s2 = s1.map(|output| {
-            Ok(ListenOutputChunk {
-                words: vec![Word {
-                    text: output.text,
-                    speaker: None,
-                    end_ms: None,
-                    start_ms: None,
-                    confidence: None,
-                }],
-            })
+            Ok(ListenOutputChunk::finalized(vec![Word {
+                text: output.text,
+                speaker: None,
+                end_ms: None,
+                start_ms: None,
+                confidence: None,
+            }]))
         });
 
         Ok(Box::from(Box::pin(s2)))
@@ -3,8 +3,9 @@ use std::future::Future;
 
 mod clova;
 mod deepgram;
+mod rtzr;
 
-use crate::deepgram::DeepgramClient;
+use crate::{deepgram::DeepgramClient, Provider, Routing};
 
 pub enum RecordedSpeech {
     File(std::path::PathBuf),
@@ -19,6 +20,9 @@ pub trait RecordedSpeechToText {
 pub struct ClientBuilder {
     pub deepgram_api_key: Option<String>,
     pub clova_api_key: Option<String>,
+    pub rtzr_client_id: Option<String>,
+    pub rtzr_client_secret: Option<String>,
+    pub routing: Option<Routing>,
 }
 
 impl ClientBuilder {
@@ -32,10 +36,24 @@ impl ClientBuilder {
         self
     }
 
+    pub fn rtzr(mut self, client_id: impl Into<String>, client_secret: impl Into<String>) -> Self {
+        self.rtzr_client_id = Some(client_id.into());
+        self.rtzr_client_secret = Some(client_secret.into());
+        self
+    }
+
+    pub fn routing(mut self, routing: Routing) -> Self {
+        self.routing = Some(routing);
+        self
+    }
+
     pub fn build(self) -> Client {
         Client {
-            deepgram_api_key: self.deepgram_api_key.unwrap(),
-            clova_api_key: self.clova_api_key.unwrap(),
+            deepgram_api_key: self.deepgram_api_key,
+            clova_api_key: self.clova_api_key,
+            rtzr_client_id: self.rtzr_client_id,
+            rtzr_client_secret: self.rtzr_client_secret,
+            routing: self.routing.unwrap_or_default(),
         }
     }
 }
@@ -44,12 +62,26 @@ impl ClientBuilder {
 pub enum MultiClient {
     Deepgram(DeepgramClient),
     Clova(hypr_clova::recorded::Client),
+    Rtzr(hypr_rtzr::recorded::Client),
+}
+
+impl MultiClient {
+    pub fn provider(&self) -> Provider {
+        match self {
+            MultiClient::Deepgram(_) => Provider::Deepgram,
+            MultiClient::Clova(_) => Provider::Clova,
+            MultiClient::Rtzr(_) => Provider::Rtzr,
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct Client {
-    pub deepgram_api_key: String,
-    pub clova_api_key: String,
+    pub deepgram_api_key: Option<String>,
+    pub clova_api_key: Option<String>,
+    pub rtzr_client_id: Option<String>,
+    pub rtzr_client_secret: Option<String>,
+    pub routing: Routing,
 }
 
 impl Client {
@@ -57,25 +89,68 @@ impl Client {
         ClientBuilder::default()
     }
 
-    pub async fn for_language(&self, language: hypr_language::Language) -> MultiClient {
-        match language.iso639() {
-            hypr_language::ISO639::Ko => {
+    /// Providers to try for `language`, limited to the ones we have credentials for.
+    /// Whisper only does realtime here, so it is never picked.
+    pub fn providers(
+        &self,
+        language: &hypr_language::Language,
+    ) -> Result<Vec<Provider>, crate::Error> {
+        self.routing.route(language, |provider| match provider {
+            Provider::Deepgram => self.deepgram_api_key.is_some(),
+            Provider::Clova => self.clova_api_key.is_some(),
+            Provider::Rtzr => self.rtzr_client_id.is_some() && self.rtzr_client_secret.is_some(),
+            Provider::Whisper => false,
+        })
+    }
+
+    /// Builds a client for the first provider that works, falling back to the next one on failure.
+    pub async fn for_language(
+        &self,
+        language: hypr_language::Language,
+    ) -> Result<MultiClient, crate::Error> {
+        let mut last_error = None;
+
+        for provider in self.providers(&language)? {
+            match self.connect(provider, language.clone()) {
+                Ok(client) => return Ok(client),
+                Err(e) => last_error = Some(e),
+            }
+        }
+
+        // `providers` never returns an empty list.
+        Err(last_error.unwrap())
+    }
+
+    fn connect(
+        &self,
+        provider: Provider,
+        language: hypr_language::Language,
+    ) -> Result<MultiClient, crate::Error> {
+        match provider {
+            Provider::Clova => {
                 let clova = hypr_clova::recorded::Client::builder()
-                    .api_key(&self.clova_api_key)
+                    .api_key(self.clova_api_key.clone().unwrap_or_default())
                     .build();
-                MultiClient::Clova(clova)
+                Ok(MultiClient::Clova(clova))
             }
-            hypr_language::ISO639::Zh => {
+            Provider::Deepgram => {
                 let deepgram = DeepgramClient::builder()
-                    .api_key(&self.deepgram_api_key)
+                    .api_key(self.deepgram_api_key.clone().unwrap_or_default())
                     .keywords(vec!["Hyprnote".to_string()])
                     .language(language)
-                    .build()
-                    .unwrap();
-
-                MultiClient::Deepgram(deepgram)
+                    .build()?;
+                Ok(MultiClient::Deepgram(deepgram))
+            }
+            Provider::Rtzr => {
+                let rtzr = hypr_rtzr::recorded::Client::builder()
+                    .client_id(self.rtzr_client_id.clone().unwrap_or_default())
+                    .client_secret(self.rtzr_client_secret.clone().unwrap_or_default())
+                    .build()?;
+                Ok(MultiClient::Rtzr(rtzr))
             }
-            _ => panic!("Unsupported language: {:?}", language),
+            Provider::Whisper => Err(crate::Error::UnsupportedLanguage(
+                language.iso639().code().to_string(),
+            )),
         }
     }
 }
@@ -85,6 +160,7 @@ impl RecordedSpeechToText for MultiClient {
         match self {
             MultiClient::Deepgram(client) => client.transcribe(input).await,
             MultiClient::Clova(client) => client.transcribe(input).await,
+            MultiClient::Rtzr(client) => client.transcribe(input).await,
         }
     }
 }
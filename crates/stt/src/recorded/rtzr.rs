@@ -0,0 +1,21 @@
+use anyhow::Result;
+
+use super::{RecordedSpeech, RecordedSpeechToText};
+
+impl RecordedSpeechToText for hypr_rtzr::recorded::Client {
+    async fn transcribe(&self, input: RecordedSpeech) -> Result<String> {
+        let config = hypr_rtzr::recorded::TranscribeConfig {
+            use_itn: true,
+            keywords: vec!["하이퍼노트".to_string()],
+            ..Default::default()
+        };
+
+        let res = match input {
+            RecordedSpeech::File(file_path) => {
+                self.transcribe_local_file(file_path, &config).await?
+            }
+        };
+
+        Ok(res.into_iter().map(|u| u.msg).collect::<Vec<_>>().join(" "))
+    }
+}
@@ -0,0 +1,186 @@
+use std::ops::Range;
+use std::time::Duration;
+
+use crate::{FRAME_SIZE, SAMPLE_RATE};
+
+#[derive(Debug, Clone)]
+pub struct VadConfig {
+    /// Frames at or above this probability start speech.
+    pub threshold: f32,
+    /// Frames below this probability count as silence once speech has started.
+    pub neg_threshold: f32,
+    /// Speech shorter than this is dropped.
+    pub min_speech: Duration,
+    /// Silence shorter than this does not end speech.
+    pub min_silence: Duration,
+    /// Padding kept around each segment, so word onsets and tails are not clipped.
+    pub hangover: Duration,
+}
+
+impl Default for VadConfig {
+    fn default() -> Self {
+        // https://github.com/snakers4/silero-vad/blob/v5.1/src/silero_vad/utils_vad.py#L186
+        Self {
+            threshold: 0.5,
+            neg_threshold: 0.35,
+            min_speech: Duration::from_millis(250),
+            min_silence: Duration::from_millis(100),
+            hangover: Duration::from_millis(30),
+        }
+    }
+}
+
+pub fn duration_to_frames(duration: Duration) -> usize {
+    let samples = (duration.as_secs_f64() * SAMPLE_RATE as f64).ceil() as usize;
+    samples.div_ceil(FRAME_SIZE)
+}
+
+pub fn frames_to_samples(frames: usize) -> usize {
+    frames * FRAME_SIZE
+}
+
+/// Turns per-frame speech probabilities into speech segments, with hysteresis between
+/// `threshold` and `neg_threshold`.
+#[derive(Debug, Clone)]
+pub struct SpeechDetector {
+    config: VadConfig,
+    min_speech_frames: usize,
+    min_silence_frames: usize,
+    hangover_frames: usize,
+    frame: usize,
+    speech_start: Option<usize>,
+    silence_start: Option<usize>,
+    last_end: usize,
+}
+
+impl SpeechDetector {
+    pub fn new(config: VadConfig) -> Self {
+        Self {
+            min_speech_frames: duration_to_frames(config.min_speech),
+            min_silence_frames: duration_to_frames(config.min_silence).max(1),
+            hangover_frames: duration_to_frames(config.hangover),
+            config,
+            frame: 0,
+            speech_start: None,
+            silence_start: None,
+            last_end: 0,
+        }
+    }
+
+    pub fn config(&self) -> &VadConfig {
+        &self.config
+    }
+
+    /// Number of frames pushed so far.
+    pub fn frames(&self) -> usize {
+        self.frame
+    }
+
+    /// Whether speech has started, even if it may still turn out to be too short to count.
+    pub fn is_triggered(&self) -> bool {
+        self.speech_start.is_some()
+    }
+
+    /// Whether speech has been going on for at least `min_speech`.
+    pub fn is_speaking(&self) -> bool {
+        self.speech_start
+            .is_some_and(|start| self.frame - start >= self.min_speech_frames)
+    }
+
+    /// Feeds the probability of the next frame. Returns a segment, in frames, once it has ended.
+    pub fn push(&mut self, prob: f32) -> Option<Range<usize>> {
+        let index = self.frame;
+        self.frame += 1;
+
+        let Some(start) = self.speech_start else {
+            if prob >= self.config.threshold {
+                self.speech_start = Some(index);
+            }
+            return None;
+        };
+
+        if prob >= self.config.threshold {
+            self.silence_start = None;
+        } else if prob < self.config.neg_threshold {
+            let silence_start = *self.silence_start.get_or_insert(index);
+
+            if self.frame - silence_start >= self.min_silence_frames {
+                self.speech_start = None;
+                self.silence_start = None;
+                return self.close(start, silence_start);
+            }
+        }
+
+        None
+    }
+
+    /// Ends speech that is still going on, e.g. at the end of the input.
+    pub fn finish(&mut self) -> Option<Range<usize>> {
+        let start = self.speech_start.take()?;
+        let end = self.silence_start.take().unwrap_or(self.frame);
+        self.close(start, end)
+    }
+
+    pub fn reset(&mut self) {
+        self.frame = 0;
+        self.speech_start = None;
+        self.silence_start = None;
+        self.last_end = 0;
+    }
+
+    fn close(&mut self, start: usize, end: usize) -> Option<Range<usize>> {
+        if end - start < self.min_speech_frames {
+            return None;
+        }
+
+        let start = start
+            .saturating_sub(self.hangover_frames)
+            .max(self.last_end);
+        let end = (end + self.hangover_frames).min(self.frame);
+        self.last_end = end;
+
+        Some(start..end)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn detect(probs: &[f32]) -> Vec<Range<usize>> {
+        let mut detector = SpeechDetector::new(VadConfig::default());
+
+        let mut segments: Vec<_> = probs.iter().filter_map(|&p| detector.push(p)).collect();
+        segments.extend(detector.finish());
+        segments
+    }
+
+    #[test]
+    fn test_segment_boundaries() {
+        let mut probs = vec![0.0; 10];
+        probs.extend(vec![0.9; 20]);
+        probs.extend(vec![0.0; 10]);
+
+        // One frame of padding (30ms) on each side.
+        assert_eq!(detect(&probs), vec![9..31]);
+    }
+
+    #[test]
+    fn test_short_silence_does_not_split() {
+        let mut probs = vec![0.9; 20];
+        probs.extend(vec![0.0; 2]);
+        probs.extend(vec![0.4; 5]);
+        probs.extend(vec![0.9; 20]);
+
+        assert_eq!(detect(&probs), vec![0..47]);
+    }
+
+    #[test]
+    fn test_short_speech_is_dropped() {
+        let mut probs = vec![0.0; 10];
+        probs.extend(vec![0.9; 3]);
+        probs.extend(vec![0.0; 10]);
+
+        assert!(detect(&probs).is_empty());
+    }
+}
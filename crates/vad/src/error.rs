@@ -8,6 +8,8 @@ pub enum Error {
     ShapeError(#[from] ndarray::ShapeError),
     #[error("Invalid or missing output from model")]
     InvalidOutput,
+    #[error("Unsupported sample rate: {0}, expected 16000")]
+    UnsupportedSampleRate(u32),
 }
 
 impl Serialize for Error {
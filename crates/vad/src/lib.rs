@@ -1,5 +1,10 @@
+mod detector;
 mod error;
+mod stream;
+
+pub use detector::*;
 pub use error::*;
+pub use stream::*;
 
 use ndarray::{Array1, Array2, Array3, ArrayBase, Ix1, Ix3, OwnedRepr};
 use ort::session::{builder::GraphOptimizationLevel, Session};
@@ -13,6 +18,9 @@ const fn ms_to_samples(ms: usize) -> usize {
     (ms * SAMPLE_RATE as usize) / 1000
 }
 
+/// Number of samples in each frame the model is run on (30ms at 16kHz).
+pub const FRAME_SIZE: usize = ms_to_samples(30);
+
 #[derive(Debug)]
 pub struct Vad {
     session: Session,
@@ -78,31 +86,29 @@ impl Vad {
 
     /// For longer audio, this will process in 30ms chunks and return the maximum probability
     pub fn run(&mut self, audio_samples: &[f32]) -> Result<f32, crate::Error> {
-        if audio_samples.len() < ms_to_samples(30) {
+        if audio_samples.len() < FRAME_SIZE {
             return self.forward(audio_samples);
         }
 
-        let chunk_size = ms_to_samples(30);
-        let num_chunks = audio_samples.len() / chunk_size;
-
-        let mut max_prob = 0.0f32;
+        Ok(self
+            .run_frames(audio_samples)?
+            .into_iter()
+            .fold(0.0f32, f32::max))
+    }
 
-        for i in 0..num_chunks {
-            let start = i * chunk_size;
-            let end = (start + chunk_size).min(audio_samples.len());
-            let prob = self.forward(&audio_samples[start..end])?;
-            max_prob = max_prob.max(prob);
-        }
+    /// Speech probability of each 30ms frame, in order. A trailing partial frame is only
+    /// evaluated if it is at least half a frame long.
+    pub fn run_frames(&mut self, audio_samples: &[f32]) -> Result<Vec<f32>, crate::Error> {
+        let mut probs = Vec::with_capacity(audio_samples.len() / FRAME_SIZE + 1);
 
-        let remaining_start = num_chunks * chunk_size;
-        if remaining_start < audio_samples.len()
-            && audio_samples.len() - remaining_start >= (chunk_size / 2)
-        {
-            let prob = self.forward(&audio_samples[remaining_start..])?;
-            max_prob = max_prob.max(prob);
+        for frame in audio_samples.chunks(FRAME_SIZE) {
+            if frame.len() < FRAME_SIZE / 2 {
+                break;
+            }
+            probs.push(self.forward(frame)?);
         }
 
-        Ok(max_prob)
+        Ok(probs)
     }
 
     pub fn reset(&mut self) {
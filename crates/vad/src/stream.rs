@@ -0,0 +1,179 @@
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures_util::Stream;
+use kalosm_sound::AsyncSource;
+
+use crate::{frames_to_samples, SpeechDetector, Vad, VadConfig, FRAME_SIZE, SAMPLE_RATE};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpeechSegment {
+    /// Offset of the first sample from the start of the source.
+    pub start_ms: u64,
+    pub end_ms: u64,
+    pub samples: Vec<f32>,
+}
+
+pub trait VadExt: AsyncSource + Sized {
+    /// Splits a 16kHz mono source into speech segments, dropping the silence between them.
+    fn speech_segments(self, config: VadConfig) -> Result<SpeechSegmentStream<Self>, crate::Error>
+    where
+        Self: Unpin,
+    {
+        SpeechSegmentStream::new(self, config)
+    }
+}
+
+impl<T: AsyncSource> VadExt for T {}
+
+pub struct SpeechSegmentStream<S: AsyncSource + Unpin> {
+    source: S,
+    vad: Vad,
+    detector: SpeechDetector,
+    frame: Vec<f32>,
+    // Audio not yet known to be silence, starting at `history_start` (in frames).
+    history: Vec<f32>,
+    history_start: usize,
+    hangover_frames: usize,
+    finished: bool,
+}
+
+impl<S: AsyncSource + Unpin> SpeechSegmentStream<S> {
+    pub fn new(source: S, config: VadConfig) -> Result<Self, crate::Error> {
+        let sample_rate = source.sample_rate();
+        if sample_rate != SAMPLE_RATE as u32 {
+            return Err(crate::Error::UnsupportedSampleRate(sample_rate));
+        }
+
+        Ok(Self {
+            source,
+            vad: Vad::new()?,
+            hangover_frames: crate::duration_to_frames(config.hangover),
+            detector: SpeechDetector::new(config),
+            frame: Vec::with_capacity(FRAME_SIZE),
+            history: Vec::new(),
+            history_start: 0,
+            finished: false,
+        })
+    }
+
+    /// Whether the source is currently in the middle of speech.
+    pub fn is_speaking(&self) -> bool {
+        self.detector.is_speaking()
+    }
+
+    fn push_frame(&mut self) -> Option<SpeechSegment> {
+        let prob = self.vad.forward(&self.frame).unwrap_or_else(|e| {
+            tracing::warn!("vad_forward_error: {:?}", e);
+            0.0
+        });
+
+        self.history.append(&mut self.frame);
+        let segment = self.detector.push(prob).map(|r| self.take_segment(r));
+
+        // Outside of speech, only keep what the next segment's hangover could reach back into.
+        if !self.detector.is_triggered() {
+            let keep = frames_to_samples(self.hangover_frames);
+            if self.history.len() > keep {
+                let drop = self.history.len() - keep;
+                self.history.drain(..drop);
+                self.history_start += drop / FRAME_SIZE;
+            }
+        }
+
+        segment
+    }
+
+    fn take_segment(&mut self, frames: std::ops::Range<usize>) -> SpeechSegment {
+        let start = frames_to_samples(frames.start.saturating_sub(self.history_start));
+        let end = frames_to_samples(frames.end - self.history_start).min(self.history.len());
+
+        let samples = self.history[start.min(end)..end].to_vec();
+        self.history.drain(..end);
+        self.history_start = frames.end;
+
+        SpeechSegment {
+            start_ms: frames_to_ms(frames.start),
+            end_ms: frames_to_ms(frames.end),
+            samples,
+        }
+    }
+}
+
+fn frames_to_ms(frames: usize) -> u64 {
+    (frames_to_samples(frames) as u64 * 1000) / SAMPLE_RATE as u64
+}
+
+impl<S: AsyncSource + Unpin> Stream for SpeechSegmentStream<S> {
+    type Item = SpeechSegment;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        if this.finished {
+            return Poll::Ready(None);
+        }
+
+        loop {
+            let next = {
+                let mut stream = std::pin::pin!(this.source.as_stream());
+                stream.as_mut().poll_next(cx)
+            };
+
+            match next {
+                Poll::Ready(Some(sample)) => {
+                    this.frame.push(sample);
+
+                    if this.frame.len() == FRAME_SIZE {
+                        if let Some(segment) = this.push_frame() {
+                            return Poll::Ready(Some(segment));
+                        }
+                    }
+                }
+                Poll::Ready(None) => {
+                    this.finished = true;
+
+                    this.history.append(&mut this.frame);
+                    let segment = this.detector.finish().map(|r| this.take_segment(r));
+                    return Poll::Ready(segment);
+                }
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_util::StreamExt;
+
+    #[tokio::test]
+    async fn test_speech_segments_english_1() {
+        let audio = rodio::Decoder::new_wav(std::io::BufReader::new(
+            std::fs::File::open(hypr_data::english_1::AUDIO_PATH).unwrap(),
+        ))
+        .unwrap();
+
+        let segments: Vec<SpeechSegment> = audio
+            .speech_segments(VadConfig::default())
+            .unwrap()
+            .collect()
+            .await;
+
+        assert!(!segments.is_empty());
+
+        let words: Vec<serde_json::Value> =
+            serde_json::from_str(hypr_data::english_1::TRANSCRIPTION_JSON).unwrap();
+        let first_word_ms = words[0]["start"].as_u64().unwrap() as i64;
+        assert!((segments[0].start_ms as i64 - first_word_ms).abs() < 300);
+
+        for pair in segments.windows(2) {
+            assert!(pair[0].end_ms <= pair[1].start_ms);
+        }
+
+        for segment in &segments {
+            let expected = (segment.end_ms - segment.start_ms) as usize * 16;
+            assert!(segment.samples.len().abs_diff(expected) <= FRAME_SIZE);
+        }
+    }
+}
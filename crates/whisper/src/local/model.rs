@@ -4,10 +4,12 @@ use lazy_static::lazy_static;
 use regex::Regex;
 
 use whisper_rs::{
-    FullParams, SamplingStrategy, WhisperContext, WhisperContextParameters, WhisperState,
+    DtwMode, FullParams, SamplingStrategy, WhisperContext, WhisperContextParameters, WhisperState,
     WhisperToken,
 };
 
+pub use whisper_rs::DtwModelPreset;
+
 lazy_static! {
     static ref TRAILING_DOTS: Regex = Regex::new(r"\.{2,}$").unwrap();
 }
@@ -18,6 +20,8 @@ pub struct WhisperBuilder {
     language: Option<crate::Language>,
     static_prompt: Option<String>,
     dynamic_prompt: Option<String>,
+    dtw_preset: Option<DtwModelPreset>,
+    n_threads: Option<i32>,
 }
 
 impl WhisperBuilder {
@@ -41,12 +45,27 @@ impl WhisperBuilder {
         self
     }
 
+    /// Aligns token timestamps with DTW on the model's alignment heads. The preset must match the model.
+    pub fn dtw_preset(mut self, dtw_preset: DtwModelPreset) -> Self {
+        self.dtw_preset = Some(dtw_preset);
+        self
+    }
+
+    /// Defaults to 1, which keeps realtime transcription from competing with the rest of the app.
+    pub fn n_threads(mut self, n_threads: i32) -> Self {
+        self.n_threads = Some(n_threads);
+        self
+    }
+
     pub fn build(self) -> Whisper {
         unsafe { Self::suppress_log() };
 
         let context_param = {
             let mut p = WhisperContextParameters::default();
-            p.dtw_parameters.mode = whisper_rs::DtwMode::None;
+            p.dtw_parameters.mode = match self.dtw_preset {
+                Some(model_preset) => DtwMode::ModelPreset { model_preset },
+                None => DtwMode::None,
+            };
             p
         };
 
@@ -62,6 +81,7 @@ impl WhisperBuilder {
             language,
             static_prompt: self.static_prompt.unwrap_or_default(),
             dynamic_prompt: self.dynamic_prompt.unwrap_or_default(),
+            n_threads: self.n_threads.unwrap_or(1),
             state,
             eot,
         }
@@ -82,6 +102,7 @@ pub struct Whisper {
     language: crate::Language,
     static_prompt: String,
     dynamic_prompt: String,
+    n_threads: i32,
     state: WhisperState,
     eot: WhisperToken,
 }
@@ -92,6 +113,24 @@ impl Whisper {
     }
 
     pub fn transcribe(&mut self, audio: &[f32]) -> Result<Vec<Segment>, super::Error> {
+        let segments = self.run(audio)?;
+
+        self.dynamic_prompt = segments
+            .iter()
+            .map(|s| s.text())
+            .collect::<Vec<&str>>()
+            .join(" ");
+
+        Ok(segments)
+    }
+
+    /// Transcribes a hypothesis for audio that will be transcribed again, e.g. an interim result.
+    /// Unlike [`Whisper::transcribe`], the text is not carried into the prompt of the next call.
+    pub fn transcribe_partial(&mut self, audio: &[f32]) -> Result<Vec<Segment>, super::Error> {
+        self.run(audio)
+    }
+
+    fn run(&mut self, audio: &[f32]) -> Result<Vec<Segment>, super::Error> {
         let params = {
             let mut p = FullParams::new(SamplingStrategy::Greedy { best_of: 1 });
 
@@ -105,9 +144,9 @@ impl Whisper {
             p.set_language(Some(self.language.as_ref()));
             p.set_initial_prompt(&initial_prompt);
 
-            p.set_n_threads(1);
+            p.set_n_threads(self.n_threads);
             p.set_detect_language(false);
-            p.set_token_timestamps(false);
+            p.set_token_timestamps(true);
             p.set_single_segment(true);
             p.set_suppress_blank(true);
             p.set_suppress_nst(true);
@@ -130,23 +169,20 @@ impl Whisper {
                 self.state.full_get_segment_t1(i)?,
             );
             let confidence = self.calculate_segment_confidence(i);
+            let words = group_words(&self.segment_tokens(i)?);
 
+            // whisper.cpp timestamps are in centiseconds.
             let mut segment = Segment {
                 text,
-                start: start as f32 / 1000.0,
-                end: end as f32 / 1000.0,
+                start: start as f32 / 100.0,
+                end: end as f32 / 100.0,
                 confidence,
+                words,
             };
             segment.trim();
             segments.push(segment);
         }
 
-        self.dynamic_prompt = segments
-            .iter()
-            .map(|s| s.text())
-            .collect::<Vec<&str>>()
-            .join(" ");
-
         Ok(segments)
     }
 
@@ -175,9 +211,7 @@ impl Whisper {
                 .full_get_token_prob(segment_idx, j)
                 .unwrap_or(0.0);
 
-            let token_confidence = token_p.powi(3);
-
-            total_confidence += token_confidence;
+            total_confidence += token_confidence(token_p);
             valid_tokens += 1;
         }
 
@@ -187,6 +221,131 @@ impl Whisper {
 
         total_confidence / valid_tokens as f32
     }
+
+    fn segment_tokens(&self, segment_idx: i32) -> Result<Vec<Token>, super::Error> {
+        let n_tokens = self.state.full_n_tokens(segment_idx)?;
+
+        let mut tokens = Vec::with_capacity(n_tokens as usize);
+        for j in 0..n_tokens {
+            if self.state.full_get_token_id(segment_idx, j)? >= self.eot {
+                continue;
+            }
+
+            let data = self.state.full_get_token_data(segment_idx, j)?;
+            tokens.push(Token {
+                bytes: self.state.full_get_token_bytes(segment_idx, j)?,
+                t0: data.t0,
+                t1: data.t1,
+                t_dtw: data.t_dtw,
+                p: data.p,
+            });
+        }
+
+        Ok(tokens)
+    }
+}
+
+fn token_confidence(p: f32) -> f32 {
+    p.powi(3)
+}
+
+// Timestamps are in centiseconds. `t_dtw` is -1 unless DTW is enabled.
+#[derive(Debug, Default, Clone)]
+struct Token {
+    bytes: Vec<u8>,
+    t0: i64,
+    t1: i64,
+    t_dtw: i64,
+    p: f32,
+}
+
+/// Merges BPE tokens into words. A token starting with a space opens a new word, and so does
+/// every token after a complete CJK character, since those scripts have no spaces. Punctuation
+/// stays with the word before it.
+fn group_words(tokens: &[Token]) -> Vec<Word> {
+    let mut words = Vec::new();
+    let mut current: Vec<&Token> = Vec::new();
+
+    for token in tokens.iter().filter(|t| !t.bytes.is_empty()) {
+        let split = token.bytes[0] == b' ' || (ends_with_cjk(&current) && !is_punctuation(token));
+        if !current.is_empty() && split {
+            words.push(Word::from_tokens(&current));
+            current.clear();
+        }
+        current.push(token);
+    }
+
+    if !current.is_empty() {
+        words.push(Word::from_tokens(&current));
+    }
+
+    words.retain(|w| !w.text.is_empty());
+    words
+}
+
+fn ends_with_cjk(tokens: &[&Token]) -> bool {
+    let bytes: Vec<u8> = tokens
+        .iter()
+        .flat_map(|t| t.bytes.iter().copied())
+        .collect();
+
+    std::str::from_utf8(&bytes)
+        .ok()
+        .and_then(|s| s.chars().last())
+        .is_some_and(is_cjk)
+}
+
+fn is_punctuation(token: &Token) -> bool {
+    std::str::from_utf8(&token.bytes)
+        .ok()
+        .and_then(|s| s.chars().next())
+        .is_some_and(|c| !c.is_alphanumeric())
+}
+
+fn is_cjk(c: char) -> bool {
+    matches!(c as u32,
+        0x3040..=0x30FF // Hiragana, Katakana
+        | 0x3400..=0x4DBF // CJK Extension A
+        | 0x4E00..=0x9FFF // CJK Unified Ideographs
+        | 0xF900..=0xFAFF // CJK Compatibility Ideographs
+        | 0x20000..=0x2FA1F // CJK Extensions B-F
+    )
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct Word {
+    pub text: String,
+    pub start: f32,
+    pub end: f32,
+    pub confidence: f32,
+}
+
+impl Word {
+    fn from_tokens(tokens: &[&Token]) -> Self {
+        let bytes: Vec<u8> = tokens
+            .iter()
+            .flat_map(|t| t.bytes.iter().copied())
+            .collect();
+
+        let first = tokens[0];
+        let last = tokens[tokens.len() - 1];
+        let start = if first.t_dtw >= 0 {
+            first.t_dtw
+        } else {
+            first.t0
+        };
+        let end = last.t1.max(start);
+
+        let confidence =
+            tokens.iter().map(|t| token_confidence(t.p)).sum::<f32>() / tokens.len() as f32;
+
+        Self {
+            text: String::from_utf8_lossy(&bytes).trim().to_string(),
+            start: start as f32 / 100.0,
+            end: end as f32 / 100.0,
+            confidence,
+        }
+    }
 }
 
 // https://github.com/floneum/floneum/blob/52967ae/models/rwhisper/src/lib.rs#L116
@@ -196,6 +355,7 @@ pub struct Segment {
     pub start: f32,
     pub end: f32,
     pub confidence: f32,
+    pub words: Vec<Word>,
 }
 
 impl Segment {
@@ -219,8 +379,30 @@ impl Segment {
         self.confidence
     }
 
+    pub fn words(&self) -> &[Word] {
+        &self.words
+    }
+
+    /// Shifts the segment and its words by `secs`, e.g. from chunk-relative to session time.
+    pub fn offset(&mut self, secs: f32) {
+        self.start += secs;
+        self.end += secs;
+
+        for word in &mut self.words {
+            word.start += secs;
+            word.end += secs;
+        }
+    }
+
     pub fn trim(&mut self) {
         self.text = TRAILING_DOTS.replace(&self.text, "").to_string();
+
+        if let Some(last) = self.words.last_mut() {
+            last.text = TRAILING_DOTS.replace(&last.text, "").to_string();
+            if last.text.is_empty() {
+                self.words.pop();
+            }
+        }
     }
 }
 
@@ -259,6 +441,72 @@ mod tests {
         }
     }
 
+    fn token(text: &str, t0: i64, t1: i64) -> Token {
+        Token {
+            bytes: text.as_bytes().to_vec(),
+            t0,
+            t1,
+            t_dtw: -1,
+            p: 1.0,
+        }
+    }
+
+    #[test]
+    fn test_group_words() {
+        let words = group_words(&[
+            token(" Hel", 0, 20),
+            token("lo", 20, 40),
+            token(",", 40, 45),
+            token(" world", 50, 90),
+        ]);
+
+        let texts: Vec<&str> = words.iter().map(|w| w.text.as_str()).collect();
+        assert_eq!(texts, vec!["Hello,", "world"]);
+        assert_eq!((words[0].start, words[0].end), (0.0, 0.45));
+        assert_eq!((words[1].start, words[1].end), (0.5, 0.9));
+    }
+
+    #[test]
+    fn test_group_words_cjk() {
+        let bytes = "你好".as_bytes();
+
+        // "你" split across two tokens, as byte-level BPE does.
+        let words = group_words(&[
+            Token {
+                bytes: bytes[..2].to_vec(),
+                ..token("", 0, 10)
+            },
+            Token {
+                bytes: bytes[2..3].to_vec(),
+                ..token("", 10, 20)
+            },
+            token("好", 20, 30),
+            token("。", 30, 35),
+        ]);
+
+        let texts: Vec<&str> = words.iter().map(|w| w.text.as_str()).collect();
+        assert_eq!(texts, vec!["你", "好。"]);
+        assert_eq!(words[0].end, 0.2);
+    }
+
+    #[test]
+    fn test_group_words_dtw() {
+        let words = group_words(&[
+            Token {
+                t_dtw: 15,
+                ..token(" Hi", 0, 30)
+            },
+            Token {
+                p: 0.5,
+                ..token(" there", 30, 60)
+            },
+        ]);
+
+        assert_eq!(words[0].start, 0.15);
+        assert_eq!(words[1].start, 0.3);
+        assert_eq!(words[1].confidence, 0.125);
+    }
+
     #[test]
     fn test_whisper() {
         let mut whisper = Whisper::builder()
@@ -272,6 +520,11 @@ mod tests {
 
         let segments = whisper.transcribe(&audio).unwrap();
         assert!(segments.len() > 0);
+
+        // Segment timestamps are in seconds, so the last one ends close to the end of the audio.
+        let duration = audio.len() as f32 / 16000.0;
+        let end = segments.last().unwrap().end;
+        assert!(end > duration / 2.0 && end <= duration + 1.0);
     }
 
     #[tokio::test]
@@ -5,6 +5,7 @@ use std::{
 
 use cpal::FromSample;
 use futures_util::{Stream, StreamExt};
+use hypr_chunker::TimedSource;
 use rodio::Source;
 
 use super::{Segment, Whisper};
@@ -22,7 +23,7 @@ pub trait TranscribeChunkedAudioStreamExt<S>: Sized {
 impl<S> TranscribeChunkedAudioStreamExt<S> for S
 where
     S: Stream + std::marker::Unpin + Send + 'static,
-    <S as Stream>::Item: Source + Send + 'static,
+    <S as Stream>::Item: TimedSource + Send + 'static,
     <<S as Stream>::Item as Iterator>::Item: rodio::Sample,
     f32: FromSample<<<S as Stream>::Item as Iterator>::Item>,
 {
@@ -38,7 +39,7 @@ where
 impl<S> Stream for ChunkedTranscriptionTask<S>
 where
     S: Stream + std::marker::Unpin + Send + 'static,
-    <S as Stream>::Item: Source + Send + 'static,
+    <S as Stream>::Item: TimedSource + Send + 'static,
     <<S as Stream>::Item as Iterator>::Item: rodio::Sample,
     f32: FromSample<<<S as Stream>::Item as Iterator>::Item>,
 {
@@ -62,6 +63,8 @@ where
 
             match this.stream.poll_next_unpin(cx) {
                 Poll::Ready(Some(source)) => {
+                    // Segments are relative to the chunk, so move them to where it starts in the session.
+                    let offset = source.start().as_secs_f32();
                     let samples: Vec<f32> = source.convert_samples().collect();
                     if !samples.is_empty() {
                         match this.whisper.transcribe(&samples) {
@@ -69,7 +72,8 @@ where
                                 tracing::error!("{:?}", e);
                                 return Poll::Pending;
                             }
-                            Ok(segments) => {
+                            Ok(mut segments) => {
+                                segments.iter_mut().for_each(|s| s.offset(offset));
                                 this.current_segment_task =
                                     Some(Box::pin(futures_util::stream::iter(segments)));
                             }
@@ -1,27 +1,40 @@
 use axum::extract::ws::{Message, WebSocket};
 
 use futures_util::{stream::SplitStream, Stream, StreamExt};
-use hypr_listener_interface::ListenInputChunk;
+use hypr_audio_utils::{OpusDecoder, OpusError};
+use hypr_listener_interface::{AudioFormat, ListenInputChunk};
 
 pub struct WebSocketAudioSource {
     receiver: Option<SplitStream<WebSocket>>,
     sample_rate: u32,
+    decoder: Option<OpusDecoder>,
 }
 
 impl WebSocketAudioSource {
-    pub fn new(receiver: SplitStream<WebSocket>, sample_rate: u32) -> Self {
-        Self {
+    pub fn new(
+        receiver: SplitStream<WebSocket>,
+        sample_rate: u32,
+        audio_format: AudioFormat,
+    ) -> Result<Self, OpusError> {
+        let decoder = match audio_format {
+            AudioFormat::Opus => Some(OpusDecoder::new(sample_rate)?),
+            AudioFormat::Json | AudioFormat::Pcm => None,
+        };
+
+        Ok(Self {
             receiver: Some(receiver),
             sample_rate,
-        }
+            decoder,
+        })
     }
 }
 
 impl kalosm_sound::AsyncSource for WebSocketAudioSource {
     fn as_stream(&mut self) -> impl Stream<Item = f32> + '_ {
         let receiver = self.receiver.as_mut().unwrap();
+        let decoder = self.decoder.as_mut();
 
-        futures_util::stream::unfold(receiver, |receiver| async move {
+        futures_util::stream::unfold((receiver, decoder), |(receiver, decoder)| async move {
             let item = receiver.next().await;
 
             match item {
@@ -33,23 +46,26 @@ impl kalosm_sound::AsyncSource for WebSocketAudioSource {
                             if data.is_empty() {
                                 None
                             } else {
-                                let samples: Vec<f32> = data
-                                    .chunks_exact(2)
-                                    .map(|chunk| {
-                                        let sample = i16::from_le_bytes([chunk[0], chunk[1]]);
-                                        sample as f32 / 32767.0
-                                    })
-                                    .collect();
-
-                                Some((samples, receiver))
+                                Some((pcm_to_samples(&data), (receiver, decoder)))
                             }
                         }
                         ListenInputChunk::End => None,
                     }
                 }
+                // Binary frames carry the negotiated `AudioFormat`, but the stream still ends with a JSON `End`.
+                Some(Ok(Message::Binary(data))) => {
+                    let samples = match decoder {
+                        Some(ref mut decoder) => decoder.decode(&data).unwrap_or_else(|e| {
+                            tracing::warn!("opus_decode_error: {:?}", e);
+                            Vec::new()
+                        }),
+                        None => pcm_to_samples(&data),
+                    };
+                    Some((samples, (receiver, decoder)))
+                }
                 Some(Ok(Message::Close(_))) => None,
                 Some(Err(_)) => None,
-                _ => Some((Vec::new(), receiver)),
+                _ => Some((Vec::new(), (receiver, decoder))),
             }
         })
         .flat_map(futures_util::stream::iter)
@@ -59,3 +75,12 @@ impl kalosm_sound::AsyncSource for WebSocketAudioSource {
         self.sample_rate
     }
 }
+
+fn pcm_to_samples(data: &[u8]) -> Vec<f32> {
+    data.chunks_exact(2)
+        .map(|chunk| {
+            let sample = i16::from_le_bytes([chunk[0], chunk[1]]);
+            sample as f32 / 32767.0
+        })
+        .collect()
+}
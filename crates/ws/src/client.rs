@@ -4,7 +4,12 @@ use backon::{ConstantBuilder, Retryable};
 use futures_util::{SinkExt, Stream, StreamExt};
 use tokio_tungstenite::{connect_async, tungstenite::client::IntoClientRequest};
 
-pub use tokio_tungstenite::tungstenite::{protocol::Message, ClientRequestBuilder};
+pub use tokio_tungstenite::tungstenite::{
+    handshake::client::Response, protocol::Message, ClientRequestBuilder,
+};
+
+type WsStream =
+    tokio_tungstenite::WebSocketStream<tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>>;
 
 pub trait WebSocketIO: Send + 'static {
     type Input: Send + Default;
@@ -26,9 +31,14 @@ impl WebSocketClient {
 
     pub async fn from_audio<T: WebSocketIO>(
         &self,
-        mut audio_stream: impl Stream<Item = bytes::Bytes> + Send + Unpin + 'static,
+        audio_stream: impl Stream<Item = bytes::Bytes> + Send + Unpin + 'static,
     ) -> Result<impl Stream<Item = T::Output>, crate::Error> {
-        let ws_stream = (|| self.try_connect(self.request.clone()))
+        Ok(self.connect().await?.from_audio::<T>(audio_stream))
+    }
+
+    /// Connects without sending anything yet, so the handshake response can be looked at first.
+    pub async fn connect(&self) -> Result<WebSocketConnection, crate::Error> {
+        (|| self.connect_once())
             .retry(
                 ConstantBuilder::default()
                     .with_max_times(20)
@@ -36,12 +46,53 @@ impl WebSocketClient {
             )
             .when(|e| {
                 tracing::error!("ws_connect_failed: {:?}", e);
-                true
+                e.is_retryable()
             })
             .sleep(tokio::time::sleep)
-            .await?;
+            .await
+    }
+
+    /// Like [`Self::connect`], but makes a single attempt, for callers that retry on their own.
+    pub async fn connect_once(&self) -> Result<WebSocketConnection, crate::Error> {
+        let (ws_stream, response) = self.try_connect(self.request.clone()).await?;
+
+        Ok(WebSocketConnection {
+            ws_stream,
+            response,
+        })
+    }
+
+    async fn try_connect(
+        &self,
+        req: ClientRequestBuilder,
+    ) -> Result<(WsStream, Response), crate::Error> {
+        let req = req.into_client_request().unwrap();
+
+        tracing::info!("connect_async: {:?}", req.uri());
+
+        let (ws_stream, response) =
+            tokio::time::timeout(std::time::Duration::from_secs(8), connect_async(req)).await??;
+
+        Ok((ws_stream, response))
+    }
+}
 
-        let (mut ws_sender, mut ws_receiver) = ws_stream.split();
+pub struct WebSocketConnection {
+    ws_stream: WsStream,
+    response: Response,
+}
+
+impl WebSocketConnection {
+    /// The server's answer to the upgrade request.
+    pub fn response(&self) -> &Response {
+        &self.response
+    }
+
+    pub fn from_audio<T: WebSocketIO>(
+        self,
+        mut audio_stream: impl Stream<Item = bytes::Bytes> + Send + Unpin + 'static,
+    ) -> impl Stream<Item = T::Output> {
+        let (mut ws_sender, mut ws_receiver) = self.ws_stream.split();
 
         let _send_task = tokio::spawn(async move {
             while let Some(data) = audio_stream.next().await {
@@ -84,25 +135,6 @@ impl WebSocketClient {
             }
         };
 
-        Ok(output_stream)
-    }
-
-    async fn try_connect(
-        &self,
-        req: ClientRequestBuilder,
-    ) -> Result<
-        tokio_tungstenite::WebSocketStream<
-            tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>,
-        >,
-        crate::Error,
-    > {
-        let req = req.into_client_request().unwrap();
-
-        tracing::info!("connect_async: {:?}", req.uri());
-
-        let (ws_stream, _) =
-            tokio::time::timeout(std::time::Duration::from_secs(8), connect_async(req)).await??;
-
-        Ok(ws_stream)
+        output_stream
     }
 }
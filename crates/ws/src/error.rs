@@ -7,3 +7,22 @@ pub enum Error {
     #[error("timeout error")]
     Timeout(#[from] tokio::time::error::Elapsed),
 }
+
+impl Error {
+    /// Whether connecting again could succeed. A handshake the server rejected for good (like an
+    /// auth failure) or a malformed URL won't.
+    pub fn is_retryable(&self) -> bool {
+        use tokio_tungstenite::tungstenite::{http::StatusCode, Error as WsError};
+
+        match self {
+            Error::Connection(WsError::Http(response)) => {
+                let status = response.status();
+                status.is_server_error()
+                    || status == StatusCode::REQUEST_TIMEOUT
+                    || status == StatusCode::TOO_MANY_REQUESTS
+            }
+            Error::Connection(WsError::Url(_)) => false,
+            _ => true,
+        }
+    }
+}
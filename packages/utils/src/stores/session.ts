@@ -69,14 +69,8 @@ export const createSessionStore = (session: Session) => {
       });
     },
     persistSession: async (session?: Session, force?: boolean) => {
-      const { session: { id } } = get();
-      const sessionFromDB = await dbCommands.getSession({ id });
-
-      // TODO: this is temp solution.
-      const item: Session = {
-        ...(session ?? get().session),
-        words: sessionFromDB?.words ?? [],
-      };
+      // Words are not saved with the session, so a stale copy here can't overwrite the transcript.
+      const item: Session = session ?? get().session;
 
       const fn = force
         ? dbCommands.upsertSession
@@ -9,6 +9,7 @@ const COMMANDS: &[&str] = &[
     "visit_session",
     "upsert_session",
     "list_sessions",
+    "search_sessions",
     "delete_session",
     "get_session",
     "set_session_event",
@@ -16,8 +17,10 @@ const COMMANDS: &[&str] = &[
     "session_remove_participant",
     "session_list_participants",
     "session_get_event",
+    "export_session",
     "get_words_onboarding",
     "get_words",
+    "replace_words",
     // template
     "list_templates",
     "upsert_template",
@@ -49,6 +52,8 @@ const COMMANDS: &[&str] = &[
     "list_session_tags",
     "assign_tag_to_session",
     "unassign_tag_from_session",
+    "list_voiceprints",
+    "delete_voiceprint",
 ];
 
 fn main() {
@@ -46,6 +46,9 @@ async onboardingSessionId() : Promise<string> {
 async listSessions(filter: ListSessionFilter | null) : Promise<Session[]> {
     return await TAURI_INVOKE("plugin:db|list_sessions", { filter });
 },
+async searchSessions(userId: string, query: string, limit: number | null) : Promise<SessionSearchResult[]> {
+    return await TAURI_INVOKE("plugin:db|search_sessions", { userId, query, limit });
+},
 async deleteSession(id: string) : Promise<null> {
     return await TAURI_INVOKE("plugin:db|delete_session", { id });
 },
@@ -67,9 +70,15 @@ async sessionListParticipants(sessionId: string) : Promise<Human[]> {
 async sessionGetEvent(sessionId: string) : Promise<Event | null> {
     return await TAURI_INVOKE("plugin:db|session_get_event", { sessionId });
 },
+async exportSession(sessionId: string, format: ExportFormat) : Promise<string> {
+    return await TAURI_INVOKE("plugin:db|export_session", { sessionId, format });
+},
 async getWords(sessionId: string) : Promise<Word[]> {
     return await TAURI_INVOKE("plugin:db|get_words", { sessionId });
 },
+async replaceWords(sessionId: string, words: Word[]) : Promise<null> {
+    return await TAURI_INVOKE("plugin:db|replace_words", { sessionId, words });
+},
 async getWordsOnboarding() : Promise<Word[]> {
     return await TAURI_INVOKE("plugin:db|get_words_onboarding");
 },
@@ -88,6 +97,12 @@ async upsertHuman(human: Human) : Promise<Human> {
 async listHumans(filter: ListHumanFilter | null) : Promise<Human[]> {
     return await TAURI_INVOKE("plugin:db|list_humans", { filter });
 },
+async listVoiceprints(humanId: string | null) : Promise<Voiceprint[]> {
+    return await TAURI_INVOKE("plugin:db|list_voiceprints", { humanId });
+},
+async deleteVoiceprint(id: string) : Promise<null> {
+    return await TAURI_INVOKE("plugin:db|delete_voiceprint", { id });
+},
 async getOrganization(id: string) : Promise<Organization | null> {
     return await TAURI_INVOKE("plugin:db|get_organization", { id });
 },
@@ -145,22 +160,35 @@ export type ChatMessage = { id: string; group_id: string; created_at: string; ro
 export type ChatMessageRole = "User" | "Assistant"
 export type Config = { id: string; user_id: string; general: ConfigGeneral; notification: ConfigNotification; ai: ConfigAI }
 export type ConfigAI = { api_base: string | null; api_key: string | null }
-export type ConfigGeneral = { autostart: boolean; display_language: string; jargons: string[]; telemetry_consent: boolean; save_recordings: boolean | null }
+export type ConfigGeneral = { autostart: boolean; display_language: string; jargons: string[]; telemetry_consent: boolean; save_recordings: boolean | null; mic_device: string | null; echo_cancellation: boolean | null }
 export type ConfigNotification = { before: boolean; auto: boolean; ignoredPlatforms: string[] | null }
 export type Event = { id: string; user_id: string; tracking_id: string; calendar_id: string | null; name: string; note: string; start_date: string; end_date: string; google_event_url: string | null }
+export type ExportFormat = "markdown" | "text" | "srt" | "vtt" | "json"
 export type GetSessionFilter = { id: string } | { calendarEventId: string } | { tagId: string }
 export type Human = { id: string; organization_id: string | null; is_user: boolean; full_name: string | null; email: string | null; job_title: string | null; linkedin_username: string | null }
 export type ListEventFilter = ({ user_id: string; limit: number | null }) & ({ type: "simple" } | { type: "search"; query: string } | { type: "dateRange"; start: string; end: string } | { type: "not-assigned-past" })
 export type ListHumanFilter = { search: [number, string] }
 export type ListOrganizationFilter = { search: [number, string] }
-export type ListSessionFilter = ({ user_id: string; limit: number | null }) & ({ type: "search"; query: string } | { type: "recentlyVisited" } | { type: "dateRange"; start: string; end: string })
+export type ListSessionFilter = ({ user_id: string; limit: number | null }) & ({ type: "search"; query: string } | { type: "fullText"; query: string } | { type: "recentlyVisited" } | { type: "dateRange"; start: string; end: string })
 export type Organization = { id: string; name: string; description: string | null }
 export type Platform = "Apple" | "Google" | "Outlook"
+export type SearchField = "title" | "rawMemo" | "enhancedMemo" | "transcript"
+export type SearchSnippet = { field: SearchField; parts: SnippetPart[] }
 export type Session = { id: string; created_at: string; visited_at: string; user_id: string; calendar_event_id: string | null; title: string; raw_memo_html: string; enhanced_memo_html: string | null; words: Word[] }
+export type SessionSearchResult = { session: Session; 
+/**
+ * Best match in each field that matched, best field first.
+ */
+snippets: SearchSnippet[] }
+export type SnippetPart = { text: string; highlighted: boolean }
 export type SpeakerIdentity = { type: "unassigned"; value: { index: number } } | { type: "assigned"; value: { id: string; label: string } }
 export type Tag = { id: string; name: string }
 export type Template = { id: string; user_id: string; title: string; description: string; sections: TemplateSection[]; tags: string[] }
 export type TemplateSection = { title: string; description: string }
+/**
+ * Speaker embedding, compared by cosine similarity.
+ */
+export type Voiceprint = { id: string; human_id: string; created_at: string; embedding: number[] }
 export type Word = { text: string; speaker: SpeakerIdentity | null; confidence: number | null; start_ms: number | null; end_ms: number | null }
 
 /** tauri-specta globals **/
@@ -53,6 +53,27 @@ pub async fn get_words(
     Ok(v)
 }
 
+#[tauri::command]
+#[specta::specta]
+#[tracing::instrument(skip(state, words))]
+pub async fn replace_words(
+    state: tauri::State<'_, crate::ManagedState>,
+    session_id: String,
+    words: Vec<hypr_listener_interface::Word>,
+) -> Result<(), String> {
+    let guard = state.lock().await;
+
+    let db = guard
+        .db
+        .as_ref()
+        .ok_or(crate::Error::NoneDatabase)
+        .map_err(|e| e.to_string())?;
+
+    db.replace_words(session_id, words)
+        .await
+        .map_err(|e| e.to_string())
+}
+
 #[tauri::command]
 #[specta::specta]
 #[tracing::instrument(skip(state))]
@@ -89,6 +110,28 @@ pub async fn list_sessions(
     db.list_sessions(filter).await.map_err(|e| e.to_string())
 }
 
+#[tauri::command]
+#[specta::specta]
+#[tracing::instrument(skip(state))]
+pub async fn search_sessions(
+    state: tauri::State<'_, crate::ManagedState>,
+    user_id: String,
+    query: String,
+    limit: Option<u8>,
+) -> Result<Vec<hypr_db_user::SessionSearchResult>, String> {
+    let guard = state.lock().await;
+
+    let db = guard
+        .db
+        .as_ref()
+        .ok_or(crate::Error::NoneDatabase)
+        .map_err(|e| e.to_string())?;
+
+    db.search_sessions(user_id, query, limit)
+        .await
+        .map_err(|e| e.to_string())
+}
+
 #[tauri::command]
 #[specta::specta]
 #[tracing::instrument(skip(state))]
@@ -245,3 +288,27 @@ pub async fn session_get_event(
         .await
         .map_err(|e| e.to_string())
 }
+
+#[tauri::command]
+#[specta::specta]
+#[tracing::instrument(skip(state))]
+pub async fn export_session(
+    state: tauri::State<'_, crate::ManagedState>,
+    session_id: String,
+    format: hypr_export::ExportFormat,
+) -> Result<String, String> {
+    let guard = state.lock().await;
+
+    let db = guard
+        .db
+        .as_ref()
+        .ok_or(crate::Error::NoneDatabase)
+        .map_err(|e| e.to_string())?;
+
+    let input = hypr_export::ExportSession::load(db, session_id)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or("Session not found".to_string())?;
+
+    hypr_export::export(&input, format).map_err(|e| e.to_string())
+}
@@ -17,6 +17,10 @@ pub trait DatabasePluginExt<R: tauri::Runtime> {
         &self,
         user_id: impl Into<String>,
     ) -> impl Future<Output = Result<Option<hypr_db_user::Config>, crate::Error>>;
+    fn db_set_config(
+        &self,
+        config: hypr_db_user::Config,
+    ) -> impl Future<Output = Result<(), crate::Error>>;
     fn db_get_session(
         &self,
         session_id: impl Into<String>,
@@ -25,6 +29,53 @@ pub trait DatabasePluginExt<R: tauri::Runtime> {
         &self,
         session: hypr_db_user::Session,
     ) -> impl Future<Output = Result<(), crate::Error>>;
+    fn db_delete_session(
+        &self,
+        session_id: impl Into<String>,
+    ) -> impl Future<Output = Result<(), crate::Error>>;
+    fn db_append_words(
+        &self,
+        session_id: impl Into<String>,
+        words: Vec<hypr_listener_interface::Word>,
+    ) -> impl Future<Output = Result<(), crate::Error>>;
+    fn db_replace_words(
+        &self,
+        session_id: impl Into<String>,
+        words: Vec<hypr_listener_interface::Word>,
+    ) -> impl Future<Output = Result<(), crate::Error>>;
+    fn db_replace_session_embeddings(
+        &self,
+        session_id: impl Into<String>,
+        model: impl Into<String>,
+        chunks: Vec<(hypr_db_user::SessionChunk, Vec<f32>)>,
+    ) -> impl Future<Output = Result<(), crate::Error>>;
+    fn db_list_sessions_without_embeddings(
+        &self,
+        user_id: impl Into<String>,
+        model: impl Into<String>,
+    ) -> impl Future<Output = Result<Vec<String>, crate::Error>>;
+    fn db_semantic_search_sessions(
+        &self,
+        user_id: impl Into<String>,
+        model: impl Into<String>,
+        embedding: Vec<f32>,
+        limit: Option<u8>,
+    ) -> impl Future<Output = Result<Vec<hypr_db_user::SemanticSearchResult>, crate::Error>>;
+    fn db_get_human(
+        &self,
+        human_id: impl Into<String>,
+    ) -> impl Future<Output = Result<Option<hypr_db_user::Human>, crate::Error>>;
+    fn db_session_list_participants(
+        &self,
+        session_id: impl Into<String>,
+    ) -> impl Future<Output = Result<Vec<hypr_db_user::Human>, crate::Error>>;
+    fn db_list_voiceprints(
+        &self,
+    ) -> impl Future<Output = Result<Vec<hypr_db_user::Voiceprint>, crate::Error>>;
+    fn db_upsert_voiceprint(
+        &self,
+        voiceprint: hypr_db_user::Voiceprint,
+    ) -> impl Future<Output = Result<hypr_db_user::Voiceprint, crate::Error>>;
 }
 
 impl<R: tauri::Runtime, T: tauri::Manager<R>> DatabasePluginExt<R> for T {
@@ -120,6 +171,90 @@ impl<R: tauri::Runtime, T: tauri::Manager<R>> DatabasePluginExt<R> for T {
         Ok(())
     }
 
+    async fn db_delete_session(&self, session_id: impl Into<String>) -> Result<(), crate::Error> {
+        let state = self.state::<crate::ManagedState>();
+        let guard = state.lock().await;
+
+        let db = guard.db.as_ref().ok_or(crate::Error::NoneDatabase)?;
+        db.delete_session(session_id).await?;
+
+        Ok(())
+    }
+
+    async fn db_append_words(
+        &self,
+        session_id: impl Into<String>,
+        words: Vec<hypr_listener_interface::Word>,
+    ) -> Result<(), crate::Error> {
+        let state = self.state::<crate::ManagedState>();
+        let guard = state.lock().await;
+
+        let db = guard.db.as_ref().ok_or(crate::Error::NoneDatabase)?;
+        db.append_words(session_id, words).await?;
+
+        Ok(())
+    }
+
+    async fn db_replace_words(
+        &self,
+        session_id: impl Into<String>,
+        words: Vec<hypr_listener_interface::Word>,
+    ) -> Result<(), crate::Error> {
+        let state = self.state::<crate::ManagedState>();
+        let guard = state.lock().await;
+
+        let db = guard.db.as_ref().ok_or(crate::Error::NoneDatabase)?;
+        db.replace_words(session_id, words).await?;
+
+        Ok(())
+    }
+
+    async fn db_replace_session_embeddings(
+        &self,
+        session_id: impl Into<String>,
+        model: impl Into<String>,
+        chunks: Vec<(hypr_db_user::SessionChunk, Vec<f32>)>,
+    ) -> Result<(), crate::Error> {
+        let state = self.state::<crate::ManagedState>();
+        let guard = state.lock().await;
+
+        let db = guard.db.as_ref().ok_or(crate::Error::NoneDatabase)?;
+        db.replace_session_embeddings(session_id, model, chunks)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn db_list_sessions_without_embeddings(
+        &self,
+        user_id: impl Into<String>,
+        model: impl Into<String>,
+    ) -> Result<Vec<String>, crate::Error> {
+        let state = self.state::<crate::ManagedState>();
+        let guard = state.lock().await;
+
+        let db = guard.db.as_ref().ok_or(crate::Error::NoneDatabase)?;
+        let session_ids = db.list_sessions_without_embeddings(user_id, model).await?;
+        Ok(session_ids)
+    }
+
+    async fn db_semantic_search_sessions(
+        &self,
+        user_id: impl Into<String>,
+        model: impl Into<String>,
+        embedding: Vec<f32>,
+        limit: Option<u8>,
+    ) -> Result<Vec<hypr_db_user::SemanticSearchResult>, crate::Error> {
+        let state = self.state::<crate::ManagedState>();
+        let guard = state.lock().await;
+
+        let db = guard.db.as_ref().ok_or(crate::Error::NoneDatabase)?;
+        let results = db
+            .semantic_search_sessions(user_id, model, embedding, limit)
+            .await?;
+        Ok(results)
+    }
+
     async fn db_get_config(
         &self,
         user_id: impl Into<String>,
@@ -131,4 +266,59 @@ impl<R: tauri::Runtime, T: tauri::Manager<R>> DatabasePluginExt<R> for T {
         let config = db.get_config(user_id.into()).await?;
         Ok(config)
     }
+
+    async fn db_set_config(&self, config: hypr_db_user::Config) -> Result<(), crate::Error> {
+        let state = self.state::<crate::ManagedState>();
+        let guard = state.lock().await;
+
+        let db = guard.db.as_ref().ok_or(crate::Error::NoneDatabase)?;
+        db.set_config(config).await?;
+
+        Ok(())
+    }
+
+    async fn db_get_human(
+        &self,
+        human_id: impl Into<String>,
+    ) -> Result<Option<hypr_db_user::Human>, crate::Error> {
+        let state = self.state::<crate::ManagedState>();
+        let guard = state.lock().await;
+
+        let db = guard.db.as_ref().ok_or(crate::Error::NoneDatabase)?;
+        let human = db.get_human(human_id).await?;
+        Ok(human)
+    }
+
+    async fn db_session_list_participants(
+        &self,
+        session_id: impl Into<String>,
+    ) -> Result<Vec<hypr_db_user::Human>, crate::Error> {
+        let state = self.state::<crate::ManagedState>();
+        let guard = state.lock().await;
+
+        let db = guard.db.as_ref().ok_or(crate::Error::NoneDatabase)?;
+        let participants = db.session_list_participants(session_id).await?;
+        Ok(participants)
+    }
+
+    async fn db_list_voiceprints(&self) -> Result<Vec<hypr_db_user::Voiceprint>, crate::Error> {
+        let state = self.state::<crate::ManagedState>();
+        let guard = state.lock().await;
+
+        let db = guard.db.as_ref().ok_or(crate::Error::NoneDatabase)?;
+        let voiceprints = db.list_voiceprints(None).await?;
+        Ok(voiceprints)
+    }
+
+    async fn db_upsert_voiceprint(
+        &self,
+        voiceprint: hypr_db_user::Voiceprint,
+    ) -> Result<hypr_db_user::Voiceprint, crate::Error> {
+        let state = self.state::<crate::ManagedState>();
+        let guard = state.lock().await;
+
+        let db = guard.db.as_ref().ok_or(crate::Error::NoneDatabase)?;
+        let voiceprint = db.upsert_voiceprint(voiceprint).await?;
+        Ok(voiceprint)
+    }
 }
@@ -35,6 +35,7 @@ fn make_specta_builder<R: tauri::Runtime>() -> tauri_specta::Builder<R> {
             commands::templates::delete_template,
             commands::sessions::onboarding_session_id,
             commands::sessions::list_sessions,
+            commands::sessions::search_sessions,
             commands::sessions::delete_session,
             commands::sessions::get_session,
             commands::sessions::set_session_event,
@@ -42,13 +43,17 @@ fn make_specta_builder<R: tauri::Runtime>() -> tauri_specta::Builder<R> {
             commands::sessions::session_remove_participant,
             commands::sessions::session_list_participants,
             commands::sessions::session_get_event,
+            commands::sessions::export_session,
             commands::sessions::get_words,
+            commands::sessions::replace_words,
             commands::sessions::get_words_onboarding,
             commands::configs::get_config,
             commands::configs::set_config,
             commands::humans::get_human,
             commands::humans::upsert_human,
             commands::humans::list_humans,
+            commands::humans::list_voiceprints,
+            commands::humans::delete_voiceprint,
             commands::organizations::get_organization,
             commands::organizations::get_organization_by_user_id,
             commands::organizations::upsert_organization,
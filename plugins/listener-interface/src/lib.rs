@@ -38,9 +38,31 @@ common_derives! {
 common_derives! {
     pub struct ListenOutputChunk {
         pub words: Vec<Word>,
+        /// Interim chunks are hypotheses for audio that is still being transcribed, and are
+        /// replaced by later chunks. Only final chunks should be persisted.
+        #[serde(default = "default_is_final")]
+        pub is_final: bool,
+        /// Increases with every chunk of a stream. An interim chunk replaces every interim chunk
+        /// with a lower revision, and a final chunk replaces all interim chunks before it.
+        #[serde(default)]
+        pub revision: u64,
     }
 }
 
+impl ListenOutputChunk {
+    pub fn finalized(words: Vec<Word>) -> Self {
+        Self {
+            words,
+            is_final: true,
+            revision: 0,
+        }
+    }
+}
+
+fn default_is_final() -> bool {
+    true
+}
+
 common_derives! {
     #[serde(tag = "type", content = "value")]
     pub enum ListenInputChunk {
@@ -60,6 +82,44 @@ impl Default for ListenInputChunk {
     }
 }
 
+common_derives! {
+    /// How `ListenInputChunk::Audio` travels over the socket. Other chunks are always JSON text.
+    #[derive(Default, Copy)]
+    pub enum AudioFormat {
+        /// JSON text, with the PCM bytes as an array of numbers.
+        #[default]
+        #[serde(rename = "json")]
+        Json,
+        /// Binary frames carrying raw 16kHz mono pcm_s16le.
+        #[serde(rename = "pcm")]
+        Pcm,
+        /// Binary frames, each one 20ms Opus packet of 16kHz mono.
+        #[serde(rename = "opus")]
+        Opus,
+    }
+}
+
+/// Upgrade response header naming the `AudioFormat` the server will decode. Servers that predate
+/// binary audio ignore the `audio_format` param and don't send it, so clients must use
+/// `AudioFormat::Json` without it.
+pub const AUDIO_FORMAT_HEADER: &str = "x-audio-format";
+
+impl AudioFormat {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AudioFormat::Json => "json",
+            AudioFormat::Pcm => "pcm",
+            AudioFormat::Opus => "opus",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        [AudioFormat::Json, AudioFormat::Pcm, AudioFormat::Opus]
+            .into_iter()
+            .find(|format| format.as_str() == s)
+    }
+}
+
 common_derives! {
     #[derive(Default)]
     pub struct ListenParams {
@@ -69,6 +129,14 @@ common_derives! {
         pub language: hypr_language::Language,
         pub static_prompt: String,
         pub dynamic_prompt: String,
+        /// Position of the first streamed sample in the session recording.
+        #[serde(default)]
+        pub offset_ms: u64,
+        #[serde(default)]
+        pub audio_format: AudioFormat,
+        /// Stays the same across reconnects of one stream, so servers can tell a resumed stream from a new one.
+        #[serde(default)]
+        pub resume_id: Option<String>,
     }
 }
 
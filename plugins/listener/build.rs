@@ -1,4 +1,7 @@
 const COMMANDS: &[&str] = &[
+    "list_microphone_devices",
+    "get_mic_device",
+    "set_mic_device",
     "check_microphone_access",
     "check_system_audio_access",
     "request_microphone_access",
@@ -9,6 +12,8 @@ const COMMANDS: &[&str] = &[
     "set_mic_muted",
     "get_speaker_muted",
     "set_speaker_muted",
+    "get_echo_cancellation",
+    "set_echo_cancellation",
     "start_session",
     "stop_session",
     "pause_session",
@@ -10,6 +10,12 @@ export const commands = {
 async listMicrophoneDevices() : Promise<string[]> {
     return await TAURI_INVOKE("plugin:listener|list_microphone_devices");
 },
+async getMicDevice() : Promise<string | null> {
+    return await TAURI_INVOKE("plugin:listener|get_mic_device");
+},
+async setMicDevice(device: string | null) : Promise<null> {
+    return await TAURI_INVOKE("plugin:listener|set_mic_device", { device });
+},
 async checkMicrophoneAccess() : Promise<boolean> {
     return await TAURI_INVOKE("plugin:listener|check_microphone_access");
 },
@@ -40,6 +46,12 @@ async getSpeakerMuted() : Promise<boolean> {
 async setSpeakerMuted(muted: boolean) : Promise<null> {
     return await TAURI_INVOKE("plugin:listener|set_speaker_muted", { muted });
 },
+async getEchoCancellation() : Promise<boolean> {
+    return await TAURI_INVOKE("plugin:listener|get_echo_cancellation");
+},
+async setEchoCancellation(enabled: boolean) : Promise<null> {
+    return await TAURI_INVOKE("plugin:listener|set_echo_cancellation", { enabled });
+},
 async startSession(sessionId: string) : Promise<null> {
     return await TAURI_INVOKE("plugin:listener|start_session", { sessionId });
 },
@@ -72,7 +84,7 @@ sessionEvent: "plugin:listener:session-event"
 
 /** user-defined types **/
 
-export type SessionEvent = { type: "inactive" } | { type: "running_active" } | { type: "running_paused" } | { type: "words"; words: Word[] } | { type: "audioAmplitude"; mic: number; speaker: number }
+export type SessionEvent = { type: "inactive" } | { type: "running_active" } | { type: "running_paused" } | { type: "words"; words: Word[] } | { type: "partialWords"; words: Word[] } | { type: "micDevice"; name: string } | { type: "transcriptionDegraded" } | { type: "transcriptionRestored" } | { type: "transcriptionFailed" } | { type: "audioAmplitude"; mic: number; speaker: number }
 export type SpeakerIdentity = { type: "unassigned"; value: { index: number } } | { type: "assigned"; value: { id: string; label: string } }
 export type Word = { text: string; speaker: SpeakerIdentity | null; confidence: number | null; start_ms: number | null; end_ms: number | null }
 
@@ -1,16 +1,36 @@
-use futures_util::Stream;
+use std::{pin::Pin, sync::Arc, time::Duration};
+
+use bytes::Bytes;
+use futures_util::{Stream, StreamExt};
+use tokio::sync::{mpsc, Mutex};
 
 use hypr_audio::AsyncSource;
-use hypr_audio_utils::AudioFormatExt;
-use hypr_ws::client::{ClientRequestBuilder, Message, WebSocketClient, WebSocketIO};
+use hypr_audio_utils::{AudioFormatExt, OPUS_FRAME_MS};
+use hypr_ws::client::{
+    ClientRequestBuilder, Message, Response, WebSocketClient, WebSocketConnection, WebSocketIO,
+};
+
+use crate::{
+    replay::ReplayBuffer, AudioFormat, ListenInputChunk, ListenOutputChunk, ListenParams,
+    AUDIO_FORMAT_HEADER,
+};
+
+const SAMPLE_RATE: u32 = 16 * 1000;
+// How much unacknowledged audio survives a disconnect.
+const REPLAY_BUFFER_MS: u64 = 60 * 1000;
+// Reconnecting waits twice as long after every failed attempt, and gives up after the last one.
+const RECONNECT_MIN_DELAY: Duration = Duration::from_secs(1);
+const RECONNECT_MAX_DELAY: Duration = Duration::from_secs(30);
+const RECONNECT_MAX_ATTEMPTS: u32 = 10;
 
-use crate::{ListenInputChunk, ListenOutputChunk};
+type AudioStream = Pin<Box<dyn Stream<Item = Bytes> + Send>>;
+type OutputStream = Pin<Box<dyn Stream<Item = ListenOutputChunk> + Send>>;
 
 #[derive(Default)]
 pub struct ListenClientBuilder {
     api_base: Option<String>,
     api_key: Option<String>,
-    params: Option<hypr_listener_interface::ListenParams>,
+    params: Option<ListenParams>,
 }
 
 impl ListenClientBuilder {
@@ -24,48 +44,42 @@ impl ListenClientBuilder {
         self
     }
 
-    pub fn params(mut self, params: hypr_listener_interface::ListenParams) -> Self {
+    pub fn params(mut self, params: ListenParams) -> Self {
         self.params = Some(params);
         self
     }
 
     pub fn build(self) -> ListenClient {
-        let uri = {
-            let mut url: url::Url = self.api_base.unwrap().parse().unwrap();
-
-            let params = self.params.unwrap_or_default();
-            let language = params.language.code();
-
-            url.set_path("/api/desktop/listen/realtime");
-            url.query_pairs_mut()
-                .append_pair("language", language)
-                .append_pair("static_prompt", &params.static_prompt)
-                .append_pair("dynamic_prompt", &params.dynamic_prompt);
-
-            let host = url.host_str().unwrap();
-
-            if host.contains("127.0.0.1") || host.contains("localhost") {
-                url.set_scheme("ws").unwrap();
-            } else {
-                url.set_scheme("wss").unwrap();
-            }
+        let mut params = self.params.unwrap_or_default();
+        params
+            .resume_id
+            .get_or_insert_with(|| uuid::Uuid::new_v4().to_string());
 
-            url.to_string().parse().unwrap()
-        };
-
-        let request = match self.api_key {
-            Some(key) => ClientRequestBuilder::new(uri)
-                .with_header("Authorization", format!("Bearer {}", key)),
-            None => ClientRequestBuilder::new(uri),
-        };
-
-        ListenClient { request }
+        ListenClient {
+            api_base: self.api_base.unwrap().parse().unwrap(),
+            api_key: self.api_key,
+            params,
+        }
     }
 }
 
 #[derive(Clone)]
 pub struct ListenClient {
-    request: ClientRequestBuilder,
+    api_base: url::Url,
+    api_key: Option<String>,
+    params: ListenParams,
+}
+
+#[derive(Debug, Clone)]
+pub enum ListenEvent {
+    Chunk(ListenOutputChunk),
+    /// The connection dropped. Audio is buffered until it is back.
+    Disconnected,
+    /// Connected again, and the buffered audio is being transcribed.
+    Reconnected,
+    /// Reconnecting was given up on, either after too many attempts or because the server
+    /// rejected the connection for good. The stream ends after this.
+    Failed,
 }
 
 impl WebSocketIO for ListenClient {
@@ -90,18 +104,313 @@ impl WebSocketIO for ListenClient {
     }
 }
 
+/// Sends audio as binary frames, for servers that were asked for `AudioFormat::Pcm` or `AudioFormat::Opus`.
+struct BinaryListenIO;
+
+impl WebSocketIO for BinaryListenIO {
+    type Input = ListenInputChunk;
+    type Output = ListenOutputChunk;
+
+    fn to_input(data: bytes::Bytes) -> Self::Input {
+        ListenClient::to_input(data)
+    }
+
+    fn to_message(input: Self::Input) -> Message {
+        match input {
+            ListenInputChunk::Audio { data } => Message::Binary(data.into()),
+            input => ListenClient::to_message(input),
+        }
+    }
+
+    fn from_message(msg: Message) -> Option<Self::Output> {
+        ListenClient::from_message(msg)
+    }
+}
+
+/// Encoded audio on its way to whichever connection is current.
+struct Feed {
+    buffer: ReplayBuffer,
+    sender: Option<mpsc::UnboundedSender<Bytes>>,
+    finished: bool,
+}
+
+impl Feed {
+    /// Routes audio to a new connection, starting with everything the server hasn't acknowledged.
+    fn attach(&mut self) -> (u64, AudioStream) {
+        let (tx, rx) = mpsc::unbounded_channel();
+
+        let (offset_ms, chunks) = self.buffer.replay();
+        for chunk in chunks {
+            let _ = tx.send(chunk);
+        }
+
+        // Without a sender the stream ends after the replay, which sends `ListenInputChunk::End`.
+        if !self.finished {
+            self.sender = Some(tx);
+        }
+
+        let stream = futures_util::stream::unfold(rx, |mut rx| async move {
+            rx.recv().await.map(|chunk| (chunk, rx))
+        });
+
+        (offset_ms, Box::pin(stream))
+    }
+}
+
 impl ListenClient {
     pub fn builder() -> ListenClientBuilder {
         ListenClientBuilder::default()
     }
 
+    /// Transcribes the audio over a connection that is re-established whenever it drops.
+    ///
+    /// Audio not yet covered by a final transcript is replayed after reconnecting, and words the
+    /// previous connection already finalized are not repeated.
     pub async fn from_audio(
         &self,
         audio_stream: impl AsyncSource + Send + Unpin + 'static,
-    ) -> Result<impl Stream<Item = ListenOutputChunk>, hypr_ws::Error> {
-        let input_stream = audio_stream.to_i16_le_chunks(16 * 1000, 1024);
-        let ws = WebSocketClient::new(self.request.clone());
-        ws.from_audio::<Self>(input_stream).await
+    ) -> Result<impl Stream<Item = ListenEvent>, crate::Error> {
+        let feed = Arc::new(Mutex::new(Feed {
+            buffer: ReplayBuffer::new(self.params.offset_ms, REPLAY_BUFFER_MS),
+            sender: None,
+            finished: false,
+        }));
+
+        let (offset_ms, audio) = feed.lock().await.attach();
+        let connection = self.handshake(self.params.audio_format, offset_ms).await?;
+
+        // Whatever the first server accepted is used for the whole stream, since the replay buffer
+        // holds encoded audio.
+        let audio_format = accepted_format(self.params.audio_format, connection.response());
+        if audio_format != self.params.audio_format {
+            tracing::warn!(
+                "listen_audio_format_fallback: {} -> {}",
+                self.params.audio_format.as_str(),
+                audio_format.as_str()
+            );
+        }
+        let mut output = Self::stream(connection, audio_format, audio);
+
+        let mut input_stream: AudioStream = match audio_format {
+            AudioFormat::Json | AudioFormat::Pcm => {
+                Box::pin(audio_stream.to_i16_le_chunks(SAMPLE_RATE, 1024))
+            }
+            AudioFormat::Opus => Box::pin(audio_stream.to_opus_packets(SAMPLE_RATE)?),
+        };
+
+        tokio::spawn({
+            let feed = feed.clone();
+
+            async move {
+                while let Some(chunk) = input_stream.next().await {
+                    let samples = chunk_samples(audio_format, &chunk);
+
+                    let mut feed = feed.lock().await;
+                    feed.buffer.push(chunk.clone(), samples);
+                    if let Some(sender) = &feed.sender {
+                        let _ = sender.send(chunk);
+                    }
+                }
+
+                let mut feed = feed.lock().await;
+                feed.finished = true;
+                feed.sender = None;
+            }
+        });
+
+        let (event_tx, event_rx) = mpsc::unbounded_channel();
+        let client = self.clone();
+
+        tokio::spawn(async move {
+            // Words ending before this were finalized by a previous connection.
+            let mut resumed_at_ms = 0;
+            // Only a connection that delivers a chunk resets the backoff, so a server that
+            // accepts and then drops every connection still runs out of attempts.
+            let mut attempts = 0;
+            let mut delay = RECONNECT_MIN_DELAY;
+
+            loop {
+                while let Some(mut chunk) = output.next().await {
+                    attempts = 0;
+                    delay = RECONNECT_MIN_DELAY;
+
+                    chunk
+                        .words
+                        .retain(|w| !w.end_ms.is_some_and(|end_ms| end_ms <= resumed_at_ms));
+
+                    if chunk.is_final {
+                        if let Some(end_ms) = chunk.words.iter().filter_map(|w| w.end_ms).max() {
+                            feed.lock().await.buffer.ack(end_ms);
+                        }
+
+                        if chunk.words.is_empty() {
+                            continue;
+                        }
+                    }
+
+                    if event_tx.send(ListenEvent::Chunk(chunk)).is_err() {
+                        return;
+                    }
+                }
+
+                if feed.lock().await.finished {
+                    break;
+                }
+
+                tracing::warn!("listen_stream_disconnected");
+                if event_tx.send(ListenEvent::Disconnected).is_err() {
+                    return;
+                }
+
+                loop {
+                    if attempts >= RECONNECT_MAX_ATTEMPTS {
+                        let _ = event_tx.send(ListenEvent::Failed);
+                        return;
+                    }
+                    if attempts > 0 {
+                        tokio::time::sleep(delay).await;
+                        delay = (delay * 2).min(RECONNECT_MAX_DELAY);
+                    }
+                    attempts += 1;
+
+                    let (offset_ms, audio, acked_ms) = {
+                        let mut feed = feed.lock().await;
+                        if feed.finished || event_tx.is_closed() {
+                            return;
+                        }
+
+                        let (offset_ms, audio) = feed.attach();
+                        (offset_ms, audio, feed.buffer.acked_ms())
+                    };
+
+                    match client.connect(audio_format, offset_ms, audio).await {
+                        Ok(stream) => {
+                            output = stream;
+                            resumed_at_ms = acked_ms;
+                            break;
+                        }
+                        Err(e) => {
+                            tracing::error!(attempts, "listen_reconnect_failed: {:?}", e);
+
+                            if !e.is_retryable() {
+                                let _ = event_tx.send(ListenEvent::Failed);
+                                return;
+                            }
+                        }
+                    }
+                }
+
+                tracing::info!("listen_stream_reconnected");
+                if event_tx.send(ListenEvent::Reconnected).is_err() {
+                    return;
+                }
+            }
+        });
+
+        Ok(futures_util::stream::unfold(
+            event_rx,
+            |mut rx| async move { rx.recv().await.map(|event| (event, rx)) },
+        ))
+    }
+
+    /// Reconnects with the format the stream already uses, and fails if the server doesn't accept it.
+    ///
+    /// Makes a single attempt, the reconnect loop does the retrying.
+    async fn connect(
+        &self,
+        audio_format: AudioFormat,
+        offset_ms: u64,
+        audio: AudioStream,
+    ) -> Result<OutputStream, crate::Error> {
+        let connection = WebSocketClient::new(self.request(audio_format, offset_ms))
+            .connect_once()
+            .await?;
+
+        if accepted_format(audio_format, connection.response()) != audio_format {
+            return Err(crate::Error::AudioFormatNotAccepted(audio_format.as_str()));
+        }
+
+        Ok(Self::stream(connection, audio_format, audio))
+    }
+
+    async fn handshake(
+        &self,
+        audio_format: AudioFormat,
+        offset_ms: u64,
+    ) -> Result<WebSocketConnection, hypr_ws::Error> {
+        WebSocketClient::new(self.request(audio_format, offset_ms))
+            .connect()
+            .await
+    }
+
+    fn stream(
+        connection: WebSocketConnection,
+        audio_format: AudioFormat,
+        audio: AudioStream,
+    ) -> OutputStream {
+        match audio_format {
+            AudioFormat::Json => Box::pin(connection.from_audio::<Self>(audio)),
+            AudioFormat::Pcm | AudioFormat::Opus => {
+                Box::pin(connection.from_audio::<BinaryListenIO>(audio))
+            }
+        }
+    }
+
+    fn request(&self, audio_format: AudioFormat, offset_ms: u64) -> ClientRequestBuilder {
+        let uri = {
+            let mut url = self.api_base.clone();
+
+            let language = self.params.language.code();
+
+            url.set_path("/api/desktop/listen/realtime");
+            url.query_pairs_mut()
+                .append_pair("language", language)
+                .append_pair("static_prompt", &self.params.static_prompt)
+                .append_pair("dynamic_prompt", &self.params.dynamic_prompt)
+                .append_pair("offset_ms", &offset_ms.to_string())
+                .append_pair("audio_format", audio_format.as_str());
+
+            if let Some(resume_id) = &self.params.resume_id {
+                url.query_pairs_mut().append_pair("resume_id", resume_id);
+            }
+
+            let host = url.host_str().unwrap();
+
+            if host.contains("127.0.0.1") || host.contains("localhost") {
+                url.set_scheme("ws").unwrap();
+            } else {
+                url.set_scheme("wss").unwrap();
+            }
+
+            url.to_string().parse().unwrap()
+        };
+
+        match &self.api_key {
+            Some(key) => ClientRequestBuilder::new(uri)
+                .with_header("Authorization", format!("Bearer {}", key)),
+            None => ClientRequestBuilder::new(uri),
+        }
+    }
+}
+
+/// The format the server acknowledged in its upgrade response, or JSON if it didn't.
+fn accepted_format(requested: AudioFormat, response: &Response) -> AudioFormat {
+    let accepted = response
+        .headers()
+        .get(AUDIO_FORMAT_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .and_then(AudioFormat::parse);
+
+    match accepted {
+        Some(format) if format == requested => format,
+        _ => AudioFormat::Json,
+    }
+}
+
+fn chunk_samples(audio_format: AudioFormat, chunk: &Bytes) -> u64 {
+    match audio_format {
+        AudioFormat::Json | AudioFormat::Pcm => (chunk.len() / 2) as u64,
+        AudioFormat::Opus => (SAMPLE_RATE * OPUS_FRAME_MS / 1000) as u64,
     }
 }
 
@@ -110,6 +419,31 @@ mod tests {
     use super::*;
     use futures_util::StreamExt;
 
+    #[test]
+    fn test_accepted_format() {
+        let response = |header: Option<&str>| {
+            let mut builder = Response::builder();
+            if let Some(value) = header {
+                builder = builder.header(AUDIO_FORMAT_HEADER, value);
+            }
+            builder.body(None).unwrap()
+        };
+
+        assert_eq!(
+            accepted_format(AudioFormat::Opus, &response(Some("opus"))),
+            AudioFormat::Opus
+        );
+        // Servers that predate binary audio don't send the header.
+        assert_eq!(
+            accepted_format(AudioFormat::Opus, &response(None)),
+            AudioFormat::Json
+        );
+        assert_eq!(
+            accepted_format(AudioFormat::Opus, &response(Some("pcm"))),
+            AudioFormat::Json
+        );
+    }
+
     #[tokio::test]
     #[ignore]
     async fn test_listen_client() {
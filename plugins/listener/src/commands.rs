@@ -10,6 +10,23 @@ pub async fn list_microphone_devices<R: tauri::Runtime>(
         .map_err(|e| e.to_string())
 }
 
+#[tauri::command]
+#[specta::specta]
+pub async fn get_mic_device<R: tauri::Runtime>(
+    app: tauri::AppHandle<R>,
+) -> Result<Option<String>, String> {
+    app.get_mic_device().await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn set_mic_device<R: tauri::Runtime>(
+    app: tauri::AppHandle<R>,
+    device: Option<String>,
+) -> Result<(), String> {
+    app.set_mic_device(device).await.map_err(|e| e.to_string())
+}
+
 #[tauri::command]
 #[specta::specta]
 pub async fn check_microphone_access<R: tauri::Runtime>(
@@ -104,6 +121,24 @@ pub async fn set_speaker_muted<R: tauri::Runtime>(
     Ok(())
 }
 
+#[tauri::command]
+#[specta::specta]
+pub async fn get_echo_cancellation<R: tauri::Runtime>(
+    app: tauri::AppHandle<R>,
+) -> Result<bool, String> {
+    Ok(app.get_echo_cancellation().await)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn set_echo_cancellation<R: tauri::Runtime>(
+    app: tauri::AppHandle<R>,
+    enabled: bool,
+) -> Result<(), String> {
+    app.set_echo_cancellation(enabled).await;
+    Ok(())
+}
+
 #[tauri::command]
 #[specta::specta]
 pub async fn start_session<R: tauri::Runtime>(
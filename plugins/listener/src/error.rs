@@ -7,13 +7,25 @@ pub enum Error {
     #[error(transparent)]
     IoError(#[from] std::io::Error),
     #[error(transparent)]
+    SerdeJsonError(#[from] serde_json::Error),
+    #[error(transparent)]
     CpalDevicesError(#[from] hypr_audio::cpal::DevicesError),
     #[error(transparent)]
+    AudioError(#[from] hypr_audio::Error),
+    #[error(transparent)]
     ListenClientError(#[from] hypr_ws::Error),
     #[error(transparent)]
+    OpusError(#[from] hypr_audio_utils::OpusError),
+    #[error(transparent)]
+    RecorderError(#[from] hypr_recorder::Error),
+    #[error(transparent)]
     DatabaseError(#[from] tauri_plugin_db::Error),
     #[error(transparent)]
     ConnectorError(#[from] tauri_plugin_connector::Error),
+    #[error("audio format not accepted: {0}")]
+    AudioFormatNotAccepted(&'static str),
+    #[error("no user")]
+    NoneUser,
     #[error("no session")]
     NoneSession,
     #[error("start session failed")]
@@ -26,6 +38,17 @@ pub enum Error {
     ResumeSessionFailed,
 }
 
+impl Error {
+    /// Whether reconnecting the listen stream could fix this.
+    pub(crate) fn is_retryable(&self) -> bool {
+        match self {
+            Error::ListenClientError(e) => e.is_retryable(),
+            Error::AudioFormatNotAccepted(_) => false,
+            _ => true,
+        }
+    }
+}
+
 impl Serialize for Error {
     fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
     where
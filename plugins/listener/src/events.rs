@@ -15,8 +15,23 @@ common_event_derives! {
         RunningActive {},
         #[serde(rename = "running_paused")]
         RunningPaused {},
+        /// Newly finalized words, following the ones already emitted or stored.
         #[serde(rename = "words")]
         Words { words: Vec<hypr_listener_interface::Word>},
+        /// Replaces the previous partial words. Cleared by the next `words` event.
+        #[serde(rename = "partialWords")]
+        PartialWords { words: Vec<hypr_listener_interface::Word>},
+        /// The input device the session is recording from, emitted whenever it is (re)opened.
+        #[serde(rename = "micDevice")]
+        MicDevice { name: String },
+        /// The connection to the STT server dropped. Audio is kept and transcribed once it is back.
+        #[serde(rename = "transcriptionDegraded")]
+        TranscriptionDegraded {},
+        #[serde(rename = "transcriptionRestored")]
+        TranscriptionRestored {},
+        /// Reconnecting was given up on, so the session ends without the rest of its transcript.
+        #[serde(rename = "transcriptionFailed")]
+        TranscriptionFailed {},
         #[serde(rename = "audioAmplitude")]
         AudioAmplitude { mic: u16, speaker: u16 },
     }
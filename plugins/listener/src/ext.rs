@@ -11,6 +11,11 @@ use {
 
 pub trait ListenerPluginExt<R: tauri::Runtime> {
     fn list_microphone_devices(&self) -> impl Future<Output = Result<Vec<String>, crate::Error>>;
+    fn get_mic_device(&self) -> impl Future<Output = Result<Option<String>, crate::Error>>;
+    fn set_mic_device(
+        &self,
+        device: Option<String>,
+    ) -> impl Future<Output = Result<(), crate::Error>>;
 
     fn check_microphone_access(&self) -> impl Future<Output = Result<bool, crate::Error>>;
     fn check_system_audio_access(&self) -> impl Future<Output = Result<bool, crate::Error>>;
@@ -23,6 +28,8 @@ pub trait ListenerPluginExt<R: tauri::Runtime> {
     fn get_speaker_muted(&self) -> impl Future<Output = bool>;
     fn set_mic_muted(&self, muted: bool) -> impl Future<Output = ()>;
     fn set_speaker_muted(&self, muted: bool) -> impl Future<Output = ()>;
+    fn get_echo_cancellation(&self) -> impl Future<Output = bool>;
+    fn set_echo_cancellation(&self, enabled: bool) -> impl Future<Output = ()>;
 
     fn get_state(&self) -> impl Future<Output = crate::fsm::State>;
     fn stop_session(&self) -> impl Future<Output = ()>;
@@ -39,6 +46,44 @@ impl<R: tauri::Runtime, T: tauri::Manager<R>> ListenerPluginExt<R> for T {
         Ok(devices.filter_map(|d| d.name().ok()).collect())
     }
 
+    #[tracing::instrument(skip_all)]
+    async fn get_mic_device(&self) -> Result<Option<String>, crate::Error> {
+        use tauri_plugin_db::DatabasePluginExt;
+
+        let user_id = self.db_user_id().await?.ok_or(crate::Error::NoneUser)?;
+        let config = self.db_get_config(&user_id).await?;
+        Ok(config.and_then(|c| c.general.mic_device))
+    }
+
+    #[tracing::instrument(skip_all)]
+    async fn set_mic_device(&self, device: Option<String>) -> Result<(), crate::Error> {
+        use tauri_plugin_db::DatabasePluginExt;
+
+        let user_id = self.db_user_id().await?.ok_or(crate::Error::NoneUser)?;
+        let mut config =
+            self.db_get_config(&user_id)
+                .await?
+                .unwrap_or_else(|| hypr_db_user::Config {
+                    id: uuid::Uuid::new_v4().to_string(),
+                    user_id: user_id.clone(),
+                    general: hypr_db_user::ConfigGeneral::default(),
+                    notification: hypr_db_user::ConfigNotification::default(),
+                    ai: hypr_db_user::ConfigAI::default(),
+                });
+        config.general.mic_device = device.clone();
+        self.db_set_config(config).await?;
+
+        let state = self.state::<crate::SharedState>();
+
+        {
+            let mut guard = state.lock().await;
+            let event = crate::fsm::StateEvent::MicDevice(device);
+            guard.fsm.handle(&event).await;
+        }
+
+        Ok(())
+    }
+
     #[tracing::instrument(skip_all)]
     async fn check_microphone_access(&self) -> Result<bool, crate::Error> {
         #[cfg(target_os = "macos")]
@@ -172,6 +217,27 @@ impl<R: tauri::Runtime, T: tauri::Manager<R>> ListenerPluginExt<R> for T {
         }
     }
 
+    #[tracing::instrument(skip_all)]
+    async fn get_echo_cancellation(&self) -> bool {
+        let state = self.state::<crate::SharedState>();
+
+        {
+            let guard = state.lock().await;
+            guard.fsm.is_echo_cancellation_enabled()
+        }
+    }
+
+    #[tracing::instrument(skip_all)]
+    async fn set_echo_cancellation(&self, enabled: bool) {
+        let state = self.state::<crate::SharedState>();
+
+        {
+            let mut guard = state.lock().await;
+            let event = crate::fsm::StateEvent::EchoCancellation(enabled);
+            guard.fsm.handle(&event).await;
+        }
+    }
+
     #[tracing::instrument(skip_all)]
     async fn start_session(&self, session_id: impl Into<String>) {
         let state = self.state::<crate::SharedState>();
@@ -1,3 +1,8 @@
+use std::collections::HashMap;
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc,
+};
 use std::time::{Duration, Instant};
 
 use statig::prelude::*;
@@ -11,10 +16,20 @@ use tokio::task::JoinSet;
 
 use hypr_audio::AsyncSource;
 
-use crate::SessionEvent;
+use crate::{ListenEvent, SessionEvent};
 
 const SAMPLE_RATE: u32 = 16000;
 const AUDIO_AMPLITUDE_THROTTLE: Duration = Duration::from_millis(100);
+// ~30 seconds of 1024-sample chunks. The recorder runs on a blocking thread and only falls behind on a stalled disk.
+const RECORDER_BUFFER_CHUNKS: usize = 512;
+// Backoff before reopening the mic after its device went away, so a flapping device doesn't spin.
+const MIC_REOPEN_INTERVAL: Duration = Duration::from_millis(500);
+
+enum RecorderInput {
+    Frames(Vec<f32>, Vec<f32>),
+    Pause,
+    Resume,
+}
 
 pub struct Session {
     app: tauri::AppHandle,
@@ -23,9 +38,16 @@ pub struct Session {
     mic_muted_rx: Option<tokio::sync::watch::Receiver<bool>>,
     speaker_muted_tx: Option<tokio::sync::watch::Sender<bool>>,
     speaker_muted_rx: Option<tokio::sync::watch::Receiver<bool>>,
+    mic_device_tx: Option<tokio::sync::watch::Sender<Option<String>>>,
+    echo_cancellation_tx: Option<tokio::sync::watch::Sender<bool>>,
+    echo_cancellation_rx: Option<tokio::sync::watch::Receiver<bool>>,
     silence_stream_tx: Option<std::sync::mpsc::Sender<()>>,
     session_state_tx: Option<tokio::sync::watch::Sender<State>>,
     tasks: Option<JoinSet<()>>,
+    recorder_task: Option<tokio::task::JoinHandle<()>>,
+    // Samples streamed for transcription per session, so a restarted session continues its
+    // timeline even when nothing was recorded.
+    streamed_samples: HashMap<String, Arc<AtomicU64>>,
 }
 
 impl Session {
@@ -37,9 +59,14 @@ impl Session {
             mic_muted_rx: None,
             speaker_muted_tx: None,
             speaker_muted_rx: None,
+            mic_device_tx: None,
+            echo_cancellation_tx: None,
+            echo_cancellation_rx: None,
             silence_stream_tx: None,
             tasks: None,
+            recorder_task: None,
             session_state_tx: None,
+            streamed_samples: HashMap::new(),
         }
     }
 
@@ -51,7 +78,7 @@ impl Session {
         let session_id = id.into();
         self.session_id = Some(session_id.clone());
 
-        let (record, language, jargons) = {
+        let (record, language, jargons, mic_device, echo_cancellation) = {
             let config = self.app.db_get_config(&user_id).await?;
 
             let record = config
@@ -63,9 +90,15 @@ impl Session {
                 |c| c.general.display_language.clone(),
             );
 
+            let mic_device = config.as_ref().and_then(|c| c.general.mic_device.clone());
+
+            let echo_cancellation = config
+                .as_ref()
+                .is_none_or(|c| c.general.echo_cancellation.unwrap_or(true));
+
             let jargons = config.map_or_else(Vec::new, |c| c.general.jargons);
 
-            (record, language, jargons)
+            (record, language, jargons, mic_device, echo_cancellation)
         };
 
         let session = self
@@ -76,6 +109,9 @@ impl Session {
 
         let (mic_muted_tx, mic_muted_rx_main) = tokio::sync::watch::channel(false);
         let (speaker_muted_tx, speaker_muted_rx_main) = tokio::sync::watch::channel(false);
+        let (mic_device_tx, mut mic_device_rx) = tokio::sync::watch::channel(mic_device);
+        let (echo_cancellation_tx, echo_cancellation_rx) =
+            tokio::sync::watch::channel(echo_cancellation);
         let (session_state_tx, session_state_rx) =
             tokio::sync::watch::channel(State::RunningActive {});
 
@@ -85,15 +121,55 @@ impl Session {
         self.mic_muted_rx = Some(mic_muted_rx_main.clone());
         self.speaker_muted_tx = Some(speaker_muted_tx);
         self.speaker_muted_rx = Some(speaker_muted_rx_main.clone());
+        self.mic_device_tx = Some(mic_device_tx);
+        self.echo_cancellation_tx = Some(echo_cancellation_tx);
+        self.echo_cancellation_rx = Some(echo_cancellation_rx.clone());
         self.session_state_tx = Some(session_state_tx);
 
-        let listen_client = setup_listen_client(&self.app, language, jargons).await?;
-
-        let mic_sample_stream = {
-            let mut input = hypr_audio::AudioInput::from_mic();
-            input.stream()
+        let dir = self.app.path().app_data_dir().unwrap().join(&session_id);
+
+        // Opened up front, because the transcript continues wherever an earlier recording of
+        // this session left off.
+        let recorder = if record {
+            let dir = dir.clone();
+            match tokio::task::spawn_blocking(move || open_recorder(&dir)).await {
+                Ok(Ok(recorder)) => Some(recorder),
+                Ok(Err(e)) => {
+                    tracing::error!("recorder_error: {:?}", e);
+                    None
+                }
+                Err(e) => {
+                    tracing::error!("recorder_error: {:?}", e);
+                    None
+                }
+            }
+        } else {
+            None
         };
-        let mut mic_stream = mic_sample_stream.resample(SAMPLE_RATE).chunks(1024);
+        let record = recorder.is_some();
+
+        // Paused audio is neither recorded nor streamed, so streamed time matches recorded time.
+        // After a restart of the app the counter is gone, and the recording or, when nothing was
+        // recorded, the stored transcript tells how far the session got.
+        let streamed_samples = self
+            .streamed_samples
+            .entry(session_id.clone())
+            .or_default()
+            .clone();
+        let recorded_samples = recorder.as_ref().map_or(0, |r| r.frames());
+        let transcribed_samples = session
+            .words
+            .iter()
+            .filter_map(|w| w.end_ms)
+            .max()
+            .map_or(0, |ms| ms * SAMPLE_RATE as u64 / 1000);
+        streamed_samples.fetch_max(recorded_samples.max(transcribed_samples), Ordering::Relaxed);
+        let offset_ms = streamed_samples.load(Ordering::Relaxed) * 1000 / SAMPLE_RATE as u64;
+
+        let listen_client = setup_listen_client(&self.app, language, jargons, offset_ms).await?;
+
+        let mut mic_stream =
+            open_mic_stream(&self.app, mic_device_rx.borrow_and_update().as_deref())?;
         tokio::time::sleep(Duration::from_millis(100)).await;
 
         let speaker_sample_stream = hypr_audio::AudioInput::from_speaker(None).stream();
@@ -105,7 +181,7 @@ impl Session {
         let (mic_tx, mut mic_rx) = mpsc::channel::<Vec<f32>>(chunk_buffer_size);
         let (speaker_tx, mut speaker_rx) = mpsc::channel::<Vec<f32>>(chunk_buffer_size);
 
-        let (save_tx, mut save_rx) = mpsc::channel::<f32>(sample_buffer_size);
+        let (save_tx, save_rx) = mpsc::channel::<RecorderInput>(RECORDER_BUFFER_CHUNKS);
         let (process_tx, process_rx) = mpsc::channel::<f32>(sample_buffer_size);
 
         {
@@ -116,25 +192,70 @@ impl Session {
         let mut tasks = JoinSet::new();
 
         tasks.spawn({
+            let app = self.app.clone();
             let mic_muted_rx = mic_muted_rx_main.clone();
             async move {
                 let mut is_muted = *mic_muted_rx.borrow();
                 let watch_rx = mic_muted_rx.clone();
 
-                while let Some(actual) = mic_stream.next().await {
-                    if watch_rx.has_changed().unwrap_or(false) {
-                        is_muted = *watch_rx.borrow();
+                loop {
+                    let reopen = tokio::select! {
+                        chunk = mic_stream.next() => match chunk {
+                            Some(actual) => {
+                                if watch_rx.has_changed().unwrap_or(false) {
+                                    is_muted = *watch_rx.borrow();
+                                }
+
+                                let maybe_muted = if is_muted {
+                                    vec![0.0; actual.len()]
+                                } else {
+                                    actual
+                                };
+
+                                if let Err(e) = mic_tx.send(maybe_muted).await {
+                                    tracing::error!("mic_tx_send_error: {:?}", e);
+                                    break;
+                                }
+                                false
+                            }
+                            // The device was unplugged, reopening falls back to the default one.
+                            None => {
+                                tracing::warn!("mic_device_lost");
+                                tokio::time::sleep(MIC_REOPEN_INTERVAL).await;
+                                true
+                            }
+                        },
+                        changed = mic_device_rx.changed() => {
+                            if changed.is_err() {
+                                break;
+                            }
+                            true
+                        }
+                    };
+
+                    if !reopen {
+                        continue;
                     }
 
-                    let maybe_muted = if is_muted {
-                        vec![0.0; actual.len()]
-                    } else {
-                        actual
+                    let started = Instant::now();
+                    mic_stream = loop {
+                        let device = mic_device_rx.borrow_and_update().clone();
+                        match open_mic_stream(&app, device.as_deref()) {
+                            Ok(stream) => break stream,
+                            Err(e) => {
+                                tracing::error!("mic_open_error: {:?}", e);
+                                tokio::time::sleep(MIC_REOPEN_INTERVAL).await;
+                            }
+                        }
                     };
 
-                    if let Err(e) = mic_tx.send(maybe_muted).await {
-                        tracing::error!("mic_tx_send_error: {:?}", e);
-                        break;
+                    // Fills the time spent without a device, so the mic stays aligned with the speaker.
+                    let missing_chunks =
+                        (started.elapsed().as_secs_f64() * SAMPLE_RATE as f64) as usize / 1024;
+                    for _ in 0..missing_chunks {
+                        if mic_tx.send(vec![0.0; 1024]).await.is_err() {
+                            return;
+                        }
                     }
                 }
             }
@@ -165,24 +286,51 @@ impl Session {
             }
         });
 
-        let app_dir = self.app.path().app_data_dir().unwrap();
-
         tasks.spawn({
             let app = self.app.clone();
             let save_tx = save_tx.clone();
+            let mut record = record;
 
             async move {
                 let mut last_broadcast = Instant::now();
+                // Created lazily and dropped when disabled, so re-enabling doesn't start from a stale filter.
+                let mut aec: Option<hypr_aec2::StreamingAEC> = None;
+                let mut paused = false;
 
-                while let (Some(mic_chunk), Some(speaker_chunk)) =
+                while let (Some(mut mic_chunk), Some(speaker_chunk)) =
                     (mic_rx.recv().await, speaker_rx.recv().await)
                 {
-                    if matches!(*session_state_rx.borrow(), State::RunningPaused {}) {
-                        let mut rx = session_state_rx.clone();
-                        let _ = rx.changed().await;
+                    let is_paused = matches!(*session_state_rx.borrow(), State::RunningPaused {});
+                    if is_paused != paused {
+                        paused = is_paused;
+
+                        let input = if paused {
+                            RecorderInput::Pause
+                        } else {
+                            RecorderInput::Resume
+                        };
+                        if record && save_tx.send(input).await.is_err() {
+                            tracing::error!("save_tx_send_error");
+                            record = false;
+                        }
+                    }
+
+                    // Keeps draining the devices while paused, so no audio from the pause is
+                    // left buffered to be processed after resuming.
+                    if paused {
                         continue;
                     }
 
+                    // Laptop speakers leak the remote side into the mic, which would otherwise be transcribed twice.
+                    if *echo_cancellation_rx.borrow() {
+                        let aec = aec.get_or_insert_with(|| {
+                            hypr_aec2::StreamingAEC::new(SAMPLE_RATE as usize)
+                        });
+                        mic_chunk = aec.process(&mic_chunk, &speaker_chunk);
+                    } else {
+                        aec = None;
+                    }
+
                     let now = Instant::now();
                     if now.duration_since(last_broadcast) >= AUDIO_AMPLITUDE_THROTTLE {
                         if let Err(e) = SessionEvent::from((&mic_chunk, &speaker_chunk)).emit(&app)
@@ -193,53 +341,38 @@ impl Session {
                     }
 
                     let mixed: Vec<f32> = mic_chunk
-                        .into_iter()
-                        .zip(speaker_chunk.into_iter())
+                        .iter()
+                        .zip(speaker_chunk.iter())
                         .map(|(a, b)| (a + b).clamp(-1.0, 1.0))
                         .collect();
 
+                    if record
+                        && save_tx
+                            .send(RecorderInput::Frames(mic_chunk, speaker_chunk))
+                            .await
+                            .is_err()
+                    {
+                        tracing::error!("save_tx_send_error");
+                        record = false;
+                    }
+
                     for &sample in &mixed {
                         if process_tx.send(sample).await.is_err() {
                             tracing::error!("process_tx_send_error");
                             return;
                         }
-
-                        if record {
-                            if save_tx.send(sample).await.is_err() {
-                                tracing::error!("save_tx_send_error");
-                            }
-                        }
                     }
+                    streamed_samples.fetch_add(mixed.len() as u64, Ordering::Relaxed);
                 }
             }
         });
 
-        if record {
-            tasks.spawn(async move {
-                let dir = app_dir.join(session_id);
-                std::fs::create_dir_all(&dir).unwrap();
-                let path = dir.join("audio.wav");
-
-                let wav_spec = hound::WavSpec {
-                    channels: 2,
-                    sample_rate: SAMPLE_RATE,
-                    bits_per_sample: 32,
-                    sample_format: hound::SampleFormat::Float,
-                };
-
-                let mut wav = if path.exists() {
-                    hound::WavWriter::append(path).unwrap()
-                } else {
-                    hound::WavWriter::create(path, wav_spec).unwrap()
-                };
-
-                while let Some(sample) = save_rx.recv().await {
-                    wav.write_sample(sample).unwrap();
-                    wav.write_sample(sample).unwrap();
+        if let Some(recorder) = recorder {
+            self.recorder_task = Some(tokio::task::spawn_blocking(move || {
+                if let Err(e) = run_recorder(recorder, dir, save_rx) {
+                    tracing::error!("recorder_error: {:?}", e);
                 }
-
-                wav.finalize().unwrap();
-            });
+            }));
         }
 
         // TODO
@@ -255,19 +388,57 @@ impl Session {
             async move {
                 futures_util::pin_mut!(listen_stream);
 
-                while let Some(result) = listen_stream.next().await {
-                    // We don't have to do this, and inefficient. But this is what works at the moment.
+                while let Some(event) = listen_stream.next().await {
+                    let result = match event {
+                        ListenEvent::Chunk(result) => result,
+                        ListenEvent::Disconnected => {
+                            if let Err(e) = (SessionEvent::TranscriptionDegraded {}).emit(&app) {
+                                tracing::error!("broadcast_error: {:?}", e);
+                            }
+                            continue;
+                        }
+                        ListenEvent::Reconnected => {
+                            if let Err(e) = (SessionEvent::TranscriptionRestored {}).emit(&app) {
+                                tracing::error!("broadcast_error: {:?}", e);
+                            }
+                            continue;
+                        }
+                        ListenEvent::Failed => {
+                            if let Err(e) = (SessionEvent::TranscriptionFailed {}).emit(&app) {
+                                tracing::error!("broadcast_error: {:?}", e);
+                            }
+                            continue;
+                        }
+                    };
+
+                    // Interim results are replaced by later ones, so they are only shown, never saved.
+                    if !result.is_final {
+                        if let Err(e) = (SessionEvent::PartialWords {
+                            words: result.words,
+                        })
+                        .emit(&app)
+                        {
+                            tracing::error!("broadcast_error: {:?}", e);
+                        }
+                        continue;
+                    }
+
                     {
-                        let updated_words = update_session(&app, &session.id, result.words)
-                            .await
-                            .unwrap();
+                        use tauri_plugin_db::DatabasePluginExt;
 
-                        SessionEvent::Words {
-                            words: updated_words,
+                        if let Err(e) = app.db_append_words(&session.id, result.words.clone()).await
+                        {
+                            tracing::error!("append_words_error: {:?}", e);
                         }
-                        .emit(&app)
                     }
-                    .unwrap();
+
+                    if let Err(e) = (SessionEvent::Words {
+                        words: result.words,
+                    })
+                    .emit(&app)
+                    {
+                        tracing::error!("broadcast_error: {:?}", e);
+                    }
                 }
 
                 tracing::info!("listen_stream_ended");
@@ -295,6 +466,9 @@ impl Session {
     #[tracing::instrument(skip_all)]
     async fn teardown_resources(&mut self) {
         self.session_id = None;
+        self.mic_device_tx = None;
+        self.echo_cancellation_tx = None;
+        self.echo_cancellation_rx = None;
 
         if let Some(tx) = self.silence_stream_tx.take() {
             let _ = tx.send(());
@@ -306,6 +480,11 @@ impl Session {
                 let _ = res;
             }
         }
+
+        // Aborting the tasks above drops every sender, so the recorder drains its buffer and finalizes.
+        if let Some(task) = self.recorder_task.take() {
+            let _ = task.await;
+        }
     }
 
     pub fn is_mic_muted(&self) -> bool {
@@ -315,6 +494,13 @@ impl Session {
         }
     }
 
+    pub fn is_echo_cancellation_enabled(&self) -> bool {
+        match &self.echo_cancellation_rx {
+            Some(rx) => *rx.borrow(),
+            None => false,
+        }
+    }
+
     pub fn is_speaker_muted(&self) -> bool {
         match &self.speaker_muted_rx {
             Some(rx) => *rx.borrow(),
@@ -327,6 +513,7 @@ async fn setup_listen_client<R: tauri::Runtime>(
     app: &tauri::AppHandle<R>,
     language: hypr_language::Language,
     jargons: Vec<String>,
+    offset_ms: u64,
 ) -> Result<crate::client::ListenClient, crate::Error> {
     let api_base = {
         use tauri_plugin_connector::{Connection, ConnectorPluginExt};
@@ -357,29 +544,86 @@ async fn setup_listen_client<R: tauri::Runtime>(
         .params(hypr_listener_interface::ListenParams {
             language,
             static_prompt,
+            offset_ms,
+            // Only a request: the client falls back to JSON unless the server acknowledges it.
+            audio_format: hypr_listener_interface::AudioFormat::Opus,
             ..Default::default()
         })
         .build())
 }
 
-async fn update_session<R: tauri::Runtime>(
+fn open_mic_stream<R: tauri::Runtime>(
     app: &tauri::AppHandle<R>,
-    session_id: impl Into<String>,
-    words: Vec<hypr_listener_interface::Word>,
-) -> Result<Vec<hypr_listener_interface::Word>, crate::Error> {
-    use tauri_plugin_db::DatabasePluginExt;
-
-    // TODO: not ideal. We might want to only do "update" everywhere instead of upserts.
-    // We do this because it is highly likely that the session fetched in the listener is stale (session can be updated on the React side).
-    let mut session = app
-        .db_get_session(session_id)
-        .await?
-        .ok_or(crate::Error::NoneSession)?;
-
-    session.words.extend(words);
-    app.db_upsert_session(session.clone()).await.unwrap();
-
-    Ok(session.words)
+    device: Option<&str>,
+) -> Result<impl futures_util::Stream<Item = Vec<f32>> + Send + Unpin + 'static, crate::Error> {
+    let mut input = hypr_audio::AudioInput::from_mic_device(device)?;
+    let name = input.mic_device_name().unwrap_or_default();
+    tracing::info!(device = ?name, "mic_stream_opened");
+
+    if let Err(e) = (SessionEvent::MicDevice { name }).emit(app) {
+        tracing::error!("broadcast_error: {:?}", e);
+    }
+
+    Ok(input.stream().resample(SAMPLE_RATE).chunks(1024))
+}
+
+fn open_recorder(dir: &std::path::Path) -> Result<hypr_recorder::Recorder, crate::Error> {
+    let recorder = hypr_recorder::Recorder::open(
+        dir,
+        hypr_recorder::RecorderConfig {
+            sample_rate: SAMPLE_RATE,
+            channels: 2,
+            ..Default::default()
+        },
+    )?;
+    Ok(recorder)
+}
+
+fn run_recorder(
+    mut recorder: hypr_recorder::Recorder,
+    dir: std::path::PathBuf,
+    mut save_rx: mpsc::Receiver<RecorderInput>,
+) -> Result<(), crate::Error> {
+    let mut manifest = crate::RecordingManifest::load(&dir)
+        .unwrap_or_else(|e| {
+            tracing::warn!("manifest_load_error: {:?}", e);
+            None
+        })
+        .unwrap_or_else(|| crate::RecordingManifest::new(SAMPLE_RATE));
+
+    manifest.start_segment(recorder.frames());
+    manifest.save(&dir)?;
+
+    let mut interleaved = Vec::new();
+
+    while let Some(input) = save_rx.blocking_recv() {
+        match input {
+            // Left channel is the mic ("me"), right channel is the speaker ("them").
+            RecorderInput::Frames(mic, speaker) => {
+                interleaved.clear();
+                for (m, s) in mic.into_iter().zip(speaker.into_iter()) {
+                    interleaved.push(m);
+                    interleaved.push(s);
+                }
+                recorder.write(&interleaved)?;
+            }
+            RecorderInput::Pause => {
+                recorder.flush()?;
+                manifest.end_segment();
+                manifest.save(&dir)?;
+            }
+            RecorderInput::Resume => {
+                manifest.start_segment(recorder.frames());
+                manifest.save(&dir)?;
+            }
+        }
+    }
+
+    manifest.end_segment();
+    manifest.save(&dir)?;
+    recorder.finalize()?;
+
+    Ok(())
 }
 
 pub enum StateEvent {
@@ -389,6 +633,8 @@ pub enum StateEvent {
     Resume,
     MicMuted(bool),
     SpeakerMuted(bool),
+    MicDevice(Option<String>),
+    EchoCancellation(bool),
 }
 
 #[state_machine(
@@ -412,6 +658,18 @@ impl Session {
                 }
                 Handled
             }
+            StateEvent::EchoCancellation(enabled) => {
+                if let Some(tx) = &self.echo_cancellation_tx {
+                    let _ = tx.send(*enabled);
+                }
+                Handled
+            }
+            StateEvent::MicDevice(device) => {
+                if let Some(tx) = &self.mic_device_tx {
+                    let _ = tx.send(device.clone());
+                }
+                Handled
+            }
             _ => Super,
         }
     }
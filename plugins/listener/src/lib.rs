@@ -8,11 +8,14 @@ mod error;
 mod events;
 mod ext;
 mod fsm;
+mod manifest;
+mod replay;
 
 pub use client::*;
 pub use error::*;
 pub use events::*;
 pub use ext::ListenerPluginExt;
+pub use manifest::*;
 
 pub use hypr_listener_interface::*;
 
@@ -29,6 +32,8 @@ fn make_specta_builder<R: tauri::Runtime>() -> tauri_specta::Builder<R> {
         .plugin_name(PLUGIN_NAME)
         .commands(tauri_specta::collect_commands![
             commands::list_microphone_devices::<tauri::Wry>,
+            commands::get_mic_device::<tauri::Wry>,
+            commands::set_mic_device::<tauri::Wry>,
             commands::check_microphone_access::<tauri::Wry>,
             commands::check_system_audio_access::<tauri::Wry>,
             commands::request_microphone_access::<tauri::Wry>,
@@ -39,6 +44,8 @@ fn make_specta_builder<R: tauri::Runtime>() -> tauri_specta::Builder<R> {
             commands::set_mic_muted::<tauri::Wry>,
             commands::get_speaker_muted::<tauri::Wry>,
             commands::set_speaker_muted::<tauri::Wry>,
+            commands::get_echo_cancellation::<tauri::Wry>,
+            commands::set_echo_cancellation::<tauri::Wry>,
             commands::start_session::<tauri::Wry>,
             commands::stop_session::<tauri::Wry>,
             commands::pause_session::<tauri::Wry>,
@@ -0,0 +1,110 @@
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+pub const MANIFEST_FILE_NAME: &str = "audio.json";
+
+const MANIFEST_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RecordingChannel {
+    Mic,
+    Speaker,
+}
+
+/// A contiguous run of recorded audio. Pausing closes the current segment, resuming opens a new one.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordingSegment {
+    /// Position of the segment's first frame in the recording, in milliseconds.
+    pub offset_ms: u64,
+    pub started_at: DateTime<Utc>,
+    pub ended_at: Option<DateTime<Utc>>,
+}
+
+/// Sidecar for the session recording describing how it maps onto wall-clock time.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordingManifest {
+    pub version: u32,
+    pub started_at: DateTime<Utc>,
+    pub sample_rate: u32,
+    /// Channel layout of the recorded segments, in interleaving order.
+    pub channels: Vec<RecordingChannel>,
+    pub segments: Vec<RecordingSegment>,
+}
+
+impl RecordingManifest {
+    pub fn new(sample_rate: u32) -> Self {
+        Self {
+            version: MANIFEST_VERSION,
+            started_at: Utc::now(),
+            sample_rate,
+            channels: vec![RecordingChannel::Mic, RecordingChannel::Speaker],
+            segments: vec![],
+        }
+    }
+
+    pub fn path(dir: impl AsRef<Path>) -> PathBuf {
+        dir.as_ref().join(MANIFEST_FILE_NAME)
+    }
+
+    pub fn load(dir: impl AsRef<Path>) -> Result<Option<Self>, crate::Error> {
+        let path = Self::path(dir);
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        let content = std::fs::read_to_string(path)?;
+        Ok(Some(serde_json::from_str(&content)?))
+    }
+
+    pub fn save(&self, dir: impl AsRef<Path>) -> Result<(), crate::Error> {
+        let path = Self::path(dir);
+        let tmp = path.with_extension("json.tmp");
+
+        std::fs::write(&tmp, serde_json::to_string_pretty(self)?)?;
+        std::fs::rename(tmp, path)?;
+        Ok(())
+    }
+
+    pub fn frames_to_ms(&self, frames: u64) -> u64 {
+        frames * 1000 / self.sample_rate as u64
+    }
+
+    pub fn start_segment(&mut self, offset_frames: u64) {
+        self.end_segment();
+        self.segments.push(RecordingSegment {
+            offset_ms: self.frames_to_ms(offset_frames),
+            started_at: Utc::now(),
+            ended_at: None,
+        });
+    }
+
+    pub fn end_segment(&mut self) {
+        if let Some(segment) = self.segments.last_mut() {
+            if segment.ended_at.is_none() {
+                segment.ended_at = Some(Utc::now());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_segments() {
+        let mut manifest = RecordingManifest::new(16000);
+
+        manifest.start_segment(0);
+        manifest.start_segment(16000 * 3);
+        manifest.end_segment();
+
+        assert_eq!(manifest.segments.len(), 2);
+        assert_eq!(manifest.segments[0].offset_ms, 0);
+        assert_eq!(manifest.segments[1].offset_ms, 3000);
+        assert!(manifest.segments.iter().all(|s| s.ended_at.is_some()));
+    }
+}
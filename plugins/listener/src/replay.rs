@@ -0,0 +1,118 @@
+use std::collections::VecDeque;
+
+use bytes::Bytes;
+
+const SAMPLE_RATE: u64 = 16 * 1000;
+
+/// Audio already sent to the server but not yet covered by a final transcript.
+///
+/// Positions are in samples since the start of the session recording, so they line up with the
+/// `start_ms` / `end_ms` of the words the server sends back.
+pub struct ReplayBuffer {
+    chunks: VecDeque<BufferedChunk>,
+    capacity: u64,
+    next: u64,
+    acked_ms: u64,
+}
+
+struct BufferedChunk {
+    start: u64,
+    end: u64,
+    data: Bytes,
+}
+
+impl ReplayBuffer {
+    pub fn new(offset_ms: u64, capacity_ms: u64) -> Self {
+        Self {
+            chunks: VecDeque::new(),
+            capacity: ms_to_samples(capacity_ms),
+            next: ms_to_samples(offset_ms),
+            acked_ms: offset_ms,
+        }
+    }
+
+    pub fn push(&mut self, data: Bytes, samples: u64) {
+        let start = self.next;
+        self.next += samples;
+        self.chunks.push_back(BufferedChunk {
+            start,
+            end: self.next,
+            data,
+        });
+
+        while self
+            .chunks
+            .front()
+            .is_some_and(|chunk| self.next - chunk.start > self.capacity)
+        {
+            // Also the steady state for servers that don't report word timestamps, as nothing is
+            // ever acknowledged. The recording still has the audio either way.
+            let dropped = self.chunks.pop_front().unwrap();
+            tracing::debug!("replay_buffer_overflow: {}ms", samples_to_ms(dropped.start));
+        }
+    }
+
+    /// Drops the audio the server has finished transcribing.
+    pub fn ack(&mut self, end_ms: u64) {
+        self.acked_ms = self.acked_ms.max(end_ms);
+
+        let acked = ms_to_samples(self.acked_ms);
+        while self.chunks.front().is_some_and(|chunk| chunk.end <= acked) {
+            self.chunks.pop_front();
+        }
+    }
+
+    pub fn acked_ms(&self) -> u64 {
+        self.acked_ms
+    }
+
+    /// Where a new connection has to start, and the audio to send it before any new audio.
+    pub fn replay(&self) -> (u64, Vec<Bytes>) {
+        let start = self.chunks.front().map_or(self.next, |chunk| chunk.start);
+        let chunks = self.chunks.iter().map(|chunk| chunk.data.clone()).collect();
+        (samples_to_ms(start), chunks)
+    }
+}
+
+fn ms_to_samples(ms: u64) -> u64 {
+    ms * SAMPLE_RATE / 1000
+}
+
+fn samples_to_ms(samples: u64) -> u64 {
+    samples * 1000 / SAMPLE_RATE
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chunk(i: u8) -> Bytes {
+        Bytes::from(vec![i])
+    }
+
+    #[test]
+    fn test_replay_buffer() {
+        let mut buffer = ReplayBuffer::new(1000, 200);
+        assert_eq!(buffer.replay(), (1000, vec![]));
+
+        // 20ms each.
+        for i in 0..5 {
+            buffer.push(chunk(i), 320);
+        }
+        assert_eq!(buffer.replay(), (1000, (0..5).map(chunk).collect()));
+
+        // Chunks straddling the acknowledged position are kept.
+        buffer.ack(1050);
+        assert_eq!(buffer.acked_ms(), 1050);
+        assert_eq!(buffer.replay(), (1040, (2..5).map(chunk).collect()));
+
+        // Acknowledgements never go backwards.
+        buffer.ack(1000);
+        assert_eq!(buffer.acked_ms(), 1050);
+
+        for i in 5..15 {
+            buffer.push(chunk(i), 320);
+        }
+        assert_eq!(buffer.replay(), (1100, (5..15).map(chunk).collect()));
+    }
+}
@@ -6,6 +6,11 @@ const COMMANDS: &[&str] = &[
     "start_server",
     "stop_server",
     "list_ollama_models",
+    "is_embedding_model_downloaded",
+    "download_embedding_model",
+    "index_session_embeddings",
+    "index_pending_embeddings",
+    "semantic_search",
 ];
 
 fn main() {
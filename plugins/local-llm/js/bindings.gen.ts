@@ -27,6 +27,21 @@ async stopServer() : Promise<null> {
 },
 async listOllamaModels() : Promise<string[]> {
     return await TAURI_INVOKE("plugin:local-llm|list_ollama_models");
+},
+async isEmbeddingModelDownloaded() : Promise<boolean> {
+    return await TAURI_INVOKE("plugin:local-llm|is_embedding_model_downloaded");
+},
+async downloadEmbeddingModel(channel: TAURI_CHANNEL<number>) : Promise<null> {
+    return await TAURI_INVOKE("plugin:local-llm|download_embedding_model", { channel });
+},
+async indexSessionEmbeddings(sessionId: string) : Promise<null> {
+    return await TAURI_INVOKE("plugin:local-llm|index_session_embeddings", { sessionId });
+},
+async indexPendingEmbeddings() : Promise<null> {
+    return await TAURI_INVOKE("plugin:local-llm|index_pending_embeddings");
+},
+async semanticSearch(query: string, limit: number | null) : Promise<SemanticSearchResult[]> {
+    return await TAURI_INVOKE("plugin:local-llm|semantic_search", { query, limit });
 }
 }
 
@@ -40,7 +55,29 @@ async listOllamaModels() : Promise<string[]> {
 
 /** user-defined types **/
 
+export type SearchField = "title" | "rawMemo" | "enhancedMemo" | "transcript"
+export type SemanticMatch = { chunk: SessionChunk; 
+/**
+ * Cosine similarity with the query, higher is closer.
+ */
+score: number }
+export type SemanticSearchResult = { session: Session; 
+/**
+ * Closest chunks of the session, closest first.
+ */
+matches: SemanticMatch[] }
+export type Session = { id: string; created_at: string; visited_at: string; user_id: string; calendar_event_id: string | null; title: string; raw_memo_html: string; enhanced_memo_html: string | null; words: Word[] }
+/**
+ * A span of a session small enough to be embedded on its own.
+ */
+export type SessionChunk = { field: SearchField; text: string; 
+/**
+ * Only set for transcript chunks with timestamps.
+ */
+start_ms: number | null; end_ms: number | null }
+export type SpeakerIdentity = { type: "unassigned"; value: { index: number } } | { type: "assigned"; value: { id: string; label: string } }
 export type TAURI_CHANNEL<TSend> = null
+export type Word = { text: string; speaker: SpeakerIdentity | null; confidence: number | null; start_ms: number | null; end_ms: number | null }
 
 /** tauri-specta globals **/
 
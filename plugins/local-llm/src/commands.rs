@@ -59,3 +59,55 @@ pub async fn list_ollama_models<R: tauri::Runtime>(
 
     Ok(models.into_iter().map(|m| m.name).collect::<Vec<_>>())
 }
+
+#[tauri::command]
+#[specta::specta]
+pub async fn is_embedding_model_downloaded<R: tauri::Runtime>(
+    app: tauri::AppHandle<R>,
+) -> Result<bool, String> {
+    Ok(app.is_embedding_model_downloaded().await)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn download_embedding_model<R: tauri::Runtime>(
+    app: tauri::AppHandle<R>,
+    channel: Channel<i8>,
+) -> Result<(), String> {
+    app.download_embedding_model(channel)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn index_session_embeddings<R: tauri::Runtime>(
+    app: tauri::AppHandle<R>,
+    session_id: String,
+) -> Result<(), String> {
+    app.index_session_embeddings(session_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn index_pending_embeddings<R: tauri::Runtime>(
+    app: tauri::AppHandle<R>,
+) -> Result<(), String> {
+    app.index_pending_embeddings()
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn semantic_search<R: tauri::Runtime>(
+    app: tauri::AppHandle<R>,
+    query: String,
+    limit: Option<u8>,
+) -> Result<Vec<hypr_db_user::SemanticSearchResult>, String> {
+    app.semantic_search(query, limit)
+        .await
+        .map_err(|e| e.to_string())
+}
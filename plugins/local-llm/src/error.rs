@@ -14,8 +14,14 @@ pub enum Error {
     IoError(#[from] std::io::Error),
     #[error(transparent)]
     StoreError(#[from] tauri_plugin_store2::Error),
+    #[error(transparent)]
+    DatabaseError(#[from] tauri_plugin_db::Error),
     #[error("Model not downloaded")]
     ModelNotDownloaded,
+    #[error("Session not found")]
+    NoneSession,
+    #[error("User not found")]
+    NoneUser,
 }
 
 impl Serialize for Error {
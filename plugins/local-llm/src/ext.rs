@@ -2,6 +2,7 @@ use std::future::Future;
 
 use hypr_file::{download_file_with_callback, DownloadProgress};
 use tauri::{ipc::Channel, Manager, Runtime};
+use tauri_plugin_db::DatabasePluginExt;
 use tauri_plugin_store2::StorePluginExt;
 
 pub trait LocalLlmPluginExt<R: Runtime> {
@@ -17,6 +18,21 @@ pub trait LocalLlmPluginExt<R: Runtime> {
     ) -> impl Future<Output = Result<(), crate::Error>>;
     fn start_server(&self) -> impl Future<Output = Result<String, crate::Error>>;
     fn stop_server(&self) -> impl Future<Output = Result<(), crate::Error>>;
+    fn is_embedding_model_downloaded(&self) -> impl Future<Output = bool>;
+    fn download_embedding_model(
+        &self,
+        channel: Channel<i8>,
+    ) -> impl Future<Output = Result<(), crate::Error>>;
+    fn index_session_embeddings(
+        &self,
+        session_id: String,
+    ) -> impl Future<Output = Result<(), crate::Error>>;
+    fn index_pending_embeddings(&self) -> impl Future<Output = Result<(), crate::Error>>;
+    fn semantic_search(
+        &self,
+        query: String,
+        limit: Option<u8>,
+    ) -> impl Future<Output = Result<Vec<hypr_db_user::SemanticSearchResult>, crate::Error>>;
 }
 
 impl<R: Runtime, T: Manager<R>> LocalLlmPluginExt<R> for T {
@@ -143,4 +159,145 @@ impl<R: Runtime, T: Manager<R>> LocalLlmPluginExt<R> for T {
         }
         Ok(())
     }
+
+    #[tracing::instrument(skip_all)]
+    async fn is_embedding_model_downloaded(&self) -> bool {
+        let state = self.state::<crate::SharedState>();
+        let s = state.lock().await;
+        s.embedding_model_path.exists()
+    }
+
+    #[tracing::instrument(skip_all)]
+    async fn download_embedding_model(&self, channel: Channel<i8>) -> Result<(), crate::Error> {
+        let model = crate::SupportedEmbeddingModel::NomicEmbedTextV1p5Q8;
+        let data_dir = self.path().app_data_dir().unwrap();
+
+        let path = model.model_path(data_dir);
+        let url = model.model_url().to_string();
+
+        let task = tokio::spawn(async move {
+            let callback = |progress: DownloadProgress| match progress {
+                DownloadProgress::Started => {
+                    let _ = channel.send(0);
+                }
+                DownloadProgress::Progress(downloaded, total_size) => {
+                    let percent = (downloaded as f64 / total_size as f64) * 100.0;
+                    let _ = channel.send(percent as i8);
+                }
+                DownloadProgress::Finished => {
+                    let _ = channel.send(100);
+                }
+            };
+
+            // Only complete files are moved into place, so `is_embedding_model_downloaded`
+            // doesn't need to know the size.
+            let partial_path = path.with_extension("gguf.part");
+            let result = match download_file_with_callback(url, &partial_path, callback).await {
+                Ok(()) => std::fs::rename(&partial_path, &path).map_err(crate::Error::from),
+                Err(e) => Err(e.into()),
+            };
+
+            if let Err(e) = result {
+                tracing::error!("embedding_model_download_error: {}", e);
+                let _ = channel.send(-1);
+            }
+        });
+
+        {
+            let state = self.state::<crate::SharedState>();
+            let mut s = state.lock().await;
+
+            if let Some(task) = s.embedding_download_task.take() {
+                task.abort();
+            }
+            s.embedding_download_task = Some(task);
+        }
+
+        Ok(())
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn index_session_embeddings(&self, session_id: String) -> Result<(), crate::Error> {
+        let model = crate::SupportedEmbeddingModel::NomicEmbedTextV1p5Q8;
+
+        let session = self
+            .db_get_session(&session_id)
+            .await?
+            .ok_or(crate::Error::NoneSession)?;
+
+        let chunks = hypr_db_user::chunk_session(&session);
+        let embeddings = if chunks.is_empty() {
+            vec![]
+        } else {
+            let texts = chunks
+                .iter()
+                .map(|chunk| format!("{}{}", model.document_prefix(), chunk.text))
+                .collect();
+            embedding_model(self).await?.embed(texts).await?
+        };
+
+        self.db_replace_session_embeddings(
+            session_id,
+            model.name(),
+            chunks.into_iter().zip(embeddings).collect(),
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    #[tracing::instrument(skip_all)]
+    async fn index_pending_embeddings(&self) -> Result<(), crate::Error> {
+        let model = crate::SupportedEmbeddingModel::NomicEmbedTextV1p5Q8;
+        let user_id = self.db_user_id().await?.ok_or(crate::Error::NoneUser)?;
+
+        let session_ids = self
+            .db_list_sessions_without_embeddings(user_id, model.name())
+            .await?;
+
+        for session_id in session_ids {
+            self.index_session_embeddings(session_id).await?;
+        }
+        Ok(())
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn semantic_search(
+        &self,
+        query: String,
+        limit: Option<u8>,
+    ) -> Result<Vec<hypr_db_user::SemanticSearchResult>, crate::Error> {
+        let model = crate::SupportedEmbeddingModel::NomicEmbedTextV1p5Q8;
+        let user_id = self.db_user_id().await?.ok_or(crate::Error::NoneUser)?;
+
+        let text = format!("{}{}", model.query_prefix(), query.trim());
+        let embedding = embedding_model(self)
+            .await?
+            .embed(vec![text])
+            .await?
+            .pop()
+            .unwrap_or_default();
+
+        let results = self
+            .db_semantic_search_sessions(user_id, model.name(), embedding, limit)
+            .await?;
+        Ok(results)
+    }
+}
+
+// Loaded on first use, and unloaded again after a while without use, like the chat model.
+async fn embedding_model<R: Runtime, T: Manager<R>>(
+    app: &T,
+) -> Result<std::sync::Arc<hypr_llama::Llama>, crate::Error> {
+    let manager = {
+        let state = app.state::<crate::SharedState>();
+        let mut guard = state.lock().await;
+        let s = &mut *guard;
+
+        s.embedding_model
+            .get_or_insert_with(|| crate::ModelManager::new(s.embedding_model_path.clone()))
+            .clone()
+    };
+
+    manager.get_model().await
 }
@@ -27,15 +27,21 @@ pub struct State {
     pub server: Option<crate::server::ServerHandle>,
     pub model_path: std::path::PathBuf,
     pub download_task: Option<tokio::task::JoinHandle<()>>,
+    pub embedding_model_path: std::path::PathBuf,
+    pub embedding_model: Option<ModelManager>,
+    pub embedding_download_task: Option<tokio::task::JoinHandle<()>>,
 }
 
 impl State {
-    pub fn new(model_path: std::path::PathBuf) -> Self {
+    pub fn new(model_path: std::path::PathBuf, embedding_model_path: std::path::PathBuf) -> Self {
         Self {
             api_base: None,
             server: None,
             model_path,
             download_task: None,
+            embedding_model_path,
+            embedding_model: None,
+            embedding_download_task: None,
         }
     }
 }
@@ -51,6 +57,11 @@ fn make_specta_builder<R: tauri::Runtime>() -> tauri_specta::Builder<R> {
             commands::start_server::<Wry>,
             commands::stop_server::<Wry>,
             commands::list_ollama_models::<Wry>,
+            commands::is_embedding_model_downloaded::<Wry>,
+            commands::download_embedding_model::<Wry>,
+            commands::index_session_embeddings::<Wry>,
+            commands::index_pending_embeddings::<Wry>,
+            commands::semantic_search::<Wry>,
         ])
         .error_handling(tauri_specta::ErrorHandlingMode::Throw)
 }
@@ -61,8 +72,13 @@ pub fn init<R: tauri::Runtime>() -> tauri::plugin::TauriPlugin<R> {
     tauri::plugin::Builder::new(PLUGIN_NAME)
         .invoke_handler(specta_builder.invoke_handler())
         .setup(|app, _api| {
-            let model_path = app.path().app_data_dir().unwrap().join("llm.gguf");
-            let state: SharedState = Arc::new(Mutex::new(State::new(model_path)));
+            let data_dir = app.path().app_data_dir().unwrap();
+            let model_path = data_dir.join("llm.gguf");
+            let embedding_model_path =
+                SupportedEmbeddingModel::NomicEmbedTextV1p5Q8.model_path(&data_dir);
+
+            let state: SharedState =
+                Arc::new(Mutex::new(State::new(model_path, embedding_model_path)));
             app.manage(state);
             Ok(())
         })
@@ -24,3 +24,42 @@ impl SupportedModel {
         }
     }
 }
+
+/// Embeds sessions for semantic search. Runs next to the chat model, not instead of it.
+#[derive(serde::Serialize, serde::Deserialize, specta::Type)]
+pub enum SupportedEmbeddingModel {
+    NomicEmbedTextV1p5Q8,
+}
+
+impl SupportedEmbeddingModel {
+    pub fn model_path(&self, data_dir: impl Into<std::path::PathBuf>) -> std::path::PathBuf {
+        match self {
+            SupportedEmbeddingModel::NomicEmbedTextV1p5Q8 => data_dir.into().join("embed.gguf"),
+        }
+    }
+
+    pub fn model_url(&self) -> &str {
+        match self {
+            SupportedEmbeddingModel::NomicEmbedTextV1p5Q8 => "https://huggingface.co/nomic-ai/nomic-embed-text-v1.5-GGUF/resolve/main/nomic-embed-text-v1.5.Q8_0.gguf"
+        }
+    }
+
+    /// Stored with every embedding, so vectors of different models are never compared.
+    pub fn name(&self) -> &str {
+        match self {
+            SupportedEmbeddingModel::NomicEmbedTextV1p5Q8 => "nomic-embed-text-v1.5",
+        }
+    }
+
+    pub fn document_prefix(&self) -> &str {
+        match self {
+            SupportedEmbeddingModel::NomicEmbedTextV1p5Q8 => "search_document: ",
+        }
+    }
+
+    pub fn query_prefix(&self) -> &str {
+        match self {
+            SupportedEmbeddingModel::NomicEmbedTextV1p5Q8 => "search_query: ",
+        }
+    }
+}
@@ -8,6 +8,10 @@ const COMMANDS: &[&str] = &[
     "get_current_model",
     "set_current_model",
     "list_supported_models",
+    "diarize_session",
+    "enroll_speaker",
+    "transcribe_session",
+    "import_session",
 ];
 
 fn main() {
@@ -33,6 +33,18 @@ async setCurrentModel(model: SupportedModel) : Promise<null> {
 },
 async listSupportedModels() : Promise<SupportedModel[]> {
     return await TAURI_INVOKE("plugin:local-stt|list_supported_models");
+},
+async diarizeSession(sessionId: string) : Promise<Word[]> {
+    return await TAURI_INVOKE("plugin:local-stt|diarize_session", { sessionId });
+},
+async enrollSpeaker(sessionId: string, speakerIndex: number, humanId: string) : Promise<Word[]> {
+    return await TAURI_INVOKE("plugin:local-stt|enroll_speaker", { sessionId, speakerIndex, humanId });
+},
+async transcribeSession(sessionId: string, model: SupportedModel, channel: TAURI_CHANNEL<number>) : Promise<Word[]> {
+    return await TAURI_INVOKE("plugin:local-stt|transcribe_session", { sessionId, model, channel });
+},
+async importSession(path: string, channel: TAURI_CHANNEL<number>) : Promise<Session> {
+    return await TAURI_INVOKE("plugin:local-stt|import_session", { path, channel });
 }
 }
 
@@ -46,8 +58,11 @@ async listSupportedModels() : Promise<SupportedModel[]> {
 
 /** user-defined types **/
 
+export type Session = { id: string; created_at: string; visited_at: string; user_id: string; calendar_event_id: string | null; title: string; raw_memo_html: string; enhanced_memo_html: string | null; words: Word[] }
+export type SpeakerIdentity = { type: "unassigned"; value: { index: number } } | { type: "assigned"; value: { id: string; label: string } }
 export type SupportedModel = "QuantizedTiny" | "QuantizedBase" | "QuantizedSmall" | "QuantizedLargeTurbo"
 export type TAURI_CHANNEL<TSend> = null
+export type Word = { text: string; speaker: SpeakerIdentity | null; confidence: number | null; start_ms: number | null; end_ms: number | null }
 
 /** tauri-specta globals **/
 
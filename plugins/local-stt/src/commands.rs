@@ -74,3 +74,52 @@ pub fn set_current_model<R: tauri::Runtime>(
 ) -> Result<(), String> {
     app.set_current_model(model).map_err(|e| e.to_string())
 }
+
+#[tauri::command]
+#[specta::specta]
+pub async fn diarize_session<R: tauri::Runtime>(
+    app: tauri::AppHandle<R>,
+    session_id: String,
+) -> Result<Vec<hypr_listener_interface::Word>, String> {
+    app.diarize_session(session_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn enroll_speaker<R: tauri::Runtime>(
+    app: tauri::AppHandle<R>,
+    session_id: String,
+    speaker_index: u8,
+    human_id: String,
+) -> Result<Vec<hypr_listener_interface::Word>, String> {
+    app.enroll_speaker(session_id, speaker_index, human_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn transcribe_session<R: tauri::Runtime>(
+    app: tauri::AppHandle<R>,
+    session_id: String,
+    model: crate::SupportedModel,
+    channel: Channel<i8>,
+) -> Result<Vec<hypr_listener_interface::Word>, String> {
+    app.transcribe_session(session_id, model, channel)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn import_session<R: tauri::Runtime>(
+    app: tauri::AppHandle<R>,
+    path: String,
+    channel: Channel<i8>,
+) -> Result<hypr_db_user::Session, String> {
+    app.import_session(path, channel)
+        .await
+        .map_err(|e| e.to_string())
+}
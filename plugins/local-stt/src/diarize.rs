@@ -0,0 +1,216 @@
+use std::path::Path;
+
+use hypr_listener_interface::{SpeakerIdentity, Word};
+use hypr_pyannote::local::{
+    diarization::{speaker_at, DiarizationConfig, Diarizer, SpeakerTurn},
+    embedding::EmbeddingExtractor,
+    voiceprint::VoiceprintMatcher,
+};
+
+pub fn to_i16(samples: &[f32]) -> Vec<i16> {
+    samples
+        .iter()
+        .map(|&s| (s.clamp(-1.0, 1.0) * i16::MAX as f32) as i16)
+        .collect()
+}
+
+/// Identity of each diarized speaker: the matched voiceprint if any, `Unassigned` otherwise.
+pub fn speaker_identities<E: AsRef<[f32]>>(
+    matcher: &VoiceprintMatcher,
+    speakers: &[E],
+) -> Vec<SpeakerIdentity> {
+    matcher
+        .assign(speakers)
+        .into_iter()
+        .enumerate()
+        .map(|(index, candidate)| match candidate {
+            Some(c) => SpeakerIdentity::Assigned {
+                id: c.id.clone(),
+                label: c.label.clone(),
+            },
+            None => SpeakerIdentity::Unassigned {
+                index: index.min(u8::MAX as usize) as u8,
+            },
+        })
+        .collect()
+}
+
+/// Labels each word with the speaker whose turn overlaps it the most. `offset_ms` is where the
+/// diarized audio starts on the words' timeline. Speakers assigned by the user are kept.
+pub fn assign_speakers(
+    words: &mut [Word],
+    turns: &[SpeakerTurn],
+    identities: &[SpeakerIdentity],
+    offset_ms: u64,
+) {
+    for word in words {
+        if matches!(word.speaker, Some(SpeakerIdentity::Assigned { .. })) {
+            continue;
+        }
+
+        let (Some(start_ms), Some(end_ms)) = (word.start_ms, word.end_ms) else {
+            continue;
+        };
+
+        let start = start_ms.saturating_sub(offset_ms) as f64 / 1000.0;
+        let end = end_ms.saturating_sub(offset_ms) as f64 / 1000.0;
+        word.speaker = speaker_at(turns, start, end).and_then(|i| identities.get(i).cloned());
+    }
+}
+
+/// Post-session pass over the whole recording in `dir`. Clustering every turn at once fixes
+/// speakers that the realtime pass split or merged before it had heard enough of them.
+pub fn diarize_recording(
+    dir: impl AsRef<Path>,
+    words: &mut [Word],
+    matcher: &VoiceprintMatcher,
+) -> Result<(), crate::Error> {
+    let recording = hypr_recorder::read_mono(dir)?;
+    diarize_samples(recording.sample_rate, &recording.samples, words, matcher)
+}
+
+/// Same as [`diarize_recording`], for samples already in memory. They start at 0 on the words'
+/// timeline.
+pub fn diarize_samples(
+    sample_rate: u32,
+    samples: &[f32],
+    words: &mut [Word],
+    matcher: &VoiceprintMatcher,
+) -> Result<(), crate::Error> {
+    if samples.is_empty() {
+        return Ok(());
+    }
+
+    let mut diarizer = Diarizer::new(sample_rate, DiarizationConfig::default())?;
+    let diarization = diarizer.diarize(&to_i16(samples))?;
+
+    let identities = speaker_identities(matcher, &diarization.speakers);
+    assign_speakers(words, &diarization.turns, &identities, 0);
+    Ok(())
+}
+
+/// Voiceprint of an unassigned speaker, computed from the audio under their words.
+pub fn speaker_voiceprint(
+    dir: impl AsRef<Path>,
+    words: &[Word],
+    index: u8,
+) -> Result<Vec<f32>, crate::Error> {
+    let recording = hypr_recorder::read_mono(dir)?;
+    let samples = speaker_samples(&recording, words, index);
+    if samples.is_empty() {
+        return Err(crate::Error::SpeakerNotFound(index));
+    }
+
+    let embedding = EmbeddingExtractor::new().compute(&to_i16(&samples))?;
+    Ok(embedding)
+}
+
+fn speaker_samples(
+    recording: &hypr_recorder::MonoRecording,
+    words: &[Word],
+    index: u8,
+) -> Vec<f32> {
+    let to_sample = |ms: u64| {
+        ((ms * recording.sample_rate as u64 / 1000) as usize).min(recording.samples.len())
+    };
+
+    let mut ranges: Vec<(usize, usize)> = words
+        .iter()
+        .filter(|w| w.speaker == Some(SpeakerIdentity::Unassigned { index }))
+        .filter_map(|w| Some((to_sample(w.start_ms?), to_sample(w.end_ms?))))
+        .filter(|(start, end)| start < end)
+        .collect();
+    ranges.sort();
+
+    // Words of the same segment share timestamps, so merge before copying.
+    let mut merged: Vec<(usize, usize)> = Vec::new();
+    for (start, end) in ranges {
+        match merged.last_mut() {
+            Some(last) if start <= last.1 => last.1 = last.1.max(end),
+            _ => merged.push((start, end)),
+        }
+    }
+
+    merged
+        .into_iter()
+        .flat_map(|(start, end)| recording.samples[start..end].iter().copied())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn word(start_ms: u64, end_ms: u64, speaker: Option<SpeakerIdentity>) -> Word {
+        Word {
+            text: "hi".to_string(),
+            speaker,
+            confidence: None,
+            start_ms: Some(start_ms),
+            end_ms: Some(end_ms),
+        }
+    }
+
+    fn unassigned(index: u8) -> SpeakerIdentity {
+        SpeakerIdentity::Unassigned { index }
+    }
+
+    #[test]
+    fn test_assign_speakers() {
+        let turns = vec![
+            SpeakerTurn {
+                start: 0.0,
+                end: 1.0,
+                speaker: 0,
+            },
+            SpeakerTurn {
+                start: 1.0,
+                end: 2.0,
+                speaker: 1,
+            },
+        ];
+
+        let alice = SpeakerIdentity::Assigned {
+            id: "1".to_string(),
+            label: "Alice".to_string(),
+        };
+        let bob = SpeakerIdentity::Assigned {
+            id: "2".to_string(),
+            label: "Bob".to_string(),
+        };
+
+        let mut words = vec![
+            word(10_200, 10_600, None),
+            word(10_900, 11_800, None),
+            word(10_000, 10_500, Some(bob.clone())),
+            word(15_000, 15_500, Some(unassigned(3))),
+        ];
+        assign_speakers(&mut words, &turns, &[unassigned(0), alice.clone()], 10_000);
+
+        assert_eq!(words[0].speaker, Some(unassigned(0)));
+        assert_eq!(words[1].speaker, Some(alice));
+        assert_eq!(words[2].speaker, Some(bob));
+        assert_eq!(words[3].speaker, None);
+    }
+
+    #[test]
+    fn test_speaker_samples() {
+        let recording = hypr_recorder::MonoRecording {
+            sample_rate: 1000,
+            samples: (0..3000).map(|i| i as f32).collect(),
+        };
+
+        let words = vec![
+            word(100, 300, Some(unassigned(0))),
+            word(100, 300, Some(unassigned(0))),
+            word(200, 400, Some(unassigned(0))),
+            word(500, 600, Some(unassigned(1))),
+            word(2900, 3500, Some(unassigned(0))),
+        ];
+
+        let samples = speaker_samples(&recording, &words, 0);
+        assert_eq!(samples.len(), 300 + 100);
+        assert_eq!(samples[0], 100.0);
+        assert_eq!(samples[300], 2900.0);
+    }
+}
@@ -12,8 +12,30 @@ pub enum Error {
     IoError(#[from] std::io::Error),
     #[error(transparent)]
     StoreError(#[from] tauri_plugin_store2::Error),
+    #[error(transparent)]
+    RecorderError(#[from] hypr_recorder::Error),
+    #[error(transparent)]
+    DiarizationError(#[from] anyhow::Error),
+    #[error(transparent)]
+    JoinError(#[from] tokio::task::JoinError),
+    #[error(transparent)]
+    DatabaseError(#[from] tauri_plugin_db::Error),
+    #[error(transparent)]
+    WhisperError(#[from] hypr_whisper::local::Error),
+    #[error(transparent)]
+    ChunkerError(#[from] hypr_chunker::Error),
+    #[error(transparent)]
+    DecodeError(#[from] hypr_audio_utils::DecodeError),
     #[error("Model not downloaded")]
     ModelNotDownloaded,
+    #[error("User not found")]
+    NoneUser,
+    #[error("Session not found")]
+    NoneSession,
+    #[error("Human not found")]
+    NoneHuman,
+    #[error("No audio for speaker {0}")]
+    SpeakerNotFound(u8),
 }
 
 impl Serialize for Error {
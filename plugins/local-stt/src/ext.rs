@@ -1,9 +1,13 @@
+use std::collections::{HashMap, HashSet};
 use std::future::Future;
 
 use tauri::{ipc::Channel, Manager, Runtime};
 use tauri_plugin_store2::StorePluginExt;
 
 use hypr_file::{download_file_with_callback, DownloadProgress};
+use hypr_listener_interface::{SpeakerIdentity, Word};
+use hypr_pyannote::local::voiceprint::{VoiceprintCandidate, VoiceprintMatcher};
+use tauri_plugin_db::DatabasePluginExt;
 
 pub trait LocalSttPluginExt<R: Runtime> {
     fn local_stt_store(&self) -> tauri_plugin_store2::ScopedStore<R, crate::StoreKey>;
@@ -25,6 +29,32 @@ pub trait LocalSttPluginExt<R: Runtime> {
         &self,
         model: &crate::SupportedModel,
     ) -> impl Future<Output = Result<bool, crate::Error>>;
+
+    fn speaker_candidates(
+        &self,
+        session_id: Option<String>,
+    ) -> impl Future<Output = Result<Vec<VoiceprintCandidate>, crate::Error>>;
+    fn diarize_session(
+        &self,
+        session_id: String,
+    ) -> impl Future<Output = Result<Vec<Word>, crate::Error>>;
+    fn enroll_speaker(
+        &self,
+        session_id: String,
+        speaker_index: u8,
+        human_id: String,
+    ) -> impl Future<Output = Result<Vec<Word>, crate::Error>>;
+    fn transcribe_session(
+        &self,
+        session_id: String,
+        model: crate::SupportedModel,
+        channel: Channel<i8>,
+    ) -> impl Future<Output = Result<Vec<Word>, crate::Error>>;
+    fn import_session(
+        &self,
+        path: String,
+        channel: Channel<i8>,
+    ) -> impl Future<Output = Result<hypr_db_user::Session, crate::Error>>;
 }
 
 impl<R: Runtime, T: Manager<R>> LocalSttPluginExt<R> for T {
@@ -78,9 +108,15 @@ impl<R: Runtime, T: Manager<R>> LocalSttPluginExt<R> for T {
             return Err(crate::Error::ModelNotDownloaded);
         }
 
+        let speaker_candidates = self.speaker_candidates(None).await.unwrap_or_else(|e| {
+            tracing::warn!("speaker_candidates_unavailable: {:?}", e);
+            vec![]
+        });
+
         let server_state = crate::ServerStateBuilder::default()
             .model_cache_dir(cache_dir)
             .model_type(model)
+            .speaker_candidates(speaker_candidates)
             .build();
 
         let server = crate::run_server(server_state).await?;
@@ -176,4 +212,277 @@ impl<R: Runtime, T: Manager<R>> LocalSttPluginExt<R> for T {
         store.set(crate::StoreKey::DefaultModel, model)?;
         Ok(())
     }
+
+    #[tracing::instrument(skip_all)]
+    async fn speaker_candidates(
+        &self,
+        session_id: Option<String>,
+    ) -> Result<Vec<VoiceprintCandidate>, crate::Error> {
+        let participants: HashSet<String> = match session_id {
+            Some(id) => self
+                .db_session_list_participants(id)
+                .await?
+                .into_iter()
+                .map(|h| h.id)
+                .collect(),
+            None => HashSet::new(),
+        };
+
+        let mut embeddings: HashMap<String, Vec<Vec<f32>>> = HashMap::new();
+        for voiceprint in self.db_list_voiceprints().await? {
+            embeddings
+                .entry(voiceprint.human_id)
+                .or_default()
+                .push(voiceprint.embedding);
+        }
+
+        let mut candidates = Vec::with_capacity(embeddings.len());
+        for (human_id, embeddings) in embeddings {
+            let Some(human) = self.db_get_human(&human_id).await? else {
+                continue;
+            };
+
+            candidates.push(VoiceprintCandidate {
+                is_participant: participants.contains(&human_id),
+                id: human_id,
+                label: speaker_label(&human),
+                embeddings,
+            });
+        }
+
+        Ok(candidates)
+    }
+
+    #[tracing::instrument(skip_all)]
+    async fn diarize_session(&self, session_id: String) -> Result<Vec<Word>, crate::Error> {
+        let mut session = self
+            .db_get_session(&session_id)
+            .await?
+            .ok_or(crate::Error::NoneSession)?;
+
+        let dir = self.path().app_data_dir()?.join(&session_id);
+        let matcher =
+            VoiceprintMatcher::new(self.speaker_candidates(Some(session_id.clone())).await?);
+
+        let mut words = std::mem::take(&mut session.words);
+        let words = tokio::task::spawn_blocking(move || {
+            crate::diarize::diarize_recording(dir, &mut words, &matcher)?;
+            Ok::<_, crate::Error>(words)
+        })
+        .await??;
+
+        self.db_replace_words(&session_id, words.clone()).await?;
+
+        Ok(words)
+    }
+
+    #[tracing::instrument(skip_all)]
+    async fn enroll_speaker(
+        &self,
+        session_id: String,
+        speaker_index: u8,
+        human_id: String,
+    ) -> Result<Vec<Word>, crate::Error> {
+        let mut session = self
+            .db_get_session(&session_id)
+            .await?
+            .ok_or(crate::Error::NoneSession)?;
+        let human = self
+            .db_get_human(&human_id)
+            .await?
+            .ok_or(crate::Error::NoneHuman)?;
+
+        let dir = self.path().app_data_dir()?.join(&session_id);
+        let words = session.words.clone();
+        let embedding = tokio::task::spawn_blocking(move || {
+            crate::diarize::speaker_voiceprint(dir, &words, speaker_index)
+        })
+        .await??;
+
+        self.db_upsert_voiceprint(hypr_db_user::Voiceprint::new(&human_id, embedding))
+            .await?;
+
+        let assigned = SpeakerIdentity::Assigned {
+            id: human.id.clone(),
+            label: speaker_label(&human),
+        };
+        for word in session.words.iter_mut() {
+            if word.speaker
+                == Some(SpeakerIdentity::Unassigned {
+                    index: speaker_index,
+                })
+            {
+                word.speaker = Some(assigned.clone());
+            }
+        }
+
+        self.db_replace_words(&session_id, session.words.clone())
+            .await?;
+
+        Ok(session.words)
+    }
+
+    #[tracing::instrument(skip(self, channel))]
+    async fn transcribe_session(
+        &self,
+        session_id: String,
+        model: crate::SupportedModel,
+        channel: Channel<i8>,
+    ) -> Result<Vec<Word>, crate::Error> {
+        if !self.is_model_downloaded(&model).await? {
+            return Err(crate::Error::ModelNotDownloaded);
+        }
+
+        self.db_get_session(&session_id)
+            .await?
+            .ok_or(crate::Error::NoneSession)?;
+
+        let config = match self.db_user_id().await? {
+            Some(user_id) => self.db_get_config(user_id).await?,
+            None => None,
+        };
+        let language: hypr_language::Language = config.as_ref().map_or_else(
+            || hypr_language::ISO639::Zh.into(),
+            |c| c.general.display_language.clone(),
+        );
+        let jargons = config.map_or_else(Vec::new, |c| c.general.jargons);
+
+        let static_prompt = format!(
+            "{} / {}:",
+            jargons.join(", "),
+            language
+                .text_transcript()
+                .unwrap_or("transcript".to_string())
+        );
+        let whisper_language = language.try_into().unwrap_or_else(|e| {
+            tracing::error!("convert_to_whisper_language: {e:?}");
+            hypr_whisper::Language::En
+        });
+
+        let data_dir = self.path().app_data_dir()?;
+        let model_path = model.model_path(&data_dir);
+        let dir = data_dir.join(&session_id);
+        let matcher =
+            VoiceprintMatcher::new(self.speaker_candidates(Some(session_id.clone())).await?);
+
+        // Unlike the realtime server, nothing else waits on this model, so it can use every core.
+        let n_threads = std::thread::available_parallelism().map_or(4, |n| n.get()) as i32;
+
+        let words = tokio::task::spawn_blocking(move || {
+            let whisper = hypr_whisper::local::Whisper::builder()
+                .model_path(model_path.to_str().unwrap())
+                .language(whisper_language)
+                .static_prompt(static_prompt)
+                .dtw_preset(model.dtw_preset())
+                .n_threads(n_threads)
+                .build();
+
+            crate::transcribe::transcribe_recording(dir, whisper, &matcher, |progress| {
+                let _ = channel.send((progress * 100.0) as i8);
+            })
+        })
+        .await??;
+
+        // Speakers the user assigned by hand are lost with the old words, `enroll_speaker` keeps
+        // them recognizable through voiceprints.
+        self.db_replace_words(&session_id, words.clone()).await?;
+
+        Ok(words)
+    }
+
+    #[tracing::instrument(skip(self, channel))]
+    async fn import_session(
+        &self,
+        path: String,
+        channel: Channel<i8>,
+    ) -> Result<hypr_db_user::Session, crate::Error> {
+        let user_id = self.db_user_id().await?.ok_or(crate::Error::NoneUser)?;
+        let model = self.get_current_model()?;
+
+        if !self.is_model_downloaded(&model).await? {
+            return Err(crate::Error::ModelNotDownloaded);
+        }
+
+        let path = std::path::PathBuf::from(path);
+        let title = path
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_default();
+
+        let session_id = uuid::Uuid::new_v4().to_string();
+        let dir = self.path().app_data_dir()?.join(&session_id);
+
+        let imported: Result<_, crate::Error> = async {
+            // Stored like a recording made by the listener, so everything that works on
+            // recordings (diarization, re-transcription, playback) works on imports too. Decoded
+            // and resampled packet by packet, since an hour of f32 audio is over half a gigabyte.
+            let dir = dir.clone();
+            tokio::task::spawn_blocking(move || {
+                let decoder = hypr_audio_utils::FileDecoder::open(&path)?;
+                let mut resampler = hypr_audio_utils::Resampler::new(decoder.sample_rate(), 16000);
+
+                let mut recorder = hypr_recorder::Recorder::open(
+                    &dir,
+                    hypr_recorder::RecorderConfig {
+                        sample_rate: 16000,
+                        channels: 1,
+                        ..Default::default()
+                    },
+                )?;
+                for samples in decoder {
+                    recorder.write(&resampler.process(&samples?))?;
+                }
+                recorder.write(&resampler.finish())?;
+                recorder.finalize()?;
+
+                Ok::<_, crate::Error>(())
+            })
+            .await??;
+
+            let now = chrono::Utc::now();
+            self.db_upsert_session(hypr_db_user::Session {
+                id: session_id.clone(),
+                user_id,
+                created_at: now,
+                visited_at: now,
+                calendar_event_id: None,
+                title,
+                raw_memo_html: "".to_string(),
+                enhanced_memo_html: None,
+                conversations: vec![],
+                words: vec![],
+            })
+            .await?;
+
+            self.transcribe_session(session_id.clone(), model, channel)
+                .await?;
+
+            self.db_get_session(&session_id)
+                .await?
+                .ok_or(crate::Error::NoneSession)
+        }
+        .await;
+
+        // A failed import leaves nothing behind, not even a half-written recording.
+        if imported.is_err() {
+            if let Err(e) = self.db_delete_session(&session_id).await {
+                tracing::warn!("import_cleanup_error: {:?}", e);
+            }
+            if dir.exists() {
+                if let Err(e) = std::fs::remove_dir_all(&dir) {
+                    tracing::warn!("import_cleanup_error: {:?}", e);
+                }
+            }
+        }
+
+        imported
+    }
+}
+
+fn speaker_label(human: &hypr_db_user::Human) -> String {
+    human
+        .full_name
+        .clone()
+        .or_else(|| human.email.clone())
+        .unwrap_or_default()
 }
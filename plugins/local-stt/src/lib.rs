@@ -2,12 +2,14 @@ use std::collections::HashMap;
 use tauri::{Manager, Wry};
 
 mod commands;
+mod diarize;
 mod error;
 mod ext;
 mod manager;
 mod model;
 pub mod server;
 mod store;
+mod transcribe;
 
 pub use error::*;
 pub use ext::*;
@@ -40,6 +42,10 @@ fn make_specta_builder<R: tauri::Runtime>() -> tauri_specta::Builder<R> {
             commands::get_current_model::<Wry>,
             commands::set_current_model::<Wry>,
             commands::list_supported_models,
+            commands::diarize_session::<Wry>,
+            commands::enroll_speaker::<Wry>,
+            commands::transcribe_session::<Wry>,
+            commands::import_session::<Wry>,
         ])
         .error_handling(tauri_specta::ErrorHandlingMode::Throw)
 }
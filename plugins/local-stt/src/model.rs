@@ -46,6 +46,17 @@ impl SupportedModel {
         }
     }
 
+    pub fn dtw_preset(&self) -> hypr_whisper::local::DtwModelPreset {
+        use hypr_whisper::local::DtwModelPreset;
+
+        match self {
+            SupportedModel::QuantizedTiny => DtwModelPreset::Tiny,
+            SupportedModel::QuantizedBase => DtwModelPreset::Base,
+            SupportedModel::QuantizedSmall => DtwModelPreset::Small,
+            SupportedModel::QuantizedLargeTurbo => DtwModelPreset::LargeV3Turbo,
+        }
+    }
+
     pub fn model_size(&self) -> u64 {
         match self {
             SupportedModel::QuantizedTiny => 43537433,
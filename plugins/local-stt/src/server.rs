@@ -14,19 +14,30 @@ use axum::{
     Router,
 };
 
-use futures_util::{SinkExt, StreamExt};
+use futures_util::{stream::SplitSink, SinkExt, StreamExt};
 use tower_http::cors::{self, CorsLayer};
 
-use hypr_chunker::ChunkerExt;
-use hypr_listener_interface::{ListenOutputChunk, ListenParams, Word};
+use hypr_chunker::{ChunkerExt, TimedSource};
+use hypr_listener_interface::{
+    AudioFormat, ListenOutputChunk, ListenParams, Word, AUDIO_FORMAT_HEADER,
+};
+use hypr_pyannote::local::{
+    diarization::{DiarizationConfig, Diarizer},
+    voiceprint::{VoiceprintCandidate, VoiceprintMatcher},
+};
 use hypr_ws_utils::WebSocketAudioSource;
 
+use crate::diarize::{assign_speakers, speaker_identities, to_i16};
 use crate::manager::{ConnectionGuard, ConnectionManager};
 
+const INTERIM_INTERVAL: std::time::Duration = std::time::Duration::from_secs(1);
+const INTERIM_MIN_SAMPLES: usize = 16 * 1000;
+
 #[derive(Default)]
 pub struct ServerStateBuilder {
     pub model_type: Option<crate::SupportedModel>,
     pub model_cache_dir: Option<PathBuf>,
+    pub speaker_candidates: Vec<VoiceprintCandidate>,
 }
 
 impl ServerStateBuilder {
@@ -40,10 +51,16 @@ impl ServerStateBuilder {
         self
     }
 
+    pub fn speaker_candidates(mut self, speaker_candidates: Vec<VoiceprintCandidate>) -> Self {
+        self.speaker_candidates = speaker_candidates;
+        self
+    }
+
     pub fn build(self) -> ServerState {
         ServerState {
             model_type: self.model_type.unwrap(),
             model_cache_dir: self.model_cache_dir.unwrap(),
+            voiceprint_matcher: VoiceprintMatcher::new(self.speaker_candidates),
             connection_manager: ConnectionManager::default(),
         }
     }
@@ -53,6 +70,7 @@ impl ServerStateBuilder {
 pub struct ServerState {
     model_type: crate::SupportedModel,
     model_cache_dir: PathBuf,
+    voiceprint_matcher: VoiceprintMatcher,
     connection_manager: ConnectionManager,
 }
 
@@ -110,9 +128,14 @@ async fn listen(
 ) -> Result<impl IntoResponse, StatusCode> {
     let guard = state.connection_manager.acquire_connection();
 
-    Ok(ws.on_upgrade(move |socket| async move {
-        websocket_with_model(socket, params, state, guard).await
-    }))
+    // Lets the client know binary audio is understood. Without it, clients fall back to JSON.
+    let audio_format = [(AUDIO_FORMAT_HEADER, params.audio_format.as_str())];
+    Ok((
+        audio_format,
+        ws.on_upgrade(move |socket| async move {
+            websocket_with_model(socket, params, state, guard).await
+        }),
+    ))
 }
 
 async fn websocket_with_model(
@@ -135,58 +158,141 @@ async fn websocket_with_model(
         .language(language)
         .static_prompt(&params.static_prompt)
         .dynamic_prompt(&params.dynamic_prompt)
+        .dtw_preset(model_type.dtw_preset())
         .build();
 
-    websocket(socket, model, guard).await;
+    tracing::info!("listen_resume_id: {:?}", params.resume_id);
+    let offset = std::time::Duration::from_millis(params.offset_ms);
+    websocket(
+        socket,
+        model,
+        state.voiceprint_matcher,
+        offset,
+        params.audio_format,
+        guard,
+    )
+    .await;
 }
 
 #[tracing::instrument(skip_all)]
-async fn websocket(socket: WebSocket, model: hypr_whisper::local::Whisper, guard: ConnectionGuard) {
+async fn websocket(
+    socket: WebSocket,
+    model: hypr_whisper::local::Whisper,
+    voiceprint_matcher: VoiceprintMatcher,
+    offset: std::time::Duration,
+    audio_format: AudioFormat,
+    guard: ConnectionGuard,
+) {
     let (mut ws_sender, ws_receiver) = socket.split();
-    let mut stream = {
-        let audio_source = WebSocketAudioSource::new(ws_receiver, 16 * 1000);
-        let chunked =
-            audio_source.chunks(hypr_chunker::RMS::new(), std::time::Duration::from_secs(15));
-        hypr_whisper::local::TranscribeChunkedAudioStreamExt::transcribe(chunked, model)
+    let mut chunks = {
+        let audio_source = match WebSocketAudioSource::new(ws_receiver, 16 * 1000, audio_format) {
+            Ok(audio_source) => audio_source,
+            Err(e) => {
+                tracing::error!("websocket_audio_source_error: {:?}", e);
+                return;
+            }
+        };
+
+        let predictor: Box<dyn hypr_chunker::Predictor> = match hypr_chunker::Silero::new() {
+            Ok(silero) => Box::new(silero),
+            Err(e) => {
+                tracing::warn!("silero_unavailable_fallback_to_rms: {:?}", e);
+                Box::new(hypr_chunker::RMS::new())
+            }
+        };
+
+        audio_source
+            .chunks(predictor, std::time::Duration::from_secs(15))
+            .offset(offset)
     };
 
+    let (job_tx, job_rx) = std::sync::mpsc::channel();
+    let (transcript_tx, mut transcript_rx) = tokio::sync::mpsc::unbounded_channel();
+    spawn_worker(model, voiceprint_matcher, job_rx, transcript_tx);
+    // Dropped once the audio ends, so the worker exits after the jobs already queued.
+    let mut job_tx = Some(job_tx);
+
+    let mut interim_interval = tokio::time::interval(INTERIM_INTERVAL);
+    interim_interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+    // Length of the pending audio when the last partial job was queued, 0 if there was none.
+    let mut interim_len = 0;
+    // Whether a partial job is queued or running. Ticks are skipped until it's done, so slow
+    // transcription doesn't pile up work.
+    let mut partial_in_flight = false;
+    // Whether interim results were sent since the last final one.
+    let mut interim_sent = false;
+    let mut revision = 0;
+
     loop {
         tokio::select! {
             _ = guard.cancelled() => {
                 tracing::info!("websocket_cancelled_by_new_connection");
                 break;
             }
-            chunk_opt = stream.next() => {
-                let Some(chunk) = chunk_opt else { break };
-                let text = chunk.text().to_string();
-                let start = chunk.start() as u64;
-                let duration = chunk.duration() as u64;
-                let confidence = chunk.confidence();
-
-                let confidence_threshold = 0.1;
-                if confidence < confidence_threshold {
-                    tracing::warn!(confidence, "skipping_transcript: {}", text);
+            _ = interim_interval.tick(), if job_tx.is_some() && !partial_in_flight => {
+                let (pending_start, pending) = chunks.pending();
+                if pending.len() < INTERIM_MIN_SAMPLES || pending.len() == interim_len {
                     continue;
                 }
+                interim_len = pending.len();
 
-                let data = ListenOutputChunk {
-                    words: text
-                        .split_whitespace()
-                        .filter(|w| !w.is_empty())
-                        .map(|w| Word {
-                            text: w.trim().to_string(),
-                            speaker: None,
-                            start_ms: Some(start),
-                            end_ms: Some(start + duration),
-                            confidence: Some(confidence),
+                if let Some(job_tx) = &job_tx {
+                    partial_in_flight = job_tx
+                        .send(Job::Partial {
+                            start: pending_start,
+                            samples: pending.to_vec(),
                         })
-                        .collect(),
+                        .is_ok();
+                }
+            }
+            chunk_opt = chunks.next(), if job_tx.is_some() => {
+                let Some(chunk) = chunk_opt else {
+                    job_tx = None;
+                    continue;
                 };
+                let chunk_start = chunk.start();
+
+                let samples: Vec<f32> = chunk.collect();
+                if samples.is_empty() {
+                    continue;
+                }
+                interim_len = 0;
+
+                if let Some(job_tx) = &job_tx {
+                    let _ = job_tx.send(Job::Final {
+                        start: chunk_start,
+                        samples,
+                    });
+                }
+            }
+            transcript = transcript_rx.recv() => {
+                let Some(Transcript { is_final, words }) = transcript else { break };
+                if !is_final {
+                    partial_in_flight = false;
+                }
+                let Some(words) = words else { continue };
 
-                let msg = Message::Text(serde_json::to_string(&data).unwrap().into());
-                if let Err(e) = ws_sender.send(msg).await {
+                if is_final {
+                    // An empty final result is still sent if it has to replace interim ones.
+                    if words.is_empty() && !interim_sent {
+                        continue;
+                    }
+                    interim_sent = false;
+                } else {
+                    interim_sent = true;
+                }
+
+                revision += 1;
+                let data = ListenOutputChunk {
+                    words,
+                    is_final,
+                    revision,
+                };
+
+                if let Err(e) = send_chunk(&mut ws_sender, &data).await {
                     tracing::warn!("websocket_send_error: {}", e);
-                    break;
+                    let _ = ws_sender.close().await;
+                    return;
                 }
             }
         }
@@ -194,3 +300,158 @@ async fn websocket(socket: WebSocket, model: hypr_whisper::local::Whisper, guard
 
     let _ = ws_sender.close().await;
 }
+
+enum Job {
+    /// Audio the chunker hasn't finished yet, transcribed for an interim result.
+    Partial {
+        start: std::time::Duration,
+        samples: Vec<f32>,
+    },
+    /// A finished chunk, transcribed and diarized for a final result.
+    Final {
+        start: std::time::Duration,
+        samples: Vec<f32>,
+    },
+}
+
+struct Transcript {
+    is_final: bool,
+    /// `None` if transcribing failed.
+    words: Option<Vec<Word>>,
+}
+
+/// Runs Whisper and the diarizer on their own thread, so they don't block the runtime.
+/// Jobs are handled in order, and the thread exits once `jobs` is dropped.
+fn spawn_worker(
+    mut model: hypr_whisper::local::Whisper,
+    voiceprint_matcher: VoiceprintMatcher,
+    jobs: std::sync::mpsc::Receiver<Job>,
+    transcripts: tokio::sync::mpsc::UnboundedSender<Transcript>,
+) {
+    std::thread::spawn(move || {
+        let mut diarizer = Diarizer::new(16 * 1000, DiarizationConfig::default())
+            .map_err(|e| tracing::warn!("diarizer_unavailable: {:?}", e))
+            .ok();
+
+        while let Ok(job) = jobs.recv() {
+            let transcript = match job {
+                Job::Partial { start, samples } => Transcript {
+                    is_final: false,
+                    words: match model.transcribe_partial(&samples) {
+                        Ok(segments) => Some(segments_to_words(segments, start)),
+                        Err(e) => {
+                            tracing::error!("transcribe_partial_error: {:?}", e);
+                            None
+                        }
+                    },
+                },
+                Job::Final { start, samples } => Transcript {
+                    is_final: true,
+                    words: transcribe_chunk(
+                        &mut model,
+                        diarizer.as_mut(),
+                        &voiceprint_matcher,
+                        start,
+                        &samples,
+                    ),
+                },
+            };
+
+            if transcripts.send(transcript).is_err() {
+                break;
+            }
+        }
+    });
+}
+
+fn transcribe_chunk(
+    model: &mut hypr_whisper::local::Whisper,
+    diarizer: Option<&mut Diarizer>,
+    voiceprint_matcher: &VoiceprintMatcher,
+    chunk_start: std::time::Duration,
+    samples: &[f32],
+) -> Option<Vec<Word>> {
+    let segments = match model.transcribe(samples) {
+        Ok(segments) => segments,
+        Err(e) => {
+            tracing::error!("transcribe_error: {:?}", e);
+            return None;
+        }
+    };
+
+    let (turns, identities) = match diarizer {
+        Some(d) => match d.process(&to_i16(samples)) {
+            Ok(turns) => {
+                let identities = speaker_identities(voiceprint_matcher, &d.speakers());
+                (turns, identities)
+            }
+            Err(e) => {
+                tracing::warn!("diarize_error: {:?}", e);
+                (vec![], vec![])
+            }
+        },
+        None => (vec![], vec![]),
+    };
+
+    let mut words = segments_to_words(segments, chunk_start);
+    // Diarized turns are relative to the chunk.
+    assign_speakers(
+        &mut words,
+        &turns,
+        &identities,
+        chunk_start.as_millis() as u64,
+    );
+
+    Some(words)
+}
+
+async fn send_chunk(
+    ws_sender: &mut SplitSink<WebSocket, Message>,
+    data: &ListenOutputChunk,
+) -> Result<(), axum::Error> {
+    let msg = Message::Text(serde_json::to_string(data).unwrap().into());
+    ws_sender.send(msg).await
+}
+
+/// Words of the confident segments, on the session timeline. `start` is where the transcribed
+/// audio starts in the session.
+pub(crate) fn segments_to_words(
+    segments: Vec<hypr_whisper::local::Segment>,
+    start: std::time::Duration,
+) -> Vec<Word> {
+    let confidence_threshold = 0.1;
+
+    let mut words = Vec::new();
+    for mut segment in segments {
+        segment.offset(start.as_secs_f32());
+
+        let confidence = segment.confidence();
+        if confidence < confidence_threshold {
+            tracing::warn!(confidence, "skipping_transcript: {}", segment.text());
+            continue;
+        }
+
+        if segment.words().is_empty() {
+            let start_ms = (segment.start() * 1000.0) as u64;
+            let end_ms = (segment.end() * 1000.0) as u64;
+
+            words.extend(segment.text().split_whitespace().map(|w| Word {
+                text: w.to_string(),
+                speaker: None,
+                start_ms: Some(start_ms),
+                end_ms: Some(end_ms),
+                confidence: Some(confidence),
+            }));
+        } else {
+            words.extend(segment.words().iter().map(|w| Word {
+                text: w.text.clone(),
+                speaker: None,
+                start_ms: Some((w.start * 1000.0) as u64),
+                end_ms: Some((w.end * 1000.0) as u64),
+                confidence: Some(w.confidence),
+            }));
+        }
+    }
+
+    words
+}
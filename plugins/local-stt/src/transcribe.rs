@@ -0,0 +1,111 @@
+use std::ops::Range;
+use std::path::Path;
+
+use hypr_chunker::Predictor;
+use hypr_listener_interface::Word;
+use hypr_pyannote::local::voiceprint::VoiceprintMatcher;
+
+const SAMPLE_RATE: u32 = 16 * 1000;
+// Whisper sees 30 seconds at a time, so chunks stay under that with room to end at a pause.
+const MAX_CHUNK_SECS: u32 = 25;
+const CUT_SEARCH_SECS: u32 = 5;
+const CUT_WINDOW_MS: u32 = 100;
+
+/// Transcribes the whole recording in `dir` as fast as `model` runs, then diarizes it if
+/// possible. `on_progress` gets the fraction of the recording transcribed so far.
+pub fn transcribe_recording(
+    dir: impl AsRef<Path>,
+    mut model: hypr_whisper::local::Whisper,
+    matcher: &VoiceprintMatcher,
+    on_progress: impl Fn(f32),
+) -> Result<Vec<Word>, crate::Error> {
+    let recording = hypr_recorder::read_mono(dir)?;
+    let samples = hypr_audio_utils::resample(recording.samples, recording.sample_rate, SAMPLE_RATE);
+
+    let predictor: Box<dyn Predictor> = match hypr_chunker::Silero::new() {
+        Ok(silero) => Box::new(silero),
+        Err(e) => {
+            tracing::warn!("silero_unavailable_fallback_to_rms: {:?}", e);
+            Box::new(hypr_chunker::RMS::new())
+        }
+    };
+
+    let mut words = Vec::new();
+    for range in split_at_pauses(&samples) {
+        let chunk = &samples[range.clone()];
+
+        // Whisper makes up text for silence.
+        if predictor.speech_start(chunk)?.is_some() {
+            let start = std::time::Duration::from_secs_f64(range.start as f64 / SAMPLE_RATE as f64);
+            let segments = model.transcribe(chunk)?;
+            words.extend(crate::server::segments_to_words(segments, start));
+        }
+
+        on_progress(range.end as f32 / samples.len() as f32);
+    }
+
+    if let Err(e) = crate::diarize::diarize_samples(SAMPLE_RATE, &samples, &mut words, matcher) {
+        tracing::warn!("diarize_error: {:?}", e);
+    }
+
+    Ok(words)
+}
+
+/// Chunks of at most `MAX_CHUNK_SECS`, each ending at the quietest spot of its last seconds so
+/// words are rarely cut in half.
+fn split_at_pauses(samples: &[f32]) -> Vec<Range<usize>> {
+    let max_len = (MAX_CHUNK_SECS * SAMPLE_RATE) as usize;
+    let search_len = (CUT_SEARCH_SECS * SAMPLE_RATE) as usize;
+    let window = (CUT_WINDOW_MS * SAMPLE_RATE / 1000) as usize;
+
+    let energy = |start: usize| {
+        samples[start..start + window]
+            .iter()
+            .map(|s| s * s)
+            .sum::<f32>()
+    };
+
+    let mut ranges = Vec::new();
+    let mut start = 0;
+
+    while samples.len() - start > max_len {
+        let end = start + max_len;
+        let cut = (end - search_len..end - window)
+            .step_by(window)
+            .min_by(|a, b| energy(*a).total_cmp(&energy(*b)))
+            .map(|i| i + window / 2)
+            .unwrap();
+
+        ranges.push(start..cut);
+        start = cut;
+    }
+
+    if start < samples.len() {
+        ranges.push(start..samples.len());
+    }
+    ranges
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_at_pauses() {
+        let ms = |ms: usize| ms * SAMPLE_RATE as usize / 1000;
+
+        // Loud everywhere but a pause at 22s.
+        let mut samples = vec![0.5; ms(60_000)];
+        samples[ms(22_000)..ms(22_200)].fill(0.0);
+
+        let ranges = split_at_pauses(&samples);
+        assert_eq!(ranges.len(), 3);
+        assert_eq!(ranges[0], 0..ms(22_050));
+        assert_eq!(ranges[1].start, ms(22_050));
+        assert!(ranges[1].len() <= ms(MAX_CHUNK_SECS as usize * 1000));
+        assert_eq!(ranges[2].end, samples.len());
+
+        assert_eq!(split_at_pauses(&samples[..ms(3_000)]), vec![0..ms(3_000)]);
+        assert!(split_at_pauses(&[]).is_empty());
+    }
+}
@@ -33,10 +33,11 @@ pub async fn audio_exist<R: tauri::Runtime>(
     session_id: String,
 ) -> Result<bool, String> {
     let data_dir = app.path().app_data_dir().unwrap();
-    let audio_path = data_dir.join(session_id).join("audio.wav");
+    let session_dir = data_dir.join(session_id);
 
-    let v = std::fs::exists(audio_path).map_err(|e| e.to_string())?;
-    Ok(v)
+    // Also picks up `audio.wav` from sessions recorded before segmenting.
+    let segments = hypr_recorder::list_segments(&session_dir).map_err(|e| e.to_string())?;
+    Ok(!segments.is_empty())
 }
 
 #[tauri::command]
@@ -46,7 +47,10 @@ pub async fn audio_open<R: tauri::Runtime>(
     session_id: String,
 ) -> Result<(), String> {
     let data_dir = app.path().app_data_dir().unwrap();
-    let audio_path = data_dir.join(session_id).join("audio.wav");
+    let session_dir = data_dir.join(session_id);
+
+    let segments = hypr_recorder::list_segments(&session_dir).map_err(|e| e.to_string())?;
+    let audio_path = segments.into_iter().next().unwrap_or(session_dir);
 
     app.opener()
         .reveal_item_in_dir(&audio_path)